@@ -0,0 +1,372 @@
+#![allow(dead_code)]
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::config::constants;
+use crate::notifiers::{Alert, DeliveryProgress};
+
+/// An alert waiting to be delivered to a single notifier.
+#[derive(Debug, Clone)]
+pub struct QueuedAlert {
+    /// Unique identifier, ordered by the time the entry was queued.
+    pub id: String,
+
+    /// Name of the notifier the alert is destined for.
+    pub notifier: String,
+
+    /// Number of failed delivery attempts so far.
+    pub attempts: u32,
+
+    /// Earliest time of the next delivery attempt.
+    pub next_attempt: chrono::DateTime<chrono::Utc>,
+
+    /// The alert itself.
+    pub alert: Alert,
+
+    /// Steps of the delivery that already succeeded.
+    pub progress: DeliveryProgress,
+
+    /// End of the notifier's quiet hours for a held alert. Held alerts are
+    /// not delivered by the worker but sent as a summary once this time
+    /// has passed, see [`AlertQueue::released`].
+    pub not_before: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl QueuedAlert {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "id": self.id,
+            "notifier": self.notifier,
+            "attempts": self.attempts,
+            "next_attempt": self.next_attempt.to_rfc3339(),
+            "alert": self.alert.to_json(),
+            "completed_steps": self.progress.steps().collect::<Vec<_>>(),
+            "not_before": self.not_before.map(|t| t.to_rfc3339()),
+        })
+    }
+
+    fn from_json(value: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            id: value["id"].as_str()?.to_string(),
+            notifier: value["notifier"].as_str()?.to_string(),
+            attempts: value["attempts"].as_u64()? as u32,
+            next_attempt: chrono::DateTime::parse_from_rfc3339(value["next_attempt"].as_str()?)
+                .ok()?
+                .with_timezone(&chrono::Utc),
+            alert: Alert::from_json(&value["alert"])?,
+            progress: DeliveryProgress::new(
+                value["completed_steps"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|step| step.as_str().map(str::to_string)),
+            ),
+            not_before: value["not_before"]
+                .as_str()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&chrono::Utc)),
+        })
+    }
+}
+
+/// Persistent queue of alerts awaiting delivery.
+///
+/// Every entry is stored as its own JSON file in the queue directory, so
+/// pending alerts survive a restart and are picked up again on the next
+/// [`AlertQueue::open`]. Files are written to a temporary name and renamed
+/// into place, so a crash never leaves a half-written entry behind.
+pub struct AlertQueue {
+    dir: PathBuf,
+    entries: Mutex<BTreeMap<String, QueuedAlert>>,
+    changed: Condvar,
+    sequence: AtomicU64,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl AlertQueue {
+    /// Open the queue stored in `dir`, loading all pending entries.
+    ///
+    /// Entries that cannot be parsed are logged and removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or read.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create alert queue at {}", dir.display()))?;
+
+        let mut entries = BTreeMap::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let parsed = fs::read(&path)
+                .ok()
+                .and_then(|data| serde_json::from_slice(&data).ok())
+                .and_then(|value| QueuedAlert::from_json(&value));
+            match parsed {
+                Some(queued) => {
+                    entries.insert(queued.id.clone(), queued);
+                }
+                None => {
+                    warn!("Removing unreadable alert queue entry {}", path.display());
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+
+        if !entries.is_empty() {
+            debug!(
+                "Loaded {} pending alert(s) from {}",
+                entries.len(),
+                dir.display()
+            );
+        }
+
+        Ok(Self {
+            dir,
+            entries: Mutex::new(entries),
+            changed: Condvar::new(),
+            sequence: AtomicU64::new(0),
+            base_delay: Duration::from_secs(constants::ALERT_RETRY_BASE_DELAY_SECONDS),
+            max_delay: Duration::from_secs(constants::ALERT_RETRY_MAX_DELAY_SECONDS),
+        })
+    }
+
+    /// Override the exponential backoff delays.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_delay` - Delay after the first failed attempt, doubled after every further failure
+    /// * `max_delay` - Upper bound of the delay between attempts
+    pub fn with_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Get the directory the queue is stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get the number of entries pending delivery, not counting held ones.
+    pub fn len(&self) -> usize {
+        self.lock()
+            .values()
+            .filter(|entry| entry.not_before.is_none())
+            .count()
+    }
+
+    /// Check whether no entries are pending delivery.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of alerts held until the end of quiet hours.
+    pub fn held_len(&self) -> usize {
+        self.lock()
+            .values()
+            .filter(|entry| entry.not_before.is_some())
+            .count()
+    }
+
+    /// Get the number of entries pending delivery per notifier.
+    pub fn depth_by_notifier(&self) -> BTreeMap<String, usize> {
+        let mut depth = BTreeMap::new();
+        for entry in self.lock().values().filter(|entry| entry.not_before.is_none()) {
+            *depth.entry(entry.notifier.clone()).or_insert(0) += 1;
+        }
+        depth
+    }
+
+    /// Queue an alert for delivery to a notifier and return the entry's identifier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be written to disk.
+    pub fn push(&self, notifier: &str, alert: &Alert) -> Result<String> {
+        self.insert(notifier, alert, None)
+    }
+
+    /// Hold an alert for a notifier until the end of its quiet hours and
+    /// return the entry's identifier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be written to disk.
+    pub fn hold(
+        &self,
+        notifier: &str,
+        alert: &Alert,
+        not_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<String> {
+        self.insert(notifier, alert, Some(not_before))
+    }
+
+    fn insert(
+        &self,
+        notifier: &str,
+        alert: &Alert,
+        not_before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<String> {
+        let now = chrono::Utc::now();
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) % 1_000_000;
+        let queued = QueuedAlert {
+            id: format!("{:013}-{:06}", now.timestamp_millis(), sequence),
+            notifier: notifier.to_string(),
+            attempts: 0,
+            next_attempt: now,
+            alert: alert.clone(),
+            progress: DeliveryProgress::default(),
+            not_before,
+        };
+
+        self.write(&queued)?;
+        let id = queued.id.clone();
+        self.lock().insert(id.clone(), queued);
+        self.changed.notify_all();
+        Ok(id)
+    }
+
+    /// Get all entries whose next attempt is due, oldest first. Held
+    /// entries are never due.
+    pub fn due(&self) -> Vec<QueuedAlert> {
+        let now = chrono::Utc::now();
+        self.lock()
+            .values()
+            .filter(|entry| entry.not_before.is_none() && entry.next_attempt <= now)
+            .cloned()
+            .collect()
+    }
+
+    /// Get all held entries whose quiet hours are over, oldest first.
+    ///
+    /// The entries stay queued until they are removed, so they survive a
+    /// restart until their summary has been queued.
+    pub fn released(&self) -> Vec<QueuedAlert> {
+        let now = chrono::Utc::now();
+        self.lock()
+            .values()
+            .filter(|entry| entry.not_before.is_some_and(|not_before| not_before <= now))
+            .cloned()
+            .collect()
+    }
+
+    /// Remove a delivered or abandoned entry.
+    pub fn remove(&self, id: &str) {
+        self.lock().remove(id);
+        if let Err(e) = fs::remove_file(self.path(id))
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("Failed to remove alert queue entry {}: {}", id, e);
+        }
+    }
+
+    /// Remove all entries matching a predicate, e.g. superseded updates.
+    pub fn discard(&self, predicate: impl Fn(&QueuedAlert) -> bool) {
+        let ids: Vec<String> = self
+            .lock()
+            .values()
+            .filter(|entry| predicate(entry))
+            .map(|entry| entry.id.clone())
+            .collect();
+        for id in ids {
+            self.remove(&id);
+        }
+    }
+
+    /// Record a failed attempt and schedule the next one with exponential backoff.
+    ///
+    /// Returns the number of failed attempts so far.
+    pub fn record_failure(&self, id: &str) -> u32 {
+        self.reschedule(id, true, |attempts| self.backoff(attempts))
+    }
+
+    /// Store the steps of a partially delivered entry that succeeded, so
+    /// retries (also after a restart) skip them.
+    pub fn save_progress(&self, id: &str, progress: &DeliveryProgress) {
+        let updated = {
+            let mut entries = self.lock();
+            let Some(entry) = entries.get_mut(id) else {
+                return;
+            };
+            entry.progress = progress.clone();
+            entry.clone()
+        };
+
+        if let Err(e) = self.write(&updated) {
+            warn!("Failed to update alert queue entry {}: {:#}", id, e);
+        }
+    }
+
+    /// Postpone an entry without counting it as a failed attempt, e.g. when
+    /// the backend asked us to slow down.
+    pub fn postpone(&self, id: &str, delay: Duration) {
+        self.reschedule(id, false, |_| delay);
+    }
+
+    /// Block until an entry is queued or the timeout expires.
+    pub fn wait(&self, timeout: Duration) {
+        let entries = self.lock();
+        let _ = self.changed.wait_timeout(entries, timeout);
+    }
+
+    /// Get the delay before the next attempt after `attempts` failures.
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    fn reschedule(&self, id: &str, failed: bool, delay: impl FnOnce(u32) -> Duration) -> u32 {
+        let updated = {
+            let mut entries = self.lock();
+            let Some(entry) = entries.get_mut(id) else {
+                return 0;
+            };
+            if failed {
+                entry.attempts += 1;
+            }
+            let delay = chrono::Duration::from_std(delay(entry.attempts))
+                .unwrap_or_else(|_| chrono::Duration::days(1));
+            entry.next_attempt = chrono::Utc::now() + delay;
+            entry.clone()
+        };
+
+        if let Err(e) = self.write(&updated) {
+            warn!("Failed to update alert queue entry {}: {:#}", id, e);
+        }
+        updated.attempts
+    }
+
+    fn write(&self, queued: &QueuedAlert) -> Result<()> {
+        let path = self.path(&queued.id);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, queued.to_json().to_string())
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, QueuedAlert>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -1,81 +1,462 @@
-use anyhow::Result;
-use reqwest::blocking::multipart;
+use log::{debug, error, info, warn};
 use serde_json::json;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// Discord alert service for sending rich embed notifications.
+use crate::alert_queue::AlertQueue;
+use crate::config::{Config, constants};
+use crate::digest::FleetDigest;
+use crate::error::{AlertError, ErrorChain};
+use crate::metrics;
+use crate::notifiers::{self, Alert, AlertDetection, AlertKind, Notifier, PrintJob, RateLimited};
+use crate::printer::PauseOutcome;
+use crate::printer_status::PrinterStatus;
+use crate::report::PrintReport;
+use crate::routing::{self, RoutingRules};
+use crate::templates::MessageTemplates;
+
+/// Alert service for sending notifications to all configured backends.
+///
+/// This module builds backend-neutral alerts about print failures, system
+/// status changes, and other important events in the print monitoring system,
+/// and fans each alert out to every configured [`Notifier`].
+///
+/// With an [`AlertQueue`] attached, alerts are written to disk and delivered
+/// by a background worker (see [`AlertService::start_worker`]) that retries
+/// with exponential backoff, so an unreachable or rate-limiting backend does
+/// not lose alerts or stall the monitoring loop.
+///
+/// [`RoutingRules`] decide which notifiers receive which alerts, and hold
+/// non-critical alerts during a notifier's quiet hours until
+/// [`AlertService::flush`] sends them as a summary. Held alerts are stored
+/// in the queue until the end of the quiet hours, so they survive a restart.
+///
+/// Alert texts come from localized [`MessageTemplates`]. Images are
+/// compressed to each notifier's upload limit, or dropped with a note if
+/// they cannot be made small enough.
 ///
-/// This module handles sending formatted Discord alerts about print failures,
-/// system status changes, and other important events in the print monitoring system.
+/// Failures are reported as [`AlertError`]s; a failed delivery holds the
+/// error of every notifier that could not be reached.
 pub struct AlertService {
-    webhook_url: String,
+    notifiers: Arc<Vec<Box<dyn Notifier>>>,
+    queue: Option<Arc<AlertQueue>>,
+    routing: RoutingRules,
+    held: Mutex<BTreeMap<String, Vec<Alert>>>,
+    templates: MessageTemplates,
+    upload_limits: BTreeMap<String, usize>,
+}
+
+/// Alerts for a notifier whose quiet hours are over, waiting for their summary.
+#[derive(Default)]
+struct ReleasedAlerts {
+    /// Alerts held in memory, put back if the summary cannot be sent.
+    held: Vec<Alert>,
+    /// Alerts held in the queue.
+    queued: Vec<Alert>,
+    /// Queue entries of the queued alerts, removed once the summary is sent.
+    ids: Vec<String>,
 }
 
 impl AlertService {
-    /// Create a new AlertService with the provided Discord webhook URL.
+    /// Create a new AlertService delivering alerts to the provided notifiers.
     ///
     /// # Arguments
     ///
-    /// * `webhook_url` - A valid Discord webhook URL
+    /// * `notifiers` - The backends every alert is sent to
     ///
-    pub fn new(webhook_url: String) -> Self {
-        Self { webhook_url }
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        Self {
+            notifiers: Arc::new(notifiers),
+            queue: None,
+            routing: RoutingRules::default(),
+            held: Mutex::new(BTreeMap::new()),
+            templates: MessageTemplates::default(),
+            upload_limits: BTreeMap::new(),
+        }
+    }
+
+    /// Render alert texts with the given templates instead of the bundled English ones.
+    pub fn with_templates(mut self, templates: MessageTemplates) -> Self {
+        self.templates = templates;
+        self
     }
 
-    /// Send a Discord alert with rich embed formatting.
+    /// Override the image upload limits of notifiers, in bytes per notifier name.
+    pub fn with_upload_limits(mut self, upload_limits: BTreeMap<String, usize>) -> Self {
+        self.upload_limits = upload_limits;
+        self
+    }
+
+    /// Route alerts to notifiers according to the given rules.
+    pub fn with_routing(mut self, routing: RoutingRules) -> Self {
+        self.routing = routing;
+        self
+    }
+
+    /// Deliver alerts through a persistent queue instead of sending them directly.
     ///
-    /// Creates a rich embed message with the specified title, description, color,
-    /// and emoji, then sends it to the configured Discord webhook.
+    /// Alerts are only delivered once the worker has been started with
+    /// [`AlertService::start_worker`].
+    pub fn with_queue(mut self, queue: AlertQueue) -> Self {
+        self.queue = Some(Arc::new(queue));
+        self
+    }
+
+    /// Create a new AlertService with the notifiers enabled by the configuration.
     ///
-    /// # Arguments
+    /// Alerts are queued in the `alert_queue` directory below the output directory.
     ///
-    /// * `title` - The title of the alert
-    /// * `description` - The main content of the alert (supports Markdown)
-    /// * `color` - The color of the embed sidebar (as a hex value, e.g., 0xFF0000 for red)
-    /// * `emoji` - An emoji to display with the title
+    /// # Errors
+    ///
+    /// Returns an error if a configured notifier, the queue or the message
+    /// templates cannot be set up, or if the routing rules or upload limits
+    /// reference a notifier that is not configured.
+    pub fn from_config(config: &Config) -> Result<Self, AlertError> {
+        let setup_failed = |e: anyhow::Error| AlertError::SetupFailed { source: e.into() };
+        let service = Self::new(notifiers::from_config(config).map_err(setup_failed)?);
+
+        let names = service.notifier_names();
+        let referenced = config
+            .routing
+            .destinations()
+            .into_iter()
+            .chain(config.upload_limits.keys().map(String::as_str));
+        for destination in referenced {
+            if !names.contains(&destination) {
+                return Err(AlertError::UnknownNotifier {
+                    name: destination.to_string(),
+                    configured: names.iter().map(|name| name.to_string()).collect(),
+                });
+            }
+        }
+
+        let templates = MessageTemplates::load(&config.locale, config.templates_dir.as_deref())
+            .map_err(setup_failed)?;
+        let queue = AlertQueue::open(config.output_dir.join(constants::ALERT_QUEUE_DIR))
+            .map_err(setup_failed)?;
+        Ok(service
+            .with_routing(config.routing.clone())
+            .with_upload_limits(config.upload_limits.clone())
+            .with_templates(templates)
+            .with_queue(queue))
+    }
+
+    /// Get the names of the configured notifiers.
+    pub fn notifier_names(&self) -> Vec<&str> {
+        self.notifiers.iter().map(|n| n.name()).collect()
+    }
+
+    /// Get the number of alerts waiting in the queue.
+    pub fn queue_depth(&self) -> usize {
+        self.queue.as_ref().map_or(0, |queue| queue.len())
+    }
+
+    /// Start the background worker delivering queued alerts.
+    ///
+    /// Does nothing if the service has no queue.
+    pub fn start_worker(&self) -> Result<Option<thread::JoinHandle<()>>, AlertError> {
+        let Some(queue) = self.queue.clone() else {
+            return Ok(None);
+        };
+
+        let pending = queue.len();
+        if pending > 0 {
+            info!("Resuming delivery of {} queued alert(s)", pending);
+        }
+
+        let notifiers = self.notifiers.clone();
+        let handle = thread::Builder::new()
+            .name("alert-queue".to_string())
+            .spawn(move || deliver_queued_alerts(&queue, &notifiers))
+            .map_err(|e| AlertError::SetupFailed { source: e.into() })?;
+        Ok(Some(handle))
+    }
+
+    /// Get the number of alerts held back by quiet hours.
+    pub fn held_alerts(&self) -> usize {
+        self.lock_held().values().map(Vec::len).sum::<usize>()
+            + self.queue.as_ref().map_or(0, |queue| queue.held_len())
+    }
+
+    /// Send an alert to every notifier its routing rules allow.
+    ///
+    /// Alerts for notifiers in their quiet hours are held unless critical,
+    /// and progress alerts only go to notifiers that update messages in place.
+    /// See [`AlertService::deliver`] for how the remaining notifiers are reached.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The HTTP request fails
-    /// - The Discord API returns an error status
-    /// - JSON serialization fails
+    /// Returns an error if delivery to any notifier failed.
+    pub fn dispatch(&self, alert: &Alert) -> Result<(), AlertError> {
+        let now = chrono::Local::now();
+        let mut destinations = Vec::new();
+
+        for notifier in self.notifiers.iter() {
+            let name = notifier.name();
+            if alert.kind == AlertKind::PrintProgress && !notifier.updates_in_place() {
+                continue;
+            }
+            if !self.routing.accepts(name, alert) {
+                continue;
+            }
+            if self.routing.holds(name, alert, now.time()) {
+                self.hold(name, alert, now);
+                continue;
+            }
+            destinations.push(notifier.as_ref());
+        }
+
+        self.deliver(alert, &destinations)
+    }
+
+    /// Hold an alert for a notifier during its quiet hours.
     ///
-    pub fn send_alert(
-        &self,
-        title: &str,
-        description: &str,
-        color: u32,
-        emoji: &str,
-    ) -> Result<()> {
-        let timestamp = chrono::Utc::now().to_rfc3339();
-
-        let embed = json!({
-            "embeds": [{
-                "title": format!("{} {}", emoji, title),
-                "description": description,
-                "color": color,
-                "timestamp": timestamp,
-                "footer": {
-                    "text": "Print Guardian"
+    /// With a queue, the alert is stored until the end of the quiet hours.
+    /// Without one, or if it cannot be written, it is kept in memory.
+    fn hold(&self, name: &str, alert: &Alert, now: chrono::DateTime<chrono::Local>) {
+        debug!(
+            "Holding {} alert for {} during quiet hours",
+            alert.kind.as_str(),
+            name
+        );
+        if let Some(queue) = &self.queue
+            && let Some(until) = self.routing.quiet_until(name, now)
+        {
+            match queue.hold(name, alert, until.with_timezone(&chrono::Utc)) {
+                Ok(_) => return,
+                Err(e) => warn!(
+                    "Failed to store held {} alert for {}, keeping it in memory: {:#}",
+                    alert.kind.as_str(),
+                    name,
+                    e
+                ),
+            }
+        }
+        self.lock_held()
+            .entry(name.to_string())
+            .or_default()
+            .push(alert.clone());
+    }
+
+    /// Deliver an alert to the given notifiers.
+    ///
+    /// With a queue, the alert is queued once per notifier and this only
+    /// fails if it cannot be written to disk. Otherwise every notifier is
+    /// attempted even if an earlier one fails, so a single unreachable
+    /// backend does not prevent delivery to the others.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AlertError::DeliveryFailed`] with the error of each
+    /// failed notifier, or an [`AlertError::QueueFailed`] if the alert
+    /// could not be queued.
+    fn deliver(&self, alert: &Alert, destinations: &[&dyn Notifier]) -> Result<(), AlertError> {
+        if destinations.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(queue) = &self.queue {
+            for notifier in destinations {
+                // A backlog of progress updates is pointless, only the latest one matters
+                if alert.kind == AlertKind::PrintProgress {
+                    queue.discard(|queued| {
+                        queued.notifier == notifier.name()
+                            && queued.alert.kind == AlertKind::PrintProgress
+                            && queued.alert.job == alert.job
+                    });
+                }
+                queue
+                    .push(notifier.name(), &self.fit_upload_limit(alert, *notifier))
+                    .map_err(|e| AlertError::QueueFailed {
+                        notifier: notifier.name().to_string(),
+                        source: e.into(),
+                    })?;
+            }
+            metrics::get().alert_queue_depth.set(queue.len() as f64);
+            return Ok(());
+        }
+
+        let mut failures = Vec::new();
+
+        for notifier in destinations {
+            if let Err(e) = notifier.send(&self.fit_upload_limit(alert, *notifier)) {
+                warn!(
+                    "Failed to send {} alert via {}: {:#}",
+                    alert.kind.as_str(),
+                    notifier.name(),
+                    e
+                );
+                failures.push((notifier.name().to_string(), e.into()));
+            }
+        }
+
+        let result = if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AlertError::DeliveryFailed {
+                alert_kind: alert.kind.as_str().to_string(),
+                failures,
+            })
+        };
+
+        metrics::get().record_alert(alert.kind.as_str(), result)
+    }
+
+    /// Deliver alerts held back by quiet hours or by notifiers (e.g. email
+    /// digests) that are due.
+    ///
+    /// Alerts held during quiet hours are sent as one summary per notifier
+    /// once its quiet hours are over. Held queue entries are only removed
+    /// once their summary is queued. Failures are logged and held entries
+    /// and notifier digests are retried on the next call.
+    pub fn flush(&self) {
+        let now = chrono::Local::now().time();
+        let mut due: BTreeMap<String, ReleasedAlerts> = BTreeMap::new();
+        {
+            let mut held = self.lock_held();
+            let names: Vec<String> = held
+                .keys()
+                .filter(|name| !self.routing.is_quiet(name, now))
+                .cloned()
+                .collect();
+            for name in names {
+                if let Some(alerts) = held.remove(&name) {
+                    due.entry(name).or_default().held.extend(alerts);
                 }
-            }]
-        });
-
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(&self.webhook_url)
-            .header("Content-Type", "application/json")
-            .json(&embed)
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to send Discord alert: HTTP {}",
-                response.status()
-            ));
+            }
+        }
+        if let Some(queue) = &self.queue {
+            for entry in queue.released() {
+                let released = due.entry(entry.notifier).or_default();
+                released.queued.push(entry.alert);
+                released.ids.push(entry.id);
+            }
         }
 
-        Ok(())
+        for (name, released) in due {
+            let ReleasedAlerts { held, queued, ids } = released;
+            let Some(notifier) = self.notifiers.iter().find(|n| n.name() == name) else {
+                warn!(
+                    "Dropping {} held alert(s) for notifier {} which is no longer configured",
+                    held.len() + queued.len(),
+                    name
+                );
+                self.remove_held(&ids);
+                continue;
+            };
+            let mut alerts: Vec<Alert> = held.iter().cloned().chain(queued).collect();
+            alerts.sort_by_key(|alert| alert.timestamp);
+            let summary = match self.templates.render(
+                "summary",
+                AlertKind::PrinterStatus,
+                &json!({ "count": alerts.len() }),
+            ) {
+                Ok(summary) => routing::summarize(summary, &alerts),
+                Err(e) => {
+                    warn!("Failed to render quiet hours summary: {:#}", e);
+                    self.restore_held(name, held);
+                    continue;
+                }
+            };
+            match self.deliver(&summary, &[notifier.as_ref()]) {
+                Ok(()) => {
+                    self.remove_held(&ids);
+                    info!(
+                        "Quiet hours over for {}, sent summary of {} held alert(s)",
+                        name,
+                        alerts.len()
+                    );
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to send quiet hours summary to {}: {}",
+                        name,
+                        ErrorChain(&e)
+                    );
+                    self.restore_held(name, held);
+                }
+            }
+        }
+
+        for notifier in self.notifiers.iter() {
+            if let Err(e) = notifier.flush() {
+                warn!("Failed to flush {} notifier: {:#}", notifier.name(), e);
+            }
+        }
+    }
+
+    /// Render the title and description of an alert from the templates.
+    fn alert(&self, kind: AlertKind, context: &serde_json::Value) -> Result<Alert, AlertError> {
+        self.templates
+            .alert(kind, context)
+            .map_err(|e| AlertError::MessageFormatError { source: e.into() })
+    }
+
+    /// Remove held queue entries whose summary has been delivered.
+    fn remove_held(&self, ids: &[String]) {
+        if let Some(queue) = &self.queue {
+            for id in ids {
+                queue.remove(id);
+            }
+        }
+    }
+
+    /// Put alerts held in memory back after their summary could not be sent.
+    fn restore_held(&self, name: String, alerts: Vec<Alert>) {
+        if !alerts.is_empty() {
+            self.lock_held().entry(name).or_default().extend(alerts);
+        }
+    }
+
+    fn lock_held(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Vec<Alert>>> {
+        self.held
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Fit an alert's image into a notifier's upload limit.
+    ///
+    /// Images over the limit are compressed; if that fails, the alert is
+    /// sent without the image and a note saying it was dropped.
+    fn fit_upload_limit<'a>(&self, alert: &'a Alert, notifier: &dyn Notifier) -> Cow<'a, Alert> {
+        let limit = self
+            .upload_limits
+            .get(notifier.name())
+            .copied()
+            .or_else(|| notifier.max_upload_bytes());
+        let (Some(image), Some(limit)) = (&alert.image, limit) else {
+            return Cow::Borrowed(alert);
+        };
+        if image.data.len() <= limit {
+            return Cow::Borrowed(alert);
+        }
+
+        let mut fitted = alert.clone();
+        fitted.image = image.fit(limit);
+        match &fitted.image {
+            Some(compressed) => info!(
+                "Compressed {} alert image for {} from {} to {} bytes",
+                alert.kind.as_str(),
+                notifier.name(),
+                image.data.len(),
+                compressed.data.len()
+            ),
+            None => {
+                warn!(
+                    "Dropped {} alert image for {}: {} bytes exceed the limit of {} bytes",
+                    alert.kind.as_str(),
+                    notifier.name(),
+                    image.data.len(),
+                    limit
+                );
+                let t = &self.templates;
+                fitted = fitted.with_field(t.label("image"), t.label("image_dropped"));
+            }
+        }
+        Cow::Owned(fitted)
     }
 
     /// Send a print failure alert with standardized formatting.
@@ -86,13 +467,16 @@ impl AlertService {
     ///
     /// # Arguments
     ///
+    /// * `event_id` - Identifier of the failure event, used for acknowledgement
     /// * `label` - The type of failure detected (e.g., "spaghetti", "layer_shift")
     /// * `confidence` - Confidence percentage (0.0 to 100.0)
     /// * `x`, `y`, `w`, `h` - Bounding box coordinates and dimensions
     /// * `annotated_image` - Optional annotated image data (JPEG format)
     ///
+    #[allow(clippy::too_many_arguments)]
     pub fn send_print_failure_alert(
         &self,
+        event_id: u64,
         label: &str,
         confidence: f32,
         x: f32,
@@ -100,85 +484,150 @@ impl AlertService {
         w: f32,
         h: f32,
         annotated_image: Option<&[u8]>,
-    ) -> Result<()> {
-        let description = format!(
-            "Detected **{}** print failure with **{:.2}%** confidence\n\n**Location:**\n• X: {:.1}\n• Y: {:.1}\n• Width: {:.1}\n• Height: {:.1}",
-            label, confidence, x, y, w, h
-        );
+    ) -> Result<(), AlertError> {
+        let t = &self.templates;
+        let alert = self
+            .alert(
+                AlertKind::PrintFailure,
+                &json!({
+                    "event_id": event_id,
+                    "label": label,
+                    "confidence": format!("{:.2}", confidence),
+                    "x": x,
+                    "y": y,
+                    "w": w,
+                    "h": h,
+                }),
+            )?
+            .with_field(t.label("label"), label)
+            .with_field(t.label("confidence"), format!("{:.2}%", confidence))
+            .with_field(t.label("position"), format!("X: {:.1}, Y: {:.1}", x, y))
+            .with_field(t.label("size"), format!("{:.1} × {:.1}", w, h))
+            .with_field(t.label("event"), format!("#{}", event_id))
+            .with_detection(AlertDetection {
+                label: label.to_string(),
+                confidence,
+                x,
+                y,
+                w,
+                h,
+            })
+            .with_image(annotated_image, "failure_detection");
 
-        // If we have an annotated image, send it with the alert
-        if let Some(image_data) = annotated_image {
-            let filename = format!("failure_detection_{}.jpg", chrono::Utc::now().timestamp());
-            self.send_alert_with_image(
-                "Print Failure Detected",
-                &description,
-                0xFFA500, // Orange color
-                "⚠️",
-                image_data,
-                &filename,
-            )
-        } else {
-            // Send regular alert without image
-            self.send_alert(
-                "Print Failure Detected",
-                &description,
-                0xFFA500, // Orange color
-                "⚠️",
-            )
-        }
+        self.dispatch(&alert)
     }
 
+    /// Send a printer status alert.
+    ///
+    /// Used when the printer state changes, e.g. when a print starts or completes.
+    /// Notifiers that update messages in place show every status change of a
+    /// print job in the job's message, ending with its final summary.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - Printer status reported by Moonraker
+    /// * `image_data` - Optional image of the current camera frame
+    /// * `job` - The print job the status change belongs to, if any
     pub fn send_printer_status_alert(
         &self,
-        status: &serde_json::Value,
+        status: &PrinterStatus,
         image_data: Option<&[u8]>,
-    ) -> Result<()> {
-        let stats = &status["result"]["status"]["print_stats"];
-
-        let description = format!(
-            "
-            Current file: **{}**
-            Current printer state: **{}**
-            **Print Stats:**
-            • Filament Used: {:.2}m
-            • Print Duration: {}
-            ",
-            stats["filename"].as_str().unwrap_or("Unknown"),
-            stats["state"].as_str().unwrap_or("unknown"),
-            stats["filament_used"].as_f64().unwrap_or(0.0) / 1000.0, // convert mm to meters
-            // convert seconds to a human-readable format with hours and minutes
-            format!(
-                "{}h {}m {}s",
-                f64::floor(stats["print_duration"].as_f64().unwrap_or(0.0) / 3600.0),
-                f64::floor(stats["print_duration"].as_f64().unwrap_or(0.0) % 3600.0 / 60.0),
-                f64::floor(stats["print_duration"].as_f64().unwrap_or(0.0) % 60.0)
-            )
-        );
+        job: Option<&PrintJob>,
+    ) -> Result<(), AlertError> {
+        let t = &self.templates;
+        let stats = &status.print_stats;
+        let duration = format_duration(stats.print_duration);
+        let state = stats.state.as_str();
+        let filename = stats
+            .filename()
+            .map(str::to_string)
+            .unwrap_or_else(|| t.label("unknown"));
+        // convert mm to meters
+        let filament_used = format!("{:.2}m", stats.filament_used / 1000.0);
+
+        let alert = self
+            .alert(
+                AlertKind::PrinterStatus,
+                &json!({
+                    "state": state,
+                    "state_message": status.klippy_message(),
+                    "filename": filename,
+                    "filament_used": filament_used,
+                    "print_duration": duration,
+                }),
+            )?
+            .with_field(t.label("file"), filename)
+            .with_field(t.label("state"), state)
+            .with_field(t.label("filament_used"), filament_used)
+            .with_field(t.label("print_duration"), duration)
+            .with_image(image_data, "printer_status")
+            .with_job(job);
+
+        self.dispatch(&alert)
+    }
 
-        let title = status["result"]["status"]["webhooks"]["state_message"]
-            .as_str()
-            .unwrap_or("Printer Status Update");
-
-        // If we have an image, send it with the alert
-        if let Some(image_bytes) = image_data {
-            let filename = format!("printer_status_{}.jpg", chrono::Utc::now().timestamp());
-            self.send_alert_with_image(
-                title,
-                &description,
-                0x0099FF, // Blue color
-                "ℹ️",
-                image_bytes,
-                &filename,
+    /// Send a progress update of the current print job.
+    ///
+    /// Only delivered to notifiers that update the job's message in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The print job in progress
+    /// * `status` - Printer status reported by Moonraker
+    /// * `failure_score` - Highest detection confidence in the latest frame (0.0 to 1.0)
+    /// * `image_data` - Optional latest annotated frame
+    pub fn send_print_progress_alert(
+        &self,
+        job: &PrintJob,
+        status: &PrinterStatus,
+        failure_score: f32,
+        image_data: Option<&[u8]>,
+    ) -> Result<(), AlertError> {
+        let t = &self.templates;
+        let filename = status
+            .print_stats
+            .filename()
+            .map(str::to_string)
+            .unwrap_or_else(|| t.label("unknown"));
+        let print_duration = status.print_stats.print_duration;
+        let progress = status.progress();
+        let progress_percent = format!("{:.1}", progress * 100.0);
+
+        // estimate the remaining time from the time spent so far
+        let eta = if progress > 0.0 {
+            let remaining = print_duration * (1.0 - progress) / progress;
+            let finish = chrono::Local::now() + chrono::Duration::seconds(remaining as i64);
+            format!(
+                "{} (+{})",
+                finish.format("%H:%M"),
+                format_duration(remaining)
             )
         } else {
-            // Send regular alert without image
-            self.send_alert(
-                title,
-                &description,
-                0x0099FF, // Blue color
-                "ℹ️",
+            t.label("unknown")
+        };
+
+        let alert = self
+            .alert(
+                AlertKind::PrintProgress,
+                &json!({
+                    "filename": filename,
+                    "progress": progress_percent,
+                    "print_duration": format_duration(print_duration),
+                    "eta": eta,
+                    "failure_score": format!("{:.1}", failure_score * 100.0),
+                }),
+            )?
+            .with_field(t.label("progress"), format!("{}%", progress_percent))
+            .with_field(t.label("print_duration"), format_duration(print_duration))
+            .with_field(t.label("eta"), eta)
+            .with_field(
+                t.label("failure_score"),
+                format!("{:.1}%", failure_score * 100.0),
             )
-        }
+            .with_image(image_data, "print_progress")
+            .with_job(Some(job));
+
+        self.dispatch(&alert)
     }
 
     /// Send a critical system offline alert.
@@ -190,139 +639,272 @@ impl AlertService {
     ///
     /// * `image_url` - The URL that failed to respond
     /// * `max_retries` - Number of retry attempts that were made
-    pub fn send_system_offline_alert(&self, image_url: &str, max_retries: u32) -> Result<()> {
-        let description = format!(
-            "Failed to fetch image from {} after {} attempts. Print monitoring is offline!",
-            image_url, max_retries
-        );
+    pub fn send_system_offline_alert(
+        &self,
+        image_url: &str,
+        max_retries: u32,
+    ) -> Result<(), AlertError> {
+        let t = &self.templates;
+        let alert = self
+            .alert(
+                AlertKind::SystemOffline,
+                &json!({ "image_url": image_url, "attempts": max_retries }),
+            )?
+            .with_field(t.label("camera"), image_url)
+            .with_field(t.label("attempts"), max_retries.to_string());
 
-        self.send_alert(
-            "CRITICAL: Print Monitoring Offline",
-            &description,
-            0xFF0000, // Red color
-            "🚨",
-        )
+        self.dispatch(&alert)
     }
 
     /// Send a system recovery alert.
     ///
     /// Used when the system comes back online after a period of being disconnected.
-    pub fn send_system_recovery_alert(&self) -> Result<()> {
-        self.send_alert(
-            "RECOVERY: Print Monitoring Back Online",
-            "Image fetch successful after connection issues.",
-            0x00FF00, // Green color
-            "✅",
-        )
+    pub fn send_system_recovery_alert(&self) -> Result<(), AlertError> {
+        let alert = self.alert(AlertKind::SystemRecovery, &json!({}))?;
+
+        self.dispatch(&alert)
     }
 
     /// Send a print pause alert.
     ///
     /// Used when the system automatically pauses the printer due to multiple
-    /// detected failures.
+    /// detected failures, reporting whether the pause took effect.
     ///
     /// # Arguments
     ///
     /// * `failure_count` - Number of failures that triggered the pause
+    /// * `event_id` - Identifier of the failure event that triggered the pause
+    /// * `outcome` - Whether the printer paused, stopped otherwise or kept printing
     /// * `annotated_image` - Optional annotated image showing the failures
     pub fn send_print_pause_alert(
         &self,
         failure_count: u32,
+        event_id: u64,
+        outcome: &PauseOutcome,
         annotated_image: Option<&[u8]>,
-    ) -> Result<()> {
-        let description = format!(
-            "Print has been paused after detecting {} print failures. Please check the printer.",
-            failure_count
-        );
-
-        // If we have an annotated image, send it with the alert
-        if let Some(image_data) = annotated_image {
-            let filename = format!("print_pause_{}.jpg", chrono::Utc::now().timestamp());
-            self.send_alert_with_image(
-                "Print Paused Due to Multiple Failures",
-                &description,
-                0xFF0000, // Red color
-                "🚨",
-                image_data,
-                &filename,
-            )
-        } else {
-            // Send regular alert without image
-            self.send_alert(
-                "Print Paused Due to Multiple Failures",
-                &description,
-                0xFF0000, // Red color
-                "🚨",
-            )
+    ) -> Result<(), AlertError> {
+        let t = &self.templates;
+        let outcome_text = match outcome {
+            PauseOutcome::Stopped { state } => {
+                format!("{} ({})", t.label("outcome_stopped"), state)
+            }
+            outcome => t.label(&format!("outcome_{}", outcome.as_str())),
+        };
+        let mut alert = self
+            .alert(
+                AlertKind::PrintPaused,
+                &json!({
+                    "failure_count": failure_count,
+                    "event_id": event_id,
+                    "outcome": outcome_text,
+                    "paused": matches!(outcome, PauseOutcome::Paused { .. }),
+                    "halted": outcome.is_halted(),
+                }),
+            )?
+            .with_field(t.label("failures"), failure_count.to_string())
+            .with_field(t.label("event"), format!("#{}", event_id))
+            .with_field(t.label("outcome"), outcome_text);
+        if let PauseOutcome::Paused { attempts } = outcome {
+            alert = alert.with_field(t.label("pause_attempts"), attempts.to_string());
         }
+        let alert = alert.with_image(annotated_image, "print_pause");
+
+        self.dispatch(&alert)
     }
 
-    /// Send a Discord alert with an attached image.
-    ///
-    /// Creates a rich embed message with an attached image file.
+    /// Send the summary report of a finished print.
     ///
     /// # Arguments
     ///
-    /// * `title` - The title of the alert
-    /// * `description` - The main content of the alert (supports Markdown)
-    /// * `color` - The color of the embed sidebar (as a hex value)
-    /// * `emoji` - An emoji to display with the title
-    /// * `image_data` - JPEG image data to attach
-    /// * `filename` - Name for the attached image file
+    /// * `report` - The finished print's report
+    /// * `image_data` - Optional rendered summary image (chart and most suspicious frames)
+    pub fn send_print_report_alert(
+        &self,
+        report: &PrintReport,
+        image_data: Option<&[u8]>,
+    ) -> Result<(), AlertError> {
+        let t = &self.templates;
+        let duration = format_duration(report.elapsed().as_secs_f64());
+        let max_score = format!("{:.1}", report.max_score() * 100.0);
+
+        let alert = self
+            .alert(
+                AlertKind::PrintReport,
+                &json!({
+                    "filename": report.filename(),
+                    "state": report.final_state(),
+                    "duration": duration,
+                    "frames_analysed": report.frames_analysed(),
+                    "max_score": max_score,
+                }),
+            )?
+            .with_field(t.label("duration"), duration)
+            .with_field(
+                t.label("frames_analysed"),
+                report.frames_analysed().to_string(),
+            )
+            .with_field(t.label("detections"), report.detections().to_string())
+            .with_field(t.label("max_failure_score"), format!("{}%", max_score))
+            .with_field(t.label("alerts_sent"), report.alerts_sent().to_string())
+            .with_field(t.label("pauses"), report.pauses().to_string())
+            .with_field(
+                t.label("camera_downtime"),
+                format_duration(report.camera_downtime().as_secs_f64()),
+            )
+            .with_image(image_data, "print_report");
+
+        self.dispatch(&alert)
+    }
+
+    /// Send the scheduled fleet digest of the monitored printer.
     ///
-    /// # Errors
+    /// # Arguments
     ///
-    /// Returns an error if:
-    /// - The HTTP request fails
-    /// - The Discord API returns an error status
-    /// - JSON serialization fails
-    pub fn send_alert_with_image(
-        &self,
-        title: &str,
-        description: &str,
-        color: u32,
-        emoji: &str,
-        image_data: &[u8],
-        filename: &str,
-    ) -> Result<()> {
-        let timestamp = chrono::Utc::now().to_rfc3339();
-
-        let embed = json!({
-            "embeds": [{
-                "title": format!("{} {}", emoji, title),
-                "description": description,
-                "color": color,
-                "timestamp": timestamp,
-                "footer": {
-                    "text": "Print Guardian"
-                },
-                "image": {
-                    "url": format!("attachment://{}", filename)
-                }
-            }]
-        });
-
-        let client = reqwest::blocking::Client::new();
-
-        // Create multipart form with JSON payload and image
-        let form = multipart::Form::new()
-            .text("payload_json", embed.to_string())
-            .part(
-                "files[0]",
-                multipart::Part::bytes(image_data.to_vec())
-                    .file_name(filename.to_string())
-                    .mime_str("image/jpeg")?,
+    /// * `digest` - Statistics of the finished digest period
+    pub fn send_fleet_digest_alert(&self, digest: &FleetDigest) -> Result<(), AlertError> {
+        let t = &self.templates;
+        let percent = |uptime: Option<f64>| {
+            uptime
+                .map(|percent| format!("{:.1}%", percent))
+                .unwrap_or_else(|| t.label("unknown"))
+        };
+
+        let alert = self
+            .alert(
+                AlertKind::FleetDigest,
+                &json!({
+                    "printer": digest.printer_name,
+                    "weekly": digest.period == "weekly",
+                    "since": digest.started_at.format("%Y-%m-%d %H:%M").to_string(),
+                }),
+            )?
+            .with_field(
+                t.label("prints_completed"),
+                digest.prints_completed.to_string(),
+            )
+            .with_field(t.label("failures_detected"), digest.failures.to_string())
+            .with_field(t.label("pauses"), digest.pauses.to_string())
+            .with_field(
+                t.label("false_positives"),
+                digest.false_positives.to_string(),
+            )
+            .with_field(t.label("camera_uptime"), percent(digest.camera.percent()))
+            .with_field(
+                t.label("moonraker_uptime"),
+                percent(digest.moonraker.percent()),
+            )
+            .with_field(
+                t.label("average_inference_time"),
+                digest
+                    .average_inference_time()
+                    .map(|duration| format!("{} ms", duration.as_millis()))
+                    .unwrap_or_else(|| t.label("unknown")),
             );
 
-        let response = client.post(&self.webhook_url).multipart(form).send()?;
+        self.dispatch(&alert)
+    }
+}
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to send Discord alert with image: HTTP {}",
-                response.status()
-            ));
+/// Format seconds as a human-readable duration with hours, minutes and seconds.
+fn format_duration(seconds: f64) -> String {
+    format!(
+        "{}h {}m {}s",
+        f64::floor(seconds / 3600.0),
+        f64::floor(seconds % 3600.0 / 60.0),
+        f64::floor(seconds % 60.0)
+    )
+}
+
+/// Deliver due entries from the queue until the process exits.
+///
+/// Due entries are attempted oldest first. A notifier that fails is skipped
+/// for the rest of the pass, and a notifier that is rate limited is not
+/// contacted again until the backend's `retry_after` has passed. Entries
+/// waiting out their backoff don't hold back newer ones, so alerts can
+/// arrive out of order after a failed attempt. Steps of a partially
+/// delivered alert that succeeded are stored with the entry and skipped
+/// on retry.
+fn deliver_queued_alerts(queue: &AlertQueue, notifiers: &[Box<dyn Notifier>]) {
+    let poll_interval = Duration::from_secs(constants::ALERT_QUEUE_POLL_INTERVAL_SECONDS);
+    let mut rate_limited_until: HashMap<String, Instant> = HashMap::new();
+
+    loop {
+        let mut failed = HashSet::new();
+
+        for entry in queue.due() {
+            if failed.contains(&entry.notifier) {
+                continue;
+            }
+            if let Some(until) = rate_limited_until.get(&entry.notifier) {
+                if Instant::now() < *until {
+                    continue;
+                }
+                rate_limited_until.remove(&entry.notifier);
+            }
+
+            let kind = entry.alert.kind.as_str();
+            let Some(notifier) = notifiers.iter().find(|n| n.name() == entry.notifier) else {
+                warn!(
+                    "Dropping queued {} alert for notifier {} which is no longer configured",
+                    kind, entry.notifier
+                );
+                queue.remove(&entry.id);
+                continue;
+            };
+
+            let mut progress = entry.progress.clone();
+            match notifier.send_resumable(&entry.alert, &mut progress) {
+                Ok(()) => {
+                    queue.remove(&entry.id);
+                    metrics::get().alerts_sent.with_label_values(&[kind]).inc();
+                }
+                Err(e) => {
+                    failed.insert(entry.notifier.clone());
+                    if progress != entry.progress {
+                        queue.save_progress(&entry.id, &progress);
+                    }
+
+                    if let Some(limit) = e.downcast_ref::<RateLimited>() {
+                        warn!(
+                            "{} is rate limiting alerts, retrying in {:.1}s",
+                            notifier.name(),
+                            limit.retry_after.as_secs_f64()
+                        );
+                        rate_limited_until
+                            .insert(entry.notifier.clone(), Instant::now() + limit.retry_after);
+                        queue.postpone(&entry.id, limit.retry_after);
+                        continue;
+                    }
+
+                    let attempts = queue.record_failure(&entry.id);
+                    metrics::get()
+                        .alerts_failed
+                        .with_label_values(&[kind])
+                        .inc();
+                    if attempts >= constants::ALERT_QUEUE_MAX_ATTEMPTS {
+                        error!(
+                            "Giving up on {} alert via {} after {} attempts: {:#}",
+                            kind,
+                            notifier.name(),
+                            attempts,
+                            e
+                        );
+                        queue.remove(&entry.id);
+                    } else {
+                        warn!(
+                            "Failed to send {} alert via {} (attempt {}), retrying in {}s: {:#}",
+                            kind,
+                            notifier.name(),
+                            attempts,
+                            queue.backoff(attempts).as_secs(),
+                            e
+                        );
+                    }
+                }
+            }
         }
 
-        Ok(())
+        metrics::get().alert_queue_depth.set(queue.len() as f64);
+        queue.wait(poll_interval);
     }
 }
@@ -0,0 +1,360 @@
+#![allow(dead_code)]
+use log::{info, warn};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tiny_http::{Method, Request};
+
+use crate::error::ErrorChain;
+use crate::feedback::FeedbackService;
+use crate::notifiers::WebhookNotifier;
+use crate::printer::PrinterService;
+use crate::server::{HttpResponse, json_response};
+use crate::state::{self, EventLevel, SharedState};
+
+/// Authenticated REST API for controlling monitoring at runtime.
+///
+/// All endpoints live under `/api` and require an `Authorization: Bearer <token>`
+/// header matching the configured API token. When no token is configured the
+/// API is disabled.
+///
+/// Endpoints:
+/// * `POST /api/monitoring/pause` / `POST /api/monitoring/resume` - Pause or resume failure detection
+/// * `GET /api/thresholds` / `PUT /api/thresholds` - Read or change detection thresholds
+/// * `POST /api/detect` - Run a one-off detection on the next loop iteration
+/// * `POST /api/alerts/snooze` / `DELETE /api/alerts/snooze` - Snooze non-critical alerts
+/// * `POST /api/print/pause` / `POST /api/print/resume` - Pause or resume the print
+/// * `GET /api/events` - List recent failure events
+/// * `POST /api/events/{id}/false-positive` - Acknowledge an event as a false positive
+pub struct ControlApi {
+    state: SharedState,
+    feedback: Arc<Mutex<FeedbackService>>,
+    printer_service: PrinterService,
+    api_token: Option<String>,
+}
+
+impl ControlApi {
+    /// Create a new ControlApi.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - Shared monitoring state used to pass requests to the monitoring loop
+    /// * `feedback` - Shared false positive feedback service
+    /// * `printer_service` - Printer service used for print control
+    /// * `api_token` - Bearer token required by all endpoints, `None` disables the API
+    pub fn new(
+        state: SharedState,
+        feedback: Arc<Mutex<FeedbackService>>,
+        printer_service: PrinterService,
+        api_token: Option<String>,
+    ) -> Self {
+        Self {
+            state,
+            feedback,
+            printer_service,
+            api_token,
+        }
+    }
+
+    /// Check whether a request may read monitoring data such as camera frames
+    /// and printer status.
+    ///
+    /// Everything is readable when no API token is configured. Otherwise the
+    /// token must be sent as a bearer header or, for `<img>` and `EventSource`
+    /// requests that cannot set headers, as a `token` query parameter.
+    pub(crate) fn may_read(&self, request: &Request) -> bool {
+        let Some(api_token) = &self.api_token else {
+            return true;
+        };
+
+        is_authorized(request, api_token)
+            || query_param(request, "token")
+                .is_some_and(|token| constant_time_eq(token.as_bytes(), api_token.as_bytes()))
+    }
+
+    /// Check whether a request may read a stored alert image, either like
+    /// [`ControlApi::may_read`] or with the `sig` query parameter of the link
+    /// sent with the alert, see [`WebhookNotifier::image_signature`].
+    pub(crate) fn may_read_alert_image(&self, request: &Request, file_name: &str) -> bool {
+        let Some(api_token) = &self.api_token else {
+            return true;
+        };
+
+        let expected = WebhookNotifier::image_signature(api_token, file_name);
+        self.may_read(request)
+            || query_param(request, "sig")
+                .is_some_and(|sig| constant_time_eq(sig.as_bytes(), expected.as_bytes()))
+    }
+
+    /// Handle a request whose path starts with `/api/`.
+    pub fn handle(&self, request: &mut Request, path: &str) -> HttpResponse {
+        let Some(api_token) = &self.api_token else {
+            return error_response(503, "control API is disabled, set API_TOKEN to enable it");
+        };
+
+        if !is_authorized(request, api_token) {
+            warn!("Rejected unauthorized control API request to {}", path);
+            return error_response(401, "missing or invalid API token");
+        }
+
+        let body = match read_json_body(request) {
+            Ok(body) => body,
+            Err(message) => return error_response(400, &message),
+        };
+
+        let segments: Vec<&str> = path.trim_matches('/').split('/').skip(1).collect();
+        match (request.method(), segments.as_slice()) {
+            (Method::Post, ["monitoring", "pause"]) => self.set_monitoring_paused(true),
+            (Method::Post, ["monitoring", "resume"]) => self.set_monitoring_paused(false),
+            (Method::Get, ["thresholds"]) => self.get_thresholds(),
+            (Method::Put, ["thresholds"]) => self.set_thresholds(&body),
+            (Method::Post, ["detect"]) => self.request_detection(),
+            (Method::Post, ["alerts", "snooze"]) => self.snooze_alerts(&body),
+            (Method::Delete, ["alerts", "snooze"]) => self.cancel_snooze(),
+            (Method::Post, ["print", "pause"]) => self.pause_print(),
+            (Method::Post, ["print", "resume"]) => self.resume_print(),
+            (Method::Get, ["events"]) => self.list_events(),
+            (Method::Post, ["events", id, "false-positive"]) => match id.parse::<u64>() {
+                Ok(event_id) => self.acknowledge_false_positive(event_id),
+                Err(_) => error_response(400, "event id must be a number"),
+            },
+            _ => error_response(404, "not found"),
+        }
+    }
+
+    fn set_monitoring_paused(&self, paused: bool) -> HttpResponse {
+        {
+            let mut state = state::lock(&self.state);
+            state.monitoring_paused = paused;
+            state.push_event(
+                EventLevel::Info,
+                if paused {
+                    "Monitoring paused through control API"
+                } else {
+                    "Monitoring resumed through control API"
+                },
+            );
+        }
+        info!(
+            "Monitoring {} through control API",
+            if paused { "paused" } else { "resumed" }
+        );
+        json_response(200, &json!({ "monitoring_paused": paused }))
+    }
+
+    fn get_thresholds(&self) -> HttpResponse {
+        let state = state::lock(&self.state);
+        json_response(
+            200,
+            &json!({
+                "objectness_threshold": state.objectness_threshold,
+                "class_prob_threshold": state.class_prob_threshold,
+            }),
+        )
+    }
+
+    fn set_thresholds(&self, body: &serde_json::Value) -> HttpResponse {
+        let objectness = body["objectness_threshold"].as_f64().map(|t| t as f32);
+        let class_prob = body["class_prob_threshold"].as_f64().map(|t| t as f32);
+
+        if objectness.is_none() && class_prob.is_none() {
+            return error_response(
+                400,
+                "expected 'objectness_threshold' and/or 'class_prob_threshold'",
+            );
+        }
+        if [objectness, class_prob]
+            .iter()
+            .flatten()
+            .any(|t| !(0.0..=1.0).contains(t))
+        {
+            return error_response(400, "thresholds must be between 0.0 and 1.0");
+        }
+
+        let mut state = state::lock(&self.state);
+        if objectness.is_some() {
+            state.pending_objectness_threshold = objectness;
+        }
+        if class_prob.is_some() {
+            state.pending_class_prob_threshold = class_prob;
+        }
+        info!(
+            "Threshold change requested through control API: objectness={:?}, class_prob={:?}",
+            objectness, class_prob
+        );
+
+        json_response(
+            202,
+            &json!({
+                "objectness_threshold": objectness.unwrap_or(state.objectness_threshold),
+                "class_prob_threshold": class_prob.unwrap_or(state.class_prob_threshold),
+            }),
+        )
+    }
+
+    fn request_detection(&self) -> HttpResponse {
+        state::lock(&self.state).detection_requested = true;
+        info!("One-off detection requested through control API");
+        json_response(202, &json!({ "detection_requested": true }))
+    }
+
+    fn snooze_alerts(&self, body: &serde_json::Value) -> HttpResponse {
+        let Some(minutes) = body["minutes"].as_u64().filter(|&m| m > 0) else {
+            return error_response(400, "expected a positive 'minutes' value");
+        };
+
+        let until = chrono::Local::now() + chrono::Duration::minutes(minutes as i64);
+        {
+            let mut state = state::lock(&self.state);
+            state.alerts_snoozed_until = Some(until);
+            state.push_event(
+                EventLevel::Info,
+                format!("Alerts snoozed for {} minutes", minutes),
+            );
+        }
+        info!("Alerts snoozed until {}", until.to_rfc3339());
+
+        json_response(200, &json!({ "alerts_snoozed_until": until.to_rfc3339() }))
+    }
+
+    fn cancel_snooze(&self) -> HttpResponse {
+        state::lock(&self.state).alerts_snoozed_until = None;
+        info!("Alert snooze cancelled through control API");
+        json_response(200, &json!({ "alerts_snoozed_until": null }))
+    }
+
+    fn pause_print(&self) -> HttpResponse {
+        match self.printer_service.pause_print() {
+            Ok(()) => {
+                info!("Print paused through control API");
+                state::lock(&self.state)
+                    .push_event(EventLevel::Warning, "Print paused through control API");
+                json_response(200, &json!({ "paused": true }))
+            }
+            Err(e) => error_response(502, &format!("failed to pause print: {}", ErrorChain(&e))),
+        }
+    }
+
+    fn resume_print(&self) -> HttpResponse {
+        match self.printer_service.resume_print() {
+            Ok(()) => {
+                info!("Print resumed through control API");
+                state::lock(&self.state)
+                    .push_event(EventLevel::Info, "Print resumed through control API");
+                json_response(200, &json!({ "resumed": true }))
+            }
+            Err(e) => error_response(502, &format!("failed to resume print: {}", ErrorChain(&e))),
+        }
+    }
+
+    fn list_events(&self) -> HttpResponse {
+        let feedback = state::lock(&self.feedback);
+        let events: Vec<_> = feedback
+            .get_events()
+            .map(|event| {
+                json!({
+                    "id": event.id,
+                    "timestamp": event.timestamp.to_rfc3339(),
+                    "detections": event.detections.len(),
+                    "paused": event.paused,
+                    "false_positive": event.false_positive,
+                })
+            })
+            .collect();
+        json_response(200, &json!({ "events": events }))
+    }
+
+    fn acknowledge_false_positive(&self, event_id: u64) -> HttpResponse {
+        // The lock is released while the sample is stored and the print
+        // resumed, so a slow printer does not stall the monitoring loop
+        let pending = {
+            let mut feedback = state::lock(&self.feedback);
+            if feedback.get_event(event_id).is_none() {
+                return error_response(404, &format!("unknown failure event #{}", event_id));
+            }
+            if feedback.is_acknowledging(event_id) {
+                return error_response(
+                    409,
+                    &format!("failure event #{} is already being acknowledged", event_id),
+                );
+            }
+            feedback.begin_acknowledgement(event_id)
+        };
+
+        let result = match pending {
+            Ok(Some(pending)) => {
+                let applied = pending.apply(&self.printer_service);
+                let mut feedback = state::lock(&self.feedback);
+                match applied {
+                    Ok(()) => {
+                        feedback.complete_acknowledgement(event_id);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        feedback.abort_acknowledgement(event_id);
+                        Err(e)
+                    }
+                }
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(()) => {
+                state::lock(&self.state).push_event(
+                    EventLevel::Info,
+                    format!("Event #{} acknowledged as a false positive", event_id),
+                );
+                json_response(
+                    200,
+                    &json!({ "event_id": event_id, "false_positive": true }),
+                )
+            }
+            Err(e) => error_response(500, &format!("{:#}", e)),
+        }
+    }
+}
+
+/// Check the request's bearer token against the configured API token.
+fn is_authorized(request: &Request, api_token: &str) -> bool {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.trim().as_bytes(), api_token.as_bytes()))
+}
+
+/// Get a query parameter of the request URL.
+fn query_param(request: &Request, name: &str) -> Option<String> {
+    reqwest::Url::parse(&format!("http://localhost{}", request.url()))
+        .ok()?
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Read the request body as JSON, treating an empty body as `null`.
+fn read_json_body(request: &mut Request) -> Result<serde_json::Value, String> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| format!("failed to read request body: {}", e))?;
+
+    if body.trim().is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+
+    serde_json::from_str(&body).map_err(|e| format!("invalid JSON body: {}", e))
+}
+
+/// Build a JSON error response.
+fn error_response(status: u16, message: &str) -> HttpResponse {
+    json_response(status, &json!({ "error": message }))
+}
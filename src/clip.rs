@@ -0,0 +1,190 @@
+#![allow(dead_code)]
+use anyhow::Result;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame};
+use log::debug;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::config::constants;
+
+/// Rolling buffer of the most recent frames of one camera.
+///
+/// Frames are kept as encoded JPEG bytes to keep memory usage low and are
+/// only decoded when a clip is rendered.
+pub struct FrameBuffer {
+    window: Duration,
+    max_frames: usize,
+    frames: VecDeque<(Instant, Vec<u8>)>,
+}
+
+impl FrameBuffer {
+    /// Create a buffer keeping frames for `window`, at most `max_frames` of them.
+    pub fn new(window: Duration, max_frames: usize) -> Self {
+        Self {
+            window,
+            max_frames,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Add a frame, dropping frames that fell out of the window.
+    pub fn push(&mut self, frame: Vec<u8>) {
+        let now = Instant::now();
+        self.frames.push_back((now, frame));
+        while self.frames.len() > self.max_frames
+            || self
+                .frames
+                .front()
+                .is_some_and(|(time, _)| now.duration_since(*time) > self.window)
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Get the buffered frames, oldest first.
+    pub fn frames(&self) -> Vec<&[u8]> {
+        self.frames
+            .iter()
+            .map(|(_, frame)| frame.as_slice())
+            .collect()
+    }
+
+    /// Get the number of buffered frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check whether no frames are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// Records recent frames of every camera and renders them as animated clips.
+///
+/// Clips are attached to failure and pause alerts, since a short sequence of
+/// frames shows a failure developing much more convincingly than a single
+/// frame. Clips are capped in size so they fit the upload limits of chat
+/// services like Discord and Telegram.
+pub struct ClipRecorder {
+    window: Duration,
+    max_bytes: usize,
+    buffers: Vec<FrameBuffer>,
+}
+
+impl ClipRecorder {
+    /// Create a recorder.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - How far back clips reach, e.g. the last 30 seconds
+    /// * `max_bytes` - Maximum size of an encoded clip
+    pub fn new(window: Duration, max_bytes: usize) -> Self {
+        Self {
+            window,
+            max_bytes,
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Record a frame of a camera.
+    pub fn record(&mut self, camera_index: usize, frame: Vec<u8>) {
+        while self.buffers.len() <= camera_index {
+            self.buffers
+                .push(FrameBuffer::new(self.window, constants::CLIP_MAX_FRAMES));
+        }
+        self.buffers[camera_index].push(frame);
+    }
+
+    /// Render the recent frames of a camera as an animated GIF.
+    ///
+    /// Returns `None` if fewer than two frames are buffered or the clip cannot
+    /// be made to fit the size limit, in which case alerts fall back to a
+    /// single frame.
+    pub fn clip(&self, camera_index: usize) -> Option<Vec<u8>> {
+        let buffer = self.buffers.get(camera_index)?;
+        if buffer.len() < 2 {
+            return None;
+        }
+
+        match encode_clip(&buffer.frames(), constants::CLIP_MAX_WIDTH, self.max_bytes) {
+            Ok(clip) => clip,
+            Err(e) => {
+                debug!("Failed to render clip of camera {}: {:#}", camera_index, e);
+                None
+            }
+        }
+    }
+}
+
+/// Encode JPEG frames as a looping animated GIF no larger than `max_bytes`.
+///
+/// Frames are scaled down to at most `max_width` pixels wide. When the result
+/// is too large, the clip is retried at a smaller size and with every other
+/// frame dropped, keeping the latest frame, until it fits.
+///
+/// Returns `Ok(None)` if the clip does not fit even at the smallest size.
+///
+/// # Errors
+///
+/// Returns an error if a frame cannot be decoded or the GIF cannot be encoded.
+pub fn encode_clip(frames: &[&[u8]], max_width: u32, max_bytes: usize) -> Result<Option<Vec<u8>>> {
+    // Scale every frame down once, smaller attempts start from the scaled frames
+    let scaled = frames
+        .iter()
+        .map(|frame| {
+            let image = image::load_from_memory(frame)?;
+            Ok(if image.width() > max_width {
+                image.resize(max_width, u32::MAX, image::imageops::FilterType::Triangle)
+            } else {
+                image
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut width = max_width;
+    let mut step = 1;
+    while width >= constants::CLIP_MIN_WIDTH {
+        // Walk backwards from the latest frame so it is always included
+        let mut selected: Vec<_> = scaled.iter().rev().step_by(step).collect();
+        selected.reverse();
+
+        let mut data = Vec::new();
+        {
+            let mut encoder = GifEncoder::new_with_speed(&mut data, constants::CLIP_ENCODER_SPEED);
+            encoder.set_repeat(Repeat::Infinite)?;
+            for image in selected {
+                let frame = if image.width() > width {
+                    image.resize(width, u32::MAX, image::imageops::FilterType::Triangle)
+                } else {
+                    image.clone()
+                };
+                encoder.encode_frame(Frame::from_parts(
+                    frame.to_rgba8(),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(constants::CLIP_FRAME_DELAY_MS, 1),
+                ))?;
+            }
+        }
+
+        if data.len() <= max_bytes {
+            return Ok(Some(data));
+        }
+
+        debug!(
+            "Clip of {} frames at {}px is {} bytes, over the limit of {} bytes",
+            scaled.len().div_ceil(step),
+            width,
+            data.len(),
+            max_bytes
+        );
+        width = width * 3 / 4;
+        if scaled.len() / step > constants::CLIP_MIN_FRAMES {
+            step *= 2;
+        }
+    }
+
+    Ok(None)
+}
@@ -1,4 +1,15 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::digest::DigestSchedule;
+use crate::error::{BoxError, ConfigError};
+use crate::moonraker_auth::MoonrakerCredentials;
+use crate::mqtt::MqttSettings;
+use crate::notifiers::{SmtpSecurity, SmtpSettings, WebhookSettings};
+use crate::printer::PauseVerification;
+use crate::routing::RoutingRules;
 
 /// Configuration for the Print Guardian application loaded from environment variables.
 ///
@@ -59,7 +70,108 @@ pub struct Config {
     /// This should be a valid Discord webhook URL where alerts about print
     /// failures and system status will be sent.
     /// Environment variable: `DISCORD_WEBHOOK`
-    pub discord_webhook: String,
+    pub discord_webhook: Option<String>,
+
+    /// Additional named Discord channels as `(name, webhook URL)` pairs.
+    ///
+    /// Each channel becomes a notifier called `discord-<name>` that can be
+    /// targeted by routing rules, e.g. an owner channel and a log channel.
+    /// Environment variable: `DISCORD_CHANNELS`
+    pub discord_channels: Vec<(String, String)>,
+
+    /// Telegram bot token for sending alerts via the Telegram Bot API.
+    /// Environment variable: `TELEGRAM_BOT_TOKEN`
+    pub telegram_bot_token: Option<String>,
+
+    /// Telegram chat IDs that receive alerts for this printer.
+    ///
+    /// Comma-separated list of user, group or channel chat IDs.
+    /// Environment variable: `TELEGRAM_CHAT_IDS`
+    pub telegram_chat_ids: Vec<String>,
+
+    /// Slack incoming webhook URL that receives alerts.
+    /// Environment variable: `SLACK_WEBHOOK_URL`
+    pub slack_webhook_url: Option<String>,
+
+    /// Slack bot token for posting alerts with the Slack Web API, or only
+    /// for uploading images when an incoming webhook is configured.
+    ///
+    /// The bot needs the `chat:write` and `files:write` scopes.
+    /// Environment variable: `SLACK_BOT_TOKEN`
+    pub slack_bot_token: Option<String>,
+
+    /// ID of the Slack channel that receives alerts.
+    /// Environment variable: `SLACK_CHANNEL_ID`
+    pub slack_channel_id: Option<String>,
+
+    /// ntfy topic URL for push notifications (e.g. `https://ntfy.sh/my-printer`).
+    /// Environment variable: `NTFY_URL`
+    pub ntfy_url: Option<String>,
+
+    /// Access token for protected ntfy topics.
+    /// Environment variable: `NTFY_TOKEN`
+    pub ntfy_token: Option<String>,
+
+    /// Gotify server URL for push notifications.
+    /// Environment variable: `GOTIFY_URL`
+    pub gotify_url: Option<String>,
+
+    /// Gotify application token.
+    /// Environment variable: `GOTIFY_TOKEN`
+    pub gotify_token: Option<String>,
+
+    /// Generic webhook settings, `None` when the webhook backend is disabled.
+    ///
+    /// Environment variables: `WEBHOOK_URL`, `WEBHOOK_METHOD`, `WEBHOOK_BODY_TEMPLATE`,
+    /// `WEBHOOK_BODY_TEMPLATE_FILE`, `WEBHOOK_HEADERS`, `WEBHOOK_SECRET`
+    pub webhook: Option<WebhookSettings>,
+
+    /// SMTP settings for email alerts, `None` when email is disabled.
+    ///
+    /// Environment variables: `SMTP_HOST`, `SMTP_PORT`, `SMTP_SECURITY`,
+    /// `SMTP_USERNAME`, `SMTP_PASSWORD`, `SMTP_FROM`, `SMTP_TO`
+    pub smtp: Option<SmtpSettings>,
+
+    /// Interval for batching non-critical email alerts into a digest.
+    ///
+    /// `None` sends every email alert immediately.
+    /// Environment variable: `SMTP_DIGEST_MINUTES`
+    pub smtp_digest_interval: Option<Duration>,
+
+    /// Which notifiers receive which alerts, and their quiet hours.
+    ///
+    /// Environment variables: `ALERT_ROUTES`, `QUIET_HOURS`, `DIGEST_NOTIFIERS`
+    pub routing: RoutingRules,
+
+    /// Image upload limits in bytes per notifier, overriding the backends' defaults.
+    /// Environment variable: `UPLOAD_LIMITS`
+    pub upload_limits: BTreeMap<String, usize>,
+
+    /// When the fleet digest is posted, `None` when digests are disabled.
+    /// Environment variable: `DIGEST_SCHEDULE`
+    pub digest_schedule: Option<DigestSchedule>,
+
+    /// Locale of alert texts, e.g. `en`, `de`, `fr` or `es`.
+    /// Environment variable: `LOCALE`
+    pub locale: String,
+
+    /// Directory with custom `<locale>.json` alert template files.
+    /// Environment variable: `ALERT_TEMPLATES_DIR`
+    pub templates_dir: Option<PathBuf>,
+
+    /// How many seconds of recent frames are attached to failure and pause
+    /// alerts as an animated clip, `None` to attach a single frame only.
+    /// Environment variable: `CLIP_SECONDS`
+    pub clip_window: Option<Duration>,
+
+    /// Maximum size of an animated clip in bytes, so it fits chat upload limits.
+    /// Environment variable: `CLIP_MAX_BYTES`
+    pub clip_max_bytes: usize,
+
+    /// How automatic pauses are confirmed, and what happens if the printer
+    /// keeps printing.
+    /// Environment variables: `PAUSE_VERIFY_SECONDS`, `PAUSE_ATTEMPTS`, `PAUSE_ESCALATION`
+    pub pause_verification: PauseVerification,
 
     /// Moonraker API URL for printer control.
     ///
@@ -68,6 +180,28 @@ pub struct Config {
     /// Environment variable: `MOONRAKER_API_URL`
     pub moonraker_api_url: String,
 
+    /// Credentials for Moonraker's `[authorization]` component.
+    /// Environment variables: `MOONRAKER_API_KEY`, `MOONRAKER_USERNAME`, `MOONRAKER_PASSWORD`
+    pub moonraker_credentials: MoonrakerCredentials,
+
+    /// Whether the printer state is followed over Moonraker's WebSocket
+    /// instead of being polled over HTTP.
+    /// Environment variable: `MOONRAKER_WEBSOCKET`
+    pub moonraker_websocket: bool,
+
+    /// URL of the printer's web interface (Mainsail/Fluidd).
+    ///
+    /// Push notifications open this page when tapped. Defaults to the
+    /// Moonraker host without the API port.
+    /// Environment variable: `PRINTER_WEB_URL`
+    pub printer_web_url: Option<String>,
+
+    /// Human-readable name of the monitored printer used in alerts.
+    ///
+    /// Defaults to the Moonraker host name.
+    /// Environment variable: `PRINTER_NAME`
+    pub printer_name: String,
+
     /// Whether to flip the image vertically after fetching.
     ///
     /// This is useful for cameras that are mounted upside down.
@@ -80,6 +214,25 @@ pub struct Config {
     /// when multiple cameras are configured. If not set,
     /// the first camera in the list will be used.
     pub display_camera_index: Option<usize>,
+
+    /// Address for the built-in HTTP server to listen on.
+    ///
+    /// The server exposes health, readiness, status and latest frame endpoints.
+    /// Set to an empty string to disable the server.
+    /// Environment variable: `HTTP_BIND_ADDRESS`
+    pub http_bind_address: Option<String>,
+
+    /// MQTT broker settings for the Home Assistant integration, `None` when disabled.
+    ///
+    /// Environment variables: `MQTT_HOST`, `MQTT_PORT`, `MQTT_USERNAME`,
+    /// `MQTT_PASSWORD`, `MQTT_DISCOVERY_PREFIX`, `MQTT_NODE_ID`
+    pub mqtt: Option<MqttSettings>,
+
+    /// Bearer token required by the control API.
+    ///
+    /// When unset, the control API under `/api/` is disabled.
+    /// Environment variable: `API_TOKEN`
+    pub api_token: Option<String>,
 }
 
 impl Config {
@@ -95,12 +248,66 @@ impl Config {
     /// - `OBJECTNESS_THRESHOLD`: Objectness threshold (default: "0.75")
     /// - `CLASS_PROB_THRESHOLD`: Class probability threshold (default: "0.75")
     /// - `IMAGE_URL`: Camera image URL(s) (required) - single URL or comma-separated list for round-robin
-    /// - `DISCORD_WEBHOOK`: Discord webhook URL (required)
+    /// - `DISCORD_WEBHOOK`: Discord webhook URL (optional)
+    /// - `DISCORD_CHANNELS`: Additional named Discord webhooks as `name=url,name=url` (optional)
+    /// - `TELEGRAM_BOT_TOKEN`: Telegram bot token (optional)
+    /// - `TELEGRAM_CHAT_IDS`: Comma-separated Telegram chat IDs (required with `TELEGRAM_BOT_TOKEN`)
+    /// - `SLACK_WEBHOOK_URL`: Slack incoming webhook URL (optional)
+    /// - `SLACK_BOT_TOKEN`: Slack bot token, used for image uploads with a webhook (optional)
+    /// - `SLACK_CHANNEL_ID`: Slack channel ID (required with `SLACK_BOT_TOKEN`)
+    /// - `NTFY_URL`: ntfy topic URL (optional)
+    /// - `NTFY_TOKEN`: ntfy access token (optional)
+    /// - `GOTIFY_URL`: Gotify server URL (optional)
+    /// - `GOTIFY_TOKEN`: Gotify application token (required with `GOTIFY_URL`)
+    /// - `WEBHOOK_URL`: Generic webhook URL (optional)
+    /// - `WEBHOOK_METHOD`: HTTP method template (default: "POST")
+    /// - `WEBHOOK_BODY_TEMPLATE`: Handlebars body template (default: the alert context as JSON)
+    /// - `WEBHOOK_BODY_TEMPLATE_FILE`: File containing the body template, alternative to `WEBHOOK_BODY_TEMPLATE`
+    /// - `WEBHOOK_HEADERS`: JSON object of header name to value templates (optional)
+    /// - `WEBHOOK_SECRET`: Secret for HMAC-SHA256 signing of the body (optional)
+    /// - `SMTP_HOST`: SMTP server host name (optional, enables email alerts)
+    /// - `SMTP_PORT`: SMTP server port (default: 587, 465 or 25 depending on `SMTP_SECURITY`)
+    /// - `SMTP_SECURITY`: "starttls", "tls" or "none" (default: "starttls")
+    /// - `SMTP_USERNAME` / `SMTP_PASSWORD`: SMTP credentials (optional)
+    /// - `SMTP_FROM`: Sender address (required with `SMTP_HOST`)
+    /// - `SMTP_TO`: Comma-separated recipient addresses (required with `SMTP_HOST`)
+    /// - `SMTP_DIGEST_MINUTES`: Batch non-critical email alerts for this many minutes (default: disabled)
+    /// - `ALERT_ROUTES`: Alerts per notifier as `notifier=selector,...;...`, selectors being alert types or severities (default: every notifier receives every alert)
+    /// - `UPLOAD_LIMITS`: Image upload limits in bytes per notifier as `notifier=bytes;...` (default: each backend's own limit)
+    /// - `DIGEST_SCHEDULE`: Post a digest of the printer's statistics, `daily HH:MM` or `weekly <weekday> HH:MM` (optional)
+    /// - `DIGEST_NOTIFIERS`: Comma-separated notifiers receiving the digest (default: all)
+    /// - `LOCALE`: Locale of alert texts, bundled: "en", "de", "fr", "es" (default: "en")
+    /// - `ALERT_TEMPLATES_DIR`: Directory with `<locale>.json` files overriding alert templates (optional)
+    /// - `QUIET_HOURS`: Quiet hours per notifier as `notifier=HH:MM-HH:MM;...`, holding non-critical alerts (optional)
+    /// - `CLIP_SECONDS`: Seconds of recent frames attached to failure and pause alerts as an animated GIF, 0 to disable (default: 30)
+    /// - `CLIP_MAX_BYTES`: Maximum size of an animated clip (default: 8000000)
+    /// - `PAUSE_VERIFY_SECONDS`: Time to wait for the printer to report `paused` after a pause request, 0 to not verify (default: 30)
+    /// - `PAUSE_ATTEMPTS`: Pause requests sent before escalating (default: 2)
+    /// - `PAUSE_ESCALATION`: "none", "cancel" or "emergency_stop" when the printer does not pause (default: "none")
     /// - `MOONRAKER_API_URL`: Moonraker API URL (required)
+    /// - `MOONRAKER_API_KEY`: Moonraker API key, sent as `X-Api-Key` (optional)
+    /// - `MOONRAKER_USERNAME` / `MOONRAKER_PASSWORD`: Moonraker user to log in as instead of an API key (optional)
+    /// - `MOONRAKER_WEBSOCKET`: Follow the printer state over Moonraker's WebSocket (default: "true")
+    /// - `PRINTER_WEB_URL`: Mainsail/Fluidd URL opened from push notifications (default: Moonraker host)
+    /// - `PRINTER_NAME`: Name of the printer used in alerts (default: Moonraker host name)
+    /// - `PUBLIC_URL`: Externally reachable base URL of the HTTP server, used for webhook image URLs (optional)
     /// - `FLIP_IMAGE`: Whether to flip images horizontally (default: "false")
-    /// - `DISPLAY_CAMERA_INDEX`: Optional index of the camera to display (default: 0)
+    /// - `DISPLAY_CAMERA_INDEX`: Optional index of the camera to display, must be below the number of cameras (default: 0)
+    /// - `HTTP_BIND_ADDRESS`: HTTP server listen address, empty to disable (default: "127.0.0.1:8080")
+    /// - `API_TOKEN`: Bearer token for the control API (optional, API disabled if unset)
+    /// - `MQTT_HOST`: MQTT broker host name (optional, enables the Home Assistant integration)
+    /// - `MQTT_PORT`: MQTT broker port (default: 1883)
+    /// - `MQTT_USERNAME` / `MQTT_PASSWORD`: MQTT credentials (optional)
+    /// - `MQTT_DISCOVERY_PREFIX`: Home Assistant discovery prefix (default: "homeassistant")
+    /// - `MQTT_NODE_ID`: Unique ID of this printer in MQTT topics (default: "printer")
     ///
-    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+    /// At least one alert backend (Discord, Telegram, Slack, ntfy, Gotify, webhook or email) must be configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConfigError`] naming the environment variable that is
+    /// missing or invalid, with the parse error as its source.
+    pub fn load() -> Result<Self, ConfigError> {
         let label_file = PathBuf::from(
             std::env::var("LABEL_FILE").unwrap_or_else(|_| "./labels.txt".to_string()),
         );
@@ -116,18 +323,11 @@ impl Config {
         let output_dir =
             PathBuf::from(std::env::var("OUTPUT_DIR").unwrap_or_else(|_| "./output".to_string()));
 
-        let objectness_threshold = std::env::var("OBJECTNESS_THRESHOLD")
-            .unwrap_or_else(|_| "0.75".to_string())
-            .parse::<f32>()
-            .map_err(|e| format!("Invalid OBJECTNESS_THRESHOLD: {}", e))?;
+        let objectness_threshold = parse_var::<f32>("OBJECTNESS_THRESHOLD")?.unwrap_or(0.75);
 
-        let class_prob_threshold = std::env::var("CLASS_PROB_THRESHOLD")
-            .unwrap_or_else(|_| "0.75".to_string())
-            .parse::<f32>()
-            .map_err(|e| format!("Invalid CLASS_PROB_THRESHOLD: {}", e))?;
+        let class_prob_threshold = parse_var::<f32>("CLASS_PROB_THRESHOLD")?.unwrap_or(0.75);
 
-        let image_url =
-            std::env::var("IMAGE_URL").map_err(|_| "IMAGE_URL environment variable is required")?;
+        let image_url = required_var("IMAGE_URL")?;
 
         // Parse image URLs - can be single URL or comma-separated list
         let image_urls: Vec<String> = image_url
@@ -137,28 +337,200 @@ impl Config {
             .collect();
 
         if image_urls.is_empty() {
-            return Err("IMAGE_URL must contain at least one valid URL".into());
+            return Err(invalid_value(
+                "IMAGE_URL",
+                &image_url,
+                "must contain at least one valid URL",
+            ));
         }
 
-        let discord_webhook = std::env::var("DISCORD_WEBHOOK")
-            .map_err(|_| "DISCORD_WEBHOOK environment variable is required")?;
+        let discord_webhook = optional_var("DISCORD_WEBHOOK");
 
-        let moonraker_api_url = std::env::var("MOONRAKER_API_URL")
-            .map_err(|_| "MOONRAKER_API_URL environment variable is required")?;
+        let discord_channels = optional_var("DISCORD_CHANNELS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| {
+                entry
+                    .split_once('=')
+                    .map(|(name, url)| (name.trim().to_string(), url.trim().to_string()))
+                    .filter(|(name, url)| !name.is_empty() && !url.is_empty())
+                    .ok_or_else(|| {
+                        invalid_value("DISCORD_CHANNELS", entry.trim(), "expected name=url")
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let flip_image = std::env::var("FLIP_IMAGE")
-            .unwrap_or_else(|_| "false".to_string())
-            .parse::<bool>()
-            .map_err(|e| format!("Invalid FLIP_IMAGE (must be 'true' or 'false'): {}", e))?;
+        let telegram_bot_token = optional_var("TELEGRAM_BOT_TOKEN");
 
-        let display_camera_index = std::env::var("DISPLAY_CAMERA_INDEX")
-            .ok()
-            .unwrap_or_else(|| "0".to_string())
-            .parse::<usize>()
-            .ok()
-            .filter(|&i| i < image_urls.len());
+        let telegram_chat_ids: Vec<String> = std::env::var("TELEGRAM_CHAT_IDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+
+        if telegram_bot_token.is_some() && telegram_chat_ids.is_empty() {
+            return Err(required_with("TELEGRAM_CHAT_IDS", "TELEGRAM_BOT_TOKEN"));
+        }
+
+        let slack_webhook_url = optional_var("SLACK_WEBHOOK_URL");
+        let slack_bot_token = optional_var("SLACK_BOT_TOKEN");
+        let slack_channel_id = optional_var("SLACK_CHANNEL_ID");
+
+        if slack_bot_token.is_some() && slack_channel_id.is_none() {
+            return Err(required_with("SLACK_CHANNEL_ID", "SLACK_BOT_TOKEN"));
+        }
+
+        let ntfy_url = optional_var("NTFY_URL");
+        let ntfy_token = optional_var("NTFY_TOKEN");
+
+        let gotify_url = optional_var("GOTIFY_URL");
+        let gotify_token = optional_var("GOTIFY_TOKEN");
+
+        if gotify_url.is_some() && gotify_token.is_none() {
+            return Err(required_with("GOTIFY_TOKEN", "GOTIFY_URL"));
+        }
+
+        let smtp = load_smtp_settings()?;
+
+        let smtp_digest_interval = parse_var::<u64>("SMTP_DIGEST_MINUTES")?
+            .filter(|&minutes| minutes > 0)
+            .map(|minutes| Duration::from_secs(minutes * 60));
+
+        let mut routing = RoutingRules::default();
+        if let Some(routes) = optional_var("ALERT_ROUTES") {
+            routing = routing
+                .parse_routes(&routes)
+                .map_err(|e| invalid_value("ALERT_ROUTES", &routes, e))?;
+        }
+        if let Some(quiet_hours) = optional_var("QUIET_HOURS") {
+            routing = routing
+                .parse_quiet_hours(&quiet_hours)
+                .map_err(|e| invalid_value("QUIET_HOURS", &quiet_hours, e))?;
+        }
+        if let Some(notifiers) = optional_var("DIGEST_NOTIFIERS") {
+            routing = routing.with_digest_destinations(
+                notifiers
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect(),
+            );
+        }
 
-        Ok(Config {
+        let upload_limits = match optional_var("UPLOAD_LIMITS") {
+            Some(limits) => crate::routing::parse_entries(&limits)
+                .and_then(|entries| {
+                    entries
+                        .into_iter()
+                        .map(|(name, bytes)| {
+                            let bytes = bytes
+                                .parse::<usize>()
+                                .map_err(|e| format!("invalid limit for {}: {}", name, e))?;
+                            Ok((name, bytes))
+                        })
+                        .collect::<Result<BTreeMap<_, _>, String>>()
+                })
+                .map_err(|e| invalid_value("UPLOAD_LIMITS", &limits, e))?,
+            None => BTreeMap::new(),
+        };
+
+        let digest_schedule = parse_var::<DigestSchedule>("DIGEST_SCHEDULE")?;
+
+        let locale = optional_var("LOCALE")
+            .map(|locale| locale.trim().to_lowercase())
+            .unwrap_or_else(|| "en".to_string());
+        let templates_dir = optional_var("ALERT_TEMPLATES_DIR").map(PathBuf::from);
+
+        let clip_window =
+            Some(parse_var::<u64>("CLIP_SECONDS")?.unwrap_or(constants::DEFAULT_CLIP_SECONDS))
+                .filter(|&seconds| seconds > 0)
+                .map(Duration::from_secs);
+        let clip_max_bytes =
+            parse_var::<usize>("CLIP_MAX_BYTES")?.unwrap_or(constants::DEFAULT_CLIP_MAX_BYTES);
+
+        let pause_verification = PauseVerification {
+            timeout: Duration::from_secs(
+                parse_var::<u64>("PAUSE_VERIFY_SECONDS")?
+                    .unwrap_or(constants::DEFAULT_PAUSE_VERIFY_SECONDS),
+            ),
+            attempts: parse_var::<u32>("PAUSE_ATTEMPTS")?
+                .unwrap_or(constants::DEFAULT_PAUSE_ATTEMPTS)
+                .max(1),
+            escalation: parse_var("PAUSE_ESCALATION")?.unwrap_or_default(),
+        };
+
+        let moonraker_api_url = required_var("MOONRAKER_API_URL")?;
+
+        let moonraker_credentials = match (
+            optional_var("MOONRAKER_API_KEY"),
+            optional_var("MOONRAKER_USERNAME"),
+            optional_var("MOONRAKER_PASSWORD"),
+        ) {
+            (None, None, None) => MoonrakerCredentials::None,
+            (Some(key), None, None) => MoonrakerCredentials::ApiKey(key),
+            (None, Some(username), Some(password)) => {
+                MoonrakerCredentials::Login { username, password }
+            }
+            (Some(_), username, _) => {
+                return Err(ConfigError::Conflicting {
+                    var_name: "MOONRAKER_API_KEY".to_string(),
+                    conflicts_with: if username.is_some() {
+                        "MOONRAKER_USERNAME"
+                    } else {
+                        "MOONRAKER_PASSWORD"
+                    }
+                    .to_string(),
+                });
+            }
+            (None, Some(_), None) => {
+                return Err(required_with("MOONRAKER_PASSWORD", "MOONRAKER_USERNAME"));
+            }
+            (None, None, Some(_)) => {
+                return Err(required_with("MOONRAKER_USERNAME", "MOONRAKER_PASSWORD"));
+            }
+        };
+
+        let moonraker_websocket = parse_var::<bool>("MOONRAKER_WEBSOCKET")?.unwrap_or(true);
+
+        let printer_web_url =
+            optional_var("PRINTER_WEB_URL").or_else(|| default_printer_web_url(&moonraker_api_url));
+
+        let printer_name = optional_var("PRINTER_NAME").unwrap_or_else(|| {
+            reqwest::Url::parse(&moonraker_api_url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| "printer".to_string())
+        });
+
+        let public_url = optional_var("PUBLIC_URL");
+
+        let webhook = load_webhook_settings(&printer_name, &public_url)?;
+
+        let flip_image = parse_var::<bool>("FLIP_IMAGE")?.unwrap_or(false);
+
+        let display_camera_index = match parse_var::<usize>("DISPLAY_CAMERA_INDEX")? {
+            Some(index) if index >= image_urls.len() => {
+                return Err(invalid_value(
+                    "DISPLAY_CAMERA_INDEX",
+                    &index.to_string(),
+                    format!("only {} camera(s) configured", image_urls.len()),
+                ));
+            }
+            index => Some(index.unwrap_or(0)),
+        };
+
+        let http_bind_address = Some(
+            std::env::var("HTTP_BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1:8080".to_string()),
+        )
+        .filter(|address| !address.trim().is_empty());
+
+        let api_token = optional_var("API_TOKEN");
+
+        let mqtt = load_mqtt_settings()?;
+
+        let config = Config {
             label_file,
             model_cfg,
             weights,
@@ -167,10 +539,212 @@ impl Config {
             class_prob_threshold,
             image_urls,
             discord_webhook,
+            discord_channels,
+            telegram_bot_token,
+            telegram_chat_ids,
+            slack_webhook_url,
+            slack_bot_token,
+            slack_channel_id,
+            ntfy_url,
+            ntfy_token,
+            gotify_url,
+            gotify_token,
+            smtp,
+            smtp_digest_interval,
+            routing,
+            upload_limits,
+            digest_schedule,
+            locale,
+            templates_dir,
+            clip_window,
+            clip_max_bytes,
+            pause_verification,
             moonraker_api_url,
+            moonraker_credentials,
+            moonraker_websocket,
+            printer_web_url,
+            printer_name,
+            webhook,
             flip_image,
             display_camera_index,
+            http_bind_address,
+            mqtt,
+            api_token,
+        };
+
+        if !config.has_alert_backend() {
+            return Err(ConfigError::NoAlertBackend);
+        }
+
+        Ok(config)
+    }
+
+    /// Whether at least one alert backend is configured.
+    pub fn has_alert_backend(&self) -> bool {
+        self.discord_webhook.is_some()
+            || !self.discord_channels.is_empty()
+            || self.telegram_bot_token.is_some()
+            || self.slack_webhook_url.is_some()
+            || self.slack_bot_token.is_some()
+            || self.ntfy_url.is_some()
+            || self.gotify_url.is_some()
+            || self.webhook.is_some()
+            || self.smtp.is_some()
+    }
+}
+
+/// Load the SMTP settings, returning `None` when `SMTP_HOST` is not set.
+fn load_smtp_settings() -> Result<Option<SmtpSettings>, ConfigError> {
+    let Some(host) = optional_var("SMTP_HOST") else {
+        return Ok(None);
+    };
+
+    let security = parse_var::<SmtpSecurity>("SMTP_SECURITY")?.unwrap_or(SmtpSecurity::StartTls);
+
+    let port = parse_var::<u16>("SMTP_PORT")?.unwrap_or(security.default_port());
+
+    let from = optional_var("SMTP_FROM").ok_or_else(|| required_with("SMTP_FROM", "SMTP_HOST"))?;
+
+    let to: Vec<String> = optional_var("SMTP_TO")
+        .unwrap_or_default()
+        .split(',')
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect();
+
+    if to.is_empty() {
+        return Err(required_with("SMTP_TO", "SMTP_HOST"));
+    }
+
+    Ok(Some(SmtpSettings {
+        host,
+        port,
+        security,
+        username: optional_var("SMTP_USERNAME"),
+        password: optional_var("SMTP_PASSWORD"),
+        from,
+        to,
+    }))
+}
+
+/// Load the generic webhook settings, returning `None` when `WEBHOOK_URL` is not set.
+fn load_webhook_settings(
+    printer_name: &str,
+    public_url: &Option<String>,
+) -> Result<Option<WebhookSettings>, ConfigError> {
+    let Some(url) = optional_var("WEBHOOK_URL") else {
+        return Ok(None);
+    };
+
+    let body_template = match optional_var("WEBHOOK_BODY_TEMPLATE_FILE") {
+        Some(path) => Some(
+            std::fs::read_to_string(&path)
+                .map_err(|source| ConfigError::FileReadError { path, source })?,
+        ),
+        None => optional_var("WEBHOOK_BODY_TEMPLATE"),
+    };
+
+    let header_templates = match optional_var("WEBHOOK_HEADERS") {
+        Some(headers) => {
+            serde_json::from_str::<std::collections::BTreeMap<String, String>>(&headers)
+                // Headers usually hold credentials, so the value is not repeated in the error
+                .map_err(|e| invalid_value("WEBHOOK_HEADERS", "<hidden>", e))?
+        }
+        None => Default::default(),
+    };
+
+    Ok(Some(WebhookSettings {
+        url,
+        method_template: optional_var("WEBHOOK_METHOD").unwrap_or_else(|| "POST".to_string()),
+        body_template,
+        header_templates,
+        secret: optional_var("WEBHOOK_SECRET"),
+        printer_name: printer_name.to_string(),
+        public_url: public_url.clone(),
+    }))
+}
+
+/// Load the MQTT settings, returning `None` when `MQTT_HOST` is not set.
+fn load_mqtt_settings() -> Result<Option<MqttSettings>, ConfigError> {
+    let Some(host) = optional_var("MQTT_HOST") else {
+        return Ok(None);
+    };
+
+    let port = parse_var::<u16>("MQTT_PORT")?.unwrap_or(1883);
+
+    let node_id = optional_var("MQTT_NODE_ID").unwrap_or_else(|| "printer".to_string());
+    if !node_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(invalid_value(
+            "MQTT_NODE_ID",
+            &node_id,
+            "may only contain letters, digits, '_' and '-'",
+        ));
+    }
+
+    Ok(Some(MqttSettings {
+        host,
+        port,
+        username: optional_var("MQTT_USERNAME"),
+        password: optional_var("MQTT_PASSWORD"),
+        discovery_prefix: optional_var("MQTT_DISCOVERY_PREFIX")
+            .unwrap_or_else(|| "homeassistant".to_string()),
+        node_id,
+    }))
+}
+
+/// Derive the web interface URL from the Moonraker URL by dropping the API port,
+/// e.g. `http://printer.local:7125` becomes `http://printer.local/`.
+fn default_printer_web_url(moonraker_api_url: &str) -> Option<String> {
+    let mut url = reqwest::Url::parse(moonraker_api_url).ok()?;
+    url.set_port(None).ok()?;
+    url.set_path("/");
+    Some(url.to_string())
+}
+
+/// Read an optional environment variable, treating empty values as unset.
+fn optional_var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Read a required environment variable, treating empty values as unset.
+fn required_var(name: &str) -> Result<String, ConfigError> {
+    optional_var(name).ok_or_else(|| ConfigError::MissingEnvVar {
+        var_name: name.to_string(),
+    })
+}
+
+/// Parse an optional environment variable, `None` when it is unset or empty.
+fn parse_var<T>(name: &str) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: Into<BoxError>,
+{
+    optional_var(name)
+        .map(|value| {
+            value
+                .parse::<T>()
+                .map_err(|e| invalid_value(name, &value, e))
         })
+        .transpose()
+}
+
+fn invalid_value(field: &str, value: &str, source: impl Into<BoxError>) -> ConfigError {
+    ConfigError::InvalidValue {
+        field: field.to_string(),
+        value: value.to_string(),
+        source: source.into(),
+    }
+}
+
+fn required_with(var_name: &str, required_by: &str) -> ConfigError {
+    ConfigError::RequiredWith {
+        var_name: var_name.to_string(),
+        required_by: required_by.to_string(),
     }
 }
 
@@ -182,12 +756,152 @@ pub mod constants {
     /// Delay between retry attempts in seconds.
     pub const RETRY_DELAY_SECONDS: u64 = 15;
 
+    /// Timeout in seconds of HTTP requests, the default of reqwest's blocking client.
+    pub const HTTP_TIMEOUT_SECONDS: u64 = 30;
+
+    /// Delay in seconds before reconnecting to the Moonraker WebSocket.
+    pub const MOONRAKER_RECONNECT_SECONDS: u64 = 5;
+
+    /// Timeout in seconds for establishing the Moonraker WebSocket connection.
+    pub const MOONRAKER_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+
+    /// Interval in seconds after which an idle Moonraker WebSocket is pinged.
+    pub const MOONRAKER_READ_TIMEOUT_SECONDS: u64 = 10;
+
+    /// Time in seconds without any message after which the Moonraker
+    /// WebSocket is considered dead, also the timeout of JSON-RPC requests.
+    pub const MOONRAKER_IDLE_TIMEOUT_SECONDS: u64 = 30;
+
+    /// Default time in seconds to wait for the printer to report `paused`
+    /// after a pause request.
+    pub const DEFAULT_PAUSE_VERIFY_SECONDS: u64 = 30;
+
+    /// Default number of pause requests sent before escalating.
+    pub const DEFAULT_PAUSE_ATTEMPTS: u32 = 2;
+
+    /// Interval in milliseconds at which the printer state is checked
+    /// while verifying a pause.
+    pub const PAUSE_VERIFY_POLL_INTERVAL_MILLIS: u64 = 500;
+
+    /// Time in seconds before pausing again after the printer ignored an
+    /// automatic pause during the same print job.
+    pub const PAUSE_RETRY_COOLDOWN_SECONDS: u64 = 600;
+
     /// Threshold for print failure count before pausing the printer.
     pub const PRINT_FAILURE_THRESHOLD: u32 = 3;
 
     /// Probability threshold for triggering alerts (0.0 to 1.0).
     pub const ALERT_PROBABILITY_THRESHOLD: f32 = 0.5;
 
+    /// Number of recent failure events kept for false positive acknowledgement.
+    pub const MAX_FAILURE_EVENTS: usize = 20;
+
+    /// Subdirectory of the output directory where false positive samples are stored.
+    pub const NEGATIVE_SAMPLES_DIR: &str = "negatives";
+
+    /// Maximum time in seconds between monitoring loop iterations before
+    /// the liveness check fails.
+    pub const LIVENESS_TIMEOUT_SECONDS: i64 = 600;
+
+    /// Number of failure score samples kept for the dashboard chart.
+    pub const SCORE_HISTORY_LENGTH: usize = 300;
+
+    /// Number of entries kept in the recent event log.
+    pub const EVENT_LOG_LENGTH: usize = 100;
+
+    /// Number of threads serving HTTP requests.
+    pub const HTTP_WORKER_THREADS: usize = 4;
+
+    /// Maximum number of dashboard Server-Sent Events clients served at once.
+    pub const MAX_STREAM_CLIENTS: usize = 8;
+
+    /// Interval in seconds between dashboard Server-Sent Events updates.
+    pub const DASHBOARD_UPDATE_INTERVAL_SECONDS: u64 = 2;
+
+    /// Interval in seconds between MQTT state updates.
+    pub const MQTT_PUBLISH_INTERVAL_SECONDS: u64 = 5;
+
+    /// Maximum MQTT packet size in bytes, large enough for annotated frames.
+    pub const MQTT_MAX_PACKET_SIZE: usize = 10 * 1024 * 1024;
+
+    /// Duration in minutes alerts are snoozed by the MQTT snooze button.
+    pub const MQTT_SNOOZE_MINUTES: i64 = 30;
+
+    /// Subdirectory of the output directory where undelivered alerts are queued.
+    pub const ALERT_QUEUE_DIR: &str = "alert_queue";
+
+    /// Subdirectory of the output directory where alerts waiting for the
+    /// next email digest are kept.
+    pub const EMAIL_DIGEST_DIR: &str = "email_digest";
+
+    /// Subdirectory of the output directory where the images linked from
+    /// webhook alerts are kept.
+    pub const ALERT_IMAGE_DIR: &str = "alert_images";
+
+    /// Number of webhook alert images kept on disk.
+    pub const ALERT_IMAGE_RETENTION: usize = 200;
+
+    /// Maximum interval in seconds between checks for due queued alerts.
+    pub const ALERT_QUEUE_POLL_INTERVAL_SECONDS: u64 = 1;
+
+    /// Delay in seconds before retrying a failed alert, doubled after every further failure.
+    pub const ALERT_RETRY_BASE_DELAY_SECONDS: u64 = 5;
+
+    /// Upper bound in seconds of the delay between alert delivery attempts.
+    pub const ALERT_RETRY_MAX_DELAY_SECONDS: u64 = 600;
+
+    /// Failed delivery attempts after which a queued alert is dropped.
+    pub const ALERT_QUEUE_MAX_ATTEMPTS: u32 = 50;
+
+    /// Default number of seconds of recent frames in an animated alert clip.
+    pub const DEFAULT_CLIP_SECONDS: u64 = 30;
+
+    /// Default maximum size of an animated alert clip, below Discord's and
+    /// Telegram's upload limits.
+    pub const DEFAULT_CLIP_MAX_BYTES: usize = 8_000_000;
+
+    /// Maximum number of frames buffered per camera for animated clips.
+    pub const CLIP_MAX_FRAMES: usize = 60;
+
+    /// Width in pixels animated clips are scaled down to.
+    pub const CLIP_MAX_WIDTH: u32 = 480;
+
+    /// Smallest width in pixels tried when shrinking a clip to fit the size limit.
+    pub const CLIP_MIN_WIDTH: u32 = 120;
+
+    /// Fewest frames kept when dropping frames to fit the size limit.
+    pub const CLIP_MIN_FRAMES: usize = 5;
+
+    /// Display time of each clip frame in milliseconds.
+    pub const CLIP_FRAME_DELAY_MS: u32 = 250;
+
+    /// GIF quantization speed from 1 (best quality) to 30 (fastest).
+    pub const CLIP_ENCODER_SPEED: i32 = 20;
+
+    /// Directory (relative to the output directory) end-of-print reports are saved to.
+    pub const REPORTS_DIR: &str = "reports";
+
+    /// Number of most suspicious frames shown in the mosaic of a print report.
+    pub const REPORT_MOSAIC_FRAMES: usize = 6;
+
+    /// Number of columns of the suspicious frames mosaic.
+    pub const REPORT_MOSAIC_COLUMNS: usize = 3;
+
+    /// Width in pixels of the print report chart and mosaic.
+    pub const REPORT_CHART_WIDTH: u32 = 900;
+
+    /// Height in pixels of the print report chart.
+    pub const REPORT_CHART_HEIGHT: u32 = 240;
+
+    /// JPEG qualities tried, per size, when compressing an image to fit an upload limit.
+    pub const UPLOAD_JPEG_QUALITIES: [u8; 3] = [85, 65, 45];
+
+    /// Smallest width in pixels an image is scaled down to to fit an upload limit.
+    pub const UPLOAD_MIN_WIDTH: u32 = 320;
+
+    /// Interval in seconds between progress updates of the print job message.
+    pub const PRINT_PROGRESS_INTERVAL_SECONDS: u64 = 60;
+
     /// URL for downloading model weights if they don't exist locally.
     pub const MODEL_WEIGHTS_URL: &str =
         "https://tsd-pub-static.s3.amazonaws.com/ml-models/model-weights-8be06cde4e.darknet";
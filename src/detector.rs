@@ -1,8 +1,10 @@
 #![allow(dead_code)]
-use anyhow::Result;
 use darknet::{BBox, Image, Network};
 use log::{debug, info};
-use std::{fs, path::PathBuf};
+use std::{fs, path::PathBuf, time::Instant};
+
+use crate::error::{DetectionError, NetworkError};
+use crate::metrics;
 
 /// Print failure detection service using YOLO/Darknet neural networks.
 ///
@@ -39,9 +41,13 @@ impl FailureDetector {
         labels_path: PathBuf,
         objectness_threshold: f32,
         class_prob_threshold: f32,
-    ) -> Result<Self> {
+    ) -> Result<Self, DetectionError> {
         // Load class labels
-        let labels = fs::read_to_string(labels_path)?
+        let labels = fs::read_to_string(&labels_path)
+            .map_err(|source| DetectionError::LabelsLoadFailed {
+                path: labels_path.display().to_string(),
+                source,
+            })?
             .lines()
             .map(ToOwned::to_owned)
             .collect::<Vec<_>>();
@@ -57,7 +63,13 @@ impl FailureDetector {
         );
 
         // Load the neural network
-        let network = Network::load(model_cfg, Some(weights_path), false)?;
+        let network = Network::load(&model_cfg, Some(&weights_path), false).map_err(|e| {
+            DetectionError::ModelLoadFailed {
+                model_cfg: model_cfg.display().to_string(),
+                weights: weights_path.display().to_string(),
+                source: e.into(),
+            }
+        })?;
 
         Ok(Self {
             network,
@@ -83,7 +95,10 @@ impl FailureDetector {
     /// - Download fails
     /// - File cannot be written to disk
     /// - Remote server returns error status
-    pub fn ensure_weights_downloaded(weights_path: &PathBuf, download_url: &str) -> Result<()> {
+    pub fn ensure_weights_downloaded(
+        weights_path: &PathBuf,
+        download_url: &str,
+    ) -> Result<(), DetectionError> {
         // download model weights to model-weights.darknet if it doesn't exist
         if !weights_path.exists() {
             info!(
@@ -91,16 +106,20 @@ impl FailureDetector {
                 weights_path.display(),
                 download_url
             );
-            let response = reqwest::blocking::get(download_url)?;
-            if !response.status().is_success() {
-                return Err(anyhow::anyhow!(
-                    "Failed to download model weights from {}: {}",
-                    download_url,
-                    response.status()
-                ));
-            }
-            let model_weights_data = response.bytes()?;
-            fs::write(weights_path, model_weights_data)?;
+            let download_failed = |e| DetectionError::WeightsDownloadFailed {
+                url: download_url.to_string(),
+                source: NetworkError::from_reqwest(download_url, e),
+            };
+            let model_weights_data = reqwest::blocking::get(download_url)
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.bytes())
+                .map_err(download_failed)?;
+            fs::write(weights_path, model_weights_data).map_err(|source| {
+                DetectionError::WeightsSaveFailed {
+                    path: weights_path.display().to_string(),
+                    source,
+                }
+            })?;
         } else {
             info!("Model weights already exist at {}", weights_path.display());
         }
@@ -128,7 +147,10 @@ impl FailureDetector {
     ///
     /// Returns an error if neural network inference fails.
     ///
-    pub fn detect_failures_from_image(&mut self, image: &Image) -> Result<Vec<Detection>> {
+    pub fn detect_failures_from_image(
+        &mut self,
+        image: &Image,
+    ) -> Result<Vec<Detection>, DetectionError> {
         debug!(
             "Processing image with dimensions: {}x{}x{}",
             image.width(),
@@ -158,14 +180,17 @@ impl FailureDetector {
                 chunk.swap(0, 2); // Swap R and B
             }
         } else if image.channels() != 1 && image.channels() != 3 {
-            return Err(anyhow::anyhow!(
-                "Unsupported image channel count: {}",
-                image.channels()
-            ));
+            return Err(DetectionError::InferenceFailed {
+                reason: format!("unsupported image channel count: {}", image.channels()),
+            });
         }
 
         // Run object detection with NMS parameters
+        let inference_start = Instant::now();
         let detections = self.network.predict(image, 0.25, 0.5, 0.45, true);
+        metrics::get()
+            .inference_duration
+            .observe(inference_start.elapsed().as_secs_f64());
 
         debug!("Raw detections count: {}", detections.len());
         debug!(
@@ -205,6 +230,7 @@ impl FailureDetector {
                     // Only add to results if it meets the threshold
                     if prob > self.class_prob_threshold {
                         let bbox = *det.bbox();
+                        metrics::get().detections.with_label_values(&[&label]).inc();
 
                         results.push(Detection {
                             label,
@@ -0,0 +1,264 @@
+#![allow(dead_code)]
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, Weekday};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// When the fleet digest is posted: every day or once a week, at a local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestSchedule {
+    /// Day of the week for weekly digests, `None` for daily digests.
+    pub weekday: Option<Weekday>,
+    /// Local time of day the digest is posted at.
+    pub time: NaiveTime,
+}
+
+impl DigestSchedule {
+    /// Get the next time the digest is due, strictly after `now`.
+    pub fn next_after(&self, now: DateTime<Local>) -> DateTime<Local> {
+        let mut date = now.date_naive();
+        loop {
+            let matches_day = self.weekday.is_none_or(|weekday| date.weekday() == weekday);
+            if matches_day
+                && let Some(candidate) = date
+                    .and_time(self.time)
+                    .and_local_timezone(Local)
+                    .earliest()
+                && candidate > now
+            {
+                return candidate;
+            }
+            date += ChronoDuration::days(1);
+        }
+    }
+
+    /// Get the name of the digest period, `daily` or `weekly`.
+    pub fn period(&self) -> &'static str {
+        if self.weekday.is_some() {
+            "weekly"
+        } else {
+            "daily"
+        }
+    }
+}
+
+impl FromStr for DigestSchedule {
+    type Err = String;
+
+    /// Parse `daily HH:MM` or `weekly <weekday> HH:MM`, e.g. `weekly mon 08:00`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let (weekday, time) = match parts.as_slice() {
+            [period, time] if period.eq_ignore_ascii_case("daily") => (None, *time),
+            [period, weekday, time] if period.eq_ignore_ascii_case("weekly") => (
+                Some(
+                    weekday
+                        .parse::<Weekday>()
+                        .map_err(|_| format!("invalid weekday '{}'", weekday))?,
+                ),
+                *time,
+            ),
+            _ => {
+                return Err(format!(
+                    "invalid digest schedule '{}' (expected 'daily HH:MM' or 'weekly <weekday> HH:MM')",
+                    s
+                ));
+            }
+        };
+
+        Ok(Self {
+            weekday,
+            time: NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|e| format!("invalid time '{}': {}", time, e))?,
+        })
+    }
+}
+
+/// Share of time a service was available, sampled by the monitoring loop.
+#[derive(Debug, Clone, Default)]
+pub struct Uptime {
+    up: Duration,
+    total: Duration,
+    last: Option<(Instant, bool)>,
+}
+
+impl Uptime {
+    /// Record whether the service is currently available.
+    ///
+    /// The time since the previous sample counts as up if the service was
+    /// available at the previous sample.
+    pub fn record(&mut self, available: bool) {
+        let now = Instant::now();
+        if let Some((since, was_available)) = self.last {
+            let elapsed = now.duration_since(since);
+            self.total += elapsed;
+            if was_available {
+                self.up += elapsed;
+            }
+        }
+        self.last = Some((now, available));
+    }
+
+    /// Get the uptime in percent, `None` before any time has been observed.
+    pub fn percent(&self) -> Option<f64> {
+        (!self.total.is_zero()).then(|| self.up.as_secs_f64() / self.total.as_secs_f64() * 100.0)
+    }
+
+    /// Start a new observation period, keeping the current availability.
+    fn restart(&mut self) {
+        self.record(self.last.is_some_and(|(_, available)| available));
+        self.up = Duration::ZERO;
+        self.total = Duration::ZERO;
+    }
+}
+
+/// Statistics of one printer over a digest period.
+#[derive(Debug, Clone)]
+pub struct FleetDigest {
+    /// Name of the printer.
+    pub printer_name: String,
+    /// Digest period, `daily` or `weekly`.
+    pub period: &'static str,
+    /// Start of the period.
+    pub started_at: DateTime<Local>,
+    /// End of the period, set once the digest is due.
+    pub ended_at: Option<DateTime<Local>>,
+    /// Prints that finished with the `complete` state.
+    pub prints_completed: u32,
+    /// Failure events detected.
+    pub failures: u32,
+    /// Prints paused by the monitor.
+    pub pauses: u32,
+    /// Failure events acknowledged as false positives.
+    pub false_positives: u64,
+    /// Camera availability.
+    pub camera: Uptime,
+    /// Moonraker availability.
+    pub moonraker: Uptime,
+    inference_total: Duration,
+    inferences: u64,
+}
+
+impl FleetDigest {
+    fn new(printer_name: &str, period: &'static str) -> Self {
+        Self {
+            printer_name: printer_name.to_string(),
+            period,
+            started_at: Local::now(),
+            ended_at: None,
+            prints_completed: 0,
+            failures: 0,
+            pauses: 0,
+            false_positives: 0,
+            camera: Uptime::default(),
+            moonraker: Uptime::default(),
+            inference_total: Duration::ZERO,
+            inferences: 0,
+        }
+    }
+
+    /// Get the average inference time, `None` if no frame was analysed.
+    pub fn average_inference_time(&self) -> Option<Duration> {
+        (self.inferences > 0).then(|| self.inference_total / self.inferences as u32)
+    }
+
+    /// Get the number of frames analysed.
+    pub fn inferences(&self) -> u64 {
+        self.inferences
+    }
+}
+
+/// Collects fleet digest statistics and hands them out when the digest is due.
+pub struct DigestScheduler {
+    schedule: DigestSchedule,
+    next_run: DateTime<Local>,
+    current: FleetDigest,
+    acknowledged_at_start: u64,
+}
+
+impl DigestScheduler {
+    /// Start collecting statistics for the first digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `printer_name` - Name of the monitored printer
+    /// * `schedule` - When digests are due
+    pub fn new(printer_name: &str, schedule: DigestSchedule) -> Self {
+        Self {
+            schedule,
+            next_run: schedule.next_after(Local::now()),
+            current: FleetDigest::new(printer_name, schedule.period()),
+            acknowledged_at_start: 0,
+        }
+    }
+
+    /// Get the time the next digest is due.
+    pub fn next_run(&self) -> DateTime<Local> {
+        self.next_run
+    }
+
+    /// Record a finished print with its final printer state.
+    pub fn record_print_finished(&mut self, final_state: &str) {
+        if final_state == "complete" {
+            self.current.prints_completed += 1;
+        }
+    }
+
+    /// Record a detected failure event.
+    pub fn record_failure(&mut self) {
+        self.current.failures += 1;
+    }
+
+    /// Record a pause triggered by the monitor.
+    pub fn record_pause(&mut self) {
+        self.current.pauses += 1;
+    }
+
+    /// Record the duration of one inference.
+    pub fn record_inference(&mut self, duration: Duration) {
+        self.current.inference_total += duration;
+        self.current.inferences += 1;
+    }
+
+    /// Record whether the camera delivered an image.
+    pub fn record_camera(&mut self, available: bool) {
+        self.current.camera.record(available);
+    }
+
+    /// Record whether Moonraker responded.
+    pub fn record_moonraker(&mut self, available: bool) {
+        self.current.moonraker.record(available);
+    }
+
+    /// Return the finished digest if it is due at `now` and start the next period.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    /// * `acknowledged_total` - Number of false positive acknowledgements since startup
+    pub fn poll(&mut self, now: DateTime<Local>, acknowledged_total: u64) -> Option<FleetDigest> {
+        if now < self.next_run {
+            return None;
+        }
+
+        let mut next = FleetDigest::new(&self.current.printer_name, self.schedule.period());
+        next.started_at = now;
+        next.camera = self.current.camera.clone();
+        next.camera.restart();
+        next.moonraker = self.current.moonraker.clone();
+        next.moonraker.restart();
+
+        let mut digest = std::mem::replace(&mut self.current, next);
+        digest
+            .camera
+            .record(digest.camera.last.is_some_and(|(_, up)| up));
+        digest
+            .moonraker
+            .record(digest.moonraker.last.is_some_and(|(_, up)| up));
+        digest.ended_at = Some(now);
+        digest.false_positives = acknowledged_total.saturating_sub(self.acknowledged_at_start);
+
+        self.acknowledged_at_start = acknowledged_total;
+        self.next_run = self.schedule.next_after(now);
+        Some(digest)
+    }
+}
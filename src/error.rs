@@ -1,14 +1,27 @@
 #![allow(dead_code)]
+//! Custom error types for the Print Guardian application.
+//!
+//! This module defines specific error types that can occur throughout the
+//! application, providing better error handling and more informative
+//! error messages for different failure scenarios.
+//!
+//! Errors keep the error that caused them as their [`source`](Error::source)
+//! instead of copying its message, so callers can match on the kind of
+//! failure at every level. Use [`ErrorChain`] to log an error together with
+//! its causes.
+
+use std::error::Error;
 use std::fmt;
+use std::io;
 
-/// Custom error types for the Print Guardian application.
-///
-/// This module defines specific error types that can occur throughout the
-/// application, providing better error handling and more informative
-/// error messages for different failure scenarios.
+use crate::config::constants;
+
+/// Boxed error used as the source of errors from other libraries.
+pub type BoxError = Box<dyn Error + Send + Sync>;
 
 /// Main error type for Print Guardian operations.
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum PrintGuardianError {
     /// Errors related to image fetching and processing.
     ImageError(ImageError),
@@ -33,42 +46,59 @@ pub enum PrintGuardianError {
 #[derive(Debug)]
 pub enum ImageError {
     /// Failed to download image from remote URL.
-    DownloadFailed { url: String, reason: String },
+    DownloadFailed { url: String, source: NetworkError },
+
+    /// Every attempt to fetch an image failed, `source` is the last failure.
+    RetriesExhausted {
+        attempts: u32,
+        source: Box<ImageError>,
+    },
 
     /// Image file could not be saved to disk.
-    SaveFailed { path: String, reason: String },
+    SaveFailed { path: String, source: io::Error },
 
     /// Image format is not supported or corrupted.
-    InvalidFormat { path: String },
+    InvalidFormat { source: image::ImageError },
+
+    /// Image could not be encoded after processing.
+    EncodeFailed { source: image::ImageError },
 
     /// Image file could not be opened or read.
-    ReadFailed { path: String, reason: String },
+    ReadFailed { path: String, source: io::Error },
 }
 
 /// Errors specific to neural network detection operations.
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum DetectionError {
-    /// Model configuration file could not be loaded.
-    ModelConfigLoadFailed { path: String, reason: String },
-
-    /// Model weights file could not be loaded.
-    WeightsLoadFailed { path: String, reason: String },
+    /// Model configuration or weights could not be loaded.
+    ModelLoadFailed {
+        model_cfg: String,
+        weights: String,
+        source: BoxError,
+    },
 
     /// Labels file could not be read or parsed.
-    LabelsLoadFailed { path: String, reason: String },
+    LabelsLoadFailed { path: String, source: io::Error },
 
     /// Neural network inference failed.
     InferenceFailed { reason: String },
 
     /// Model weights download failed.
-    WeightsDownloadFailed { url: String, reason: String },
+    WeightsDownloadFailed { url: String, source: NetworkError },
+
+    /// Downloaded model weights could not be saved.
+    WeightsSaveFailed { path: String, source: io::Error },
 }
 
 /// Errors specific to printer control operations.
 #[derive(Debug)]
 pub enum PrinterError {
     /// Failed to connect to printer API.
-    ConnectionFailed { api_url: String, reason: String },
+    ConnectionFailed {
+        api_url: String,
+        source: NetworkError,
+    },
 
     /// Printer API returned an error response.
     ApiError {
@@ -77,6 +107,9 @@ pub enum PrinterError {
         message: String,
     },
 
+    /// Printer API returned a response that could not be understood.
+    InvalidResponse { endpoint: String, source: BoxError },
+
     /// Printer is in an invalid state for the requested operation.
     InvalidState {
         requested_action: String,
@@ -90,14 +123,32 @@ pub enum PrinterError {
 /// Errors specific to alert and notification systems.
 #[derive(Debug)]
 pub enum AlertError {
-    /// Discord webhook request failed.
-    WebhookFailed { reason: String },
+    /// One or more notifiers failed to deliver an alert.
+    ///
+    /// Holds the error of every failed notifier by name; the first one is
+    /// the error's source.
+    DeliveryFailed {
+        alert_kind: String,
+        failures: Vec<(String, BoxError)>,
+    },
 
     /// Alert message formatting failed.
-    MessageFormatError { reason: String },
+    MessageFormatError { source: BoxError },
 
     /// Invalid webhook URL provided.
     InvalidWebhookUrl { url: String },
+
+    /// The alert configuration references a notifier that is not configured.
+    UnknownNotifier {
+        name: String,
+        configured: Vec<String>,
+    },
+
+    /// Notifiers, templates, the queue or its worker could not be set up.
+    SetupFailed { source: BoxError },
+
+    /// Alert could not be written to the delivery queue.
+    QueueFailed { notifier: String, source: BoxError },
 }
 
 /// Errors related to configuration and application setup.
@@ -107,24 +158,42 @@ pub enum ConfigError {
     MissingEnvVar { var_name: String },
 
     /// Configuration file could not be read.
-    FileReadError { path: String, reason: String },
+    FileReadError { path: String, source: io::Error },
 
     /// Invalid configuration values provided.
     InvalidValue {
         field: String,
         value: String,
-        reason: String,
+        source: BoxError,
     },
 
     /// Required file path does not exist.
     MissingFile { path: String },
+
+    /// Environment variable required by another one is missing.
+    RequiredWith {
+        var_name: String,
+        required_by: String,
+    },
+
+    /// Two environment variables that exclude each other are both set.
+    Conflicting {
+        var_name: String,
+        conflicts_with: String,
+    },
+
+    /// No alert backend is configured.
+    NoAlertBackend,
 }
 
 /// Errors related to network connectivity and communication.
 #[derive(Debug)]
 pub enum NetworkError {
     /// Generic network request failed.
-    RequestFailed { url: String, reason: String },
+    RequestFailed { url: String, source: BoxError },
+
+    /// Server answered with an unsuccessful HTTP status.
+    HttpStatus { url: String, status: u16 },
 
     /// Network timeout occurred.
     Timeout { url: String, timeout_seconds: u64 },
@@ -136,16 +205,121 @@ pub enum NetworkError {
     SslError { url: String, reason: String },
 }
 
+impl PrintGuardianError {
+    /// Check whether the operation may succeed if it is retried later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PrintGuardianError::ImageError(e) => e.is_transient(),
+            PrintGuardianError::PrinterError(e) => e.is_transient(),
+            PrintGuardianError::NetworkError(e) => e.is_transient(),
+            PrintGuardianError::AlertError(AlertError::DeliveryFailed { .. }) => true,
+            _ => false,
+        }
+    }
+
+    /// Check whether the application cannot keep running, because retrying
+    /// will not help without changing the configuration.
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            PrintGuardianError::PrinterError(e) => e.is_fatal(),
+            PrintGuardianError::ConfigError(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl ImageError {
+    /// Check whether fetching the image may succeed if it is retried later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ImageError::DownloadFailed { source, .. } => source.is_transient(),
+            ImageError::RetriesExhausted { source, .. } => source.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+impl PrinterError {
+    /// Check whether the request may succeed if it is retried later, e.g.
+    /// because Moonraker is restarting or did not answer in time.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PrinterError::ConnectionFailed { source, .. } => source.is_transient(),
+            PrinterError::ApiError { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Check whether the printer cannot be controlled until the
+    /// configuration is fixed, i.e. Moonraker rejects the credentials.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, PrinterError::AuthenticationFailed { .. })
+    }
+}
+
+impl NetworkError {
+    /// Classify an error of a request to `url` made with reqwest.
+    ///
+    /// Requests are made with reqwest's blocking client, which times out
+    /// after [`constants::HTTP_TIMEOUT_SECONDS`].
+    pub fn from_reqwest(url: &str, error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            return NetworkError::Timeout {
+                url: url.to_string(),
+                timeout_seconds: constants::HTTP_TIMEOUT_SECONDS,
+            };
+        }
+        if let Some(status) = error.status() {
+            return NetworkError::HttpStatus {
+                url: url.to_string(),
+                status: status.as_u16(),
+            };
+        }
+        NetworkError::RequestFailed {
+            url: url.to_string(),
+            source: error.into(),
+        }
+    }
+
+    /// Check whether the request may succeed if it is retried later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            NetworkError::RequestFailed { .. }
+            | NetworkError::Timeout { .. }
+            | NetworkError::DnsError { .. } => true,
+            NetworkError::HttpStatus { status, .. } => *status >= 500 || *status == 429,
+            NetworkError::SslError { .. } => false,
+        }
+    }
+}
+
+/// Displays an error followed by the messages of its sources, separated
+/// by colons, e.g. `Failed to connect to printer at '...': Network request
+/// to '...' failed: ...`.
+pub struct ErrorChain<'a>(pub &'a (dyn Error + 'static));
+
+impl fmt::Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(error) = source {
+            write!(f, ": {}", error)?;
+            source = error.source();
+        }
+        Ok(())
+    }
+}
+
 // Implement Display trait for user-friendly error messages
 impl fmt::Display for PrintGuardianError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            PrintGuardianError::ImageError(e) => write!(f, "Image error: {}", e),
-            PrintGuardianError::DetectionError(e) => write!(f, "Detection error: {}", e),
-            PrintGuardianError::PrinterError(e) => write!(f, "Printer error: {}", e),
-            PrintGuardianError::AlertError(e) => write!(f, "Alert error: {}", e),
-            PrintGuardianError::ConfigError(e) => write!(f, "Configuration error: {}", e),
-            PrintGuardianError::NetworkError(e) => write!(f, "Network error: {}", e),
+            PrintGuardianError::ImageError(_) => write!(f, "Image error"),
+            PrintGuardianError::DetectionError(_) => write!(f, "Detection error"),
+            PrintGuardianError::PrinterError(_) => write!(f, "Printer error"),
+            PrintGuardianError::AlertError(_) => write!(f, "Alert error"),
+            PrintGuardianError::ConfigError(_) => write!(f, "Configuration error"),
+            PrintGuardianError::NetworkError(_) => write!(f, "Network error"),
         }
     }
 }
@@ -153,17 +327,23 @@ impl fmt::Display for PrintGuardianError {
 impl fmt::Display for ImageError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ImageError::DownloadFailed { url, reason } => {
-                write!(f, "Failed to download image from '{}': {}", url, reason)
+            ImageError::DownloadFailed { url, .. } => {
+                write!(f, "Failed to download image from '{}'", url)
+            }
+            ImageError::RetriesExhausted { attempts, .. } => {
+                write!(f, "Failed to fetch image after {} attempts", attempts)
+            }
+            ImageError::SaveFailed { path, .. } => {
+                write!(f, "Failed to save image to '{}'", path)
             }
-            ImageError::SaveFailed { path, reason } => {
-                write!(f, "Failed to save image to '{}': {}", path, reason)
+            ImageError::InvalidFormat { .. } => {
+                write!(f, "Invalid or corrupted image format")
             }
-            ImageError::InvalidFormat { path } => {
-                write!(f, "Invalid or corrupted image format: {}", path)
+            ImageError::EncodeFailed { .. } => {
+                write!(f, "Failed to encode image")
             }
-            ImageError::ReadFailed { path, reason } => {
-                write!(f, "Failed to read image from '{}': {}", path, reason)
+            ImageError::ReadFailed { path, .. } => {
+                write!(f, "Failed to read image from '{}'", path)
             }
         }
     }
@@ -172,28 +352,26 @@ impl fmt::Display for ImageError {
 impl fmt::Display for DetectionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            DetectionError::ModelConfigLoadFailed { path, reason } => {
-                write!(f, "Failed to load model config from '{}': {}", path, reason)
-            }
-            DetectionError::WeightsLoadFailed { path, reason } => {
+            DetectionError::ModelLoadFailed {
+                model_cfg, weights, ..
+            } => {
                 write!(
                     f,
-                    "Failed to load model weights from '{}': {}",
-                    path, reason
+                    "Failed to load model from '{}' with weights '{}'",
+                    model_cfg, weights
                 )
             }
-            DetectionError::LabelsLoadFailed { path, reason } => {
-                write!(f, "Failed to load labels from '{}': {}", path, reason)
+            DetectionError::LabelsLoadFailed { path, .. } => {
+                write!(f, "Failed to load labels from '{}'", path)
             }
             DetectionError::InferenceFailed { reason } => {
                 write!(f, "Neural network inference failed: {}", reason)
             }
-            DetectionError::WeightsDownloadFailed { url, reason } => {
-                write!(
-                    f,
-                    "Failed to download model weights from '{}': {}",
-                    url, reason
-                )
+            DetectionError::WeightsDownloadFailed { url, .. } => {
+                write!(f, "Failed to download model weights from '{}'", url)
+            }
+            DetectionError::WeightsSaveFailed { path, .. } => {
+                write!(f, "Failed to save model weights to '{}'", path)
             }
         }
     }
@@ -202,12 +380,8 @@ impl fmt::Display for DetectionError {
 impl fmt::Display for PrinterError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            PrinterError::ConnectionFailed { api_url, reason } => {
-                write!(
-                    f,
-                    "Failed to connect to printer at '{}': {}",
-                    api_url, reason
-                )
+            PrinterError::ConnectionFailed { api_url, .. } => {
+                write!(f, "Failed to connect to printer at '{}'", api_url)
             }
             PrinterError::ApiError {
                 endpoint,
@@ -220,6 +394,9 @@ impl fmt::Display for PrinterError {
                     endpoint, status, message
                 )
             }
+            PrinterError::InvalidResponse { endpoint, .. } => {
+                write!(f, "Invalid printer API response from '{}'", endpoint)
+            }
             PrinterError::InvalidState {
                 requested_action,
                 current_state,
@@ -240,14 +417,37 @@ impl fmt::Display for PrinterError {
 impl fmt::Display for AlertError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            AlertError::WebhookFailed { reason } => {
-                write!(f, "Discord webhook request failed: {}", reason)
+            AlertError::DeliveryFailed {
+                alert_kind,
+                failures,
+            } => {
+                let names: Vec<&str> = failures.iter().map(|(name, _)| name.as_str()).collect();
+                write!(
+                    f,
+                    "Failed to deliver {} alert via {}",
+                    alert_kind,
+                    names.join(", ")
+                )
             }
-            AlertError::MessageFormatError { reason } => {
-                write!(f, "Failed to format alert message: {}", reason)
+            AlertError::MessageFormatError { .. } => {
+                write!(f, "Failed to format alert message")
             }
             AlertError::InvalidWebhookUrl { url } => {
-                write!(f, "Invalid Discord webhook URL: {}", url)
+                write!(f, "Invalid webhook URL: {}", url)
+            }
+            AlertError::UnknownNotifier { name, configured } => {
+                write!(
+                    f,
+                    "Alert configuration references unknown notifier '{}' (configured: {})",
+                    name,
+                    configured.join(", ")
+                )
+            }
+            AlertError::SetupFailed { .. } => {
+                write!(f, "Failed to set up alert delivery")
+            }
+            AlertError::QueueFailed { notifier, .. } => {
+                write!(f, "Failed to queue alert for {}", notifier)
             }
         }
     }
@@ -259,27 +459,37 @@ impl fmt::Display for ConfigError {
             ConfigError::MissingEnvVar { var_name } => {
                 write!(f, "Required environment variable '{}' is not set", var_name)
             }
-            ConfigError::FileReadError { path, reason } => {
+            ConfigError::FileReadError { path, .. } => {
+                write!(f, "Failed to read configuration file '{}'", path)
+            }
+            ConfigError::InvalidValue { field, value, .. } => {
+                write!(f, "Invalid value '{}' for field '{}'", value, field)
+            }
+            ConfigError::MissingFile { path } => {
+                write!(f, "Required file not found: {}", path)
+            }
+            ConfigError::RequiredWith {
+                var_name,
+                required_by,
+            } => {
+                write!(f, "{} is required when {} is set", var_name, required_by)
+            }
+            ConfigError::Conflicting {
+                var_name,
+                conflicts_with,
+            } => {
                 write!(
                     f,
-                    "Failed to read configuration file '{}': {}",
-                    path, reason
+                    "{} cannot be set together with {}",
+                    var_name, conflicts_with
                 )
             }
-            ConfigError::InvalidValue {
-                field,
-                value,
-                reason,
-            } => {
+            ConfigError::NoAlertBackend => {
                 write!(
                     f,
-                    "Invalid value '{}' for field '{}': {}",
-                    value, field, reason
+                    "At least one alert backend must be configured (DISCORD_WEBHOOK, DISCORD_CHANNELS, TELEGRAM_BOT_TOKEN, SLACK_WEBHOOK_URL, SLACK_BOT_TOKEN, NTFY_URL, GOTIFY_URL, WEBHOOK_URL or SMTP_HOST)"
                 )
             }
-            ConfigError::MissingFile { path } => {
-                write!(f, "Required file not found: {}", path)
-            }
         }
     }
 }
@@ -287,8 +497,11 @@ impl fmt::Display for ConfigError {
 impl fmt::Display for NetworkError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            NetworkError::RequestFailed { url, reason } => {
-                write!(f, "Network request to '{}' failed: {}", url, reason)
+            NetworkError::RequestFailed { url, .. } => {
+                write!(f, "Network request to '{}' failed", url)
+            }
+            NetworkError::HttpStatus { url, status } => {
+                write!(f, "Request to '{}' failed with HTTP {}", url, status)
             }
             NetworkError::Timeout {
                 url,
@@ -311,8 +524,8 @@ impl fmt::Display for NetworkError {
 }
 
 // Implement std::error::Error trait
-impl std::error::Error for PrintGuardianError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+impl Error for PrintGuardianError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             PrintGuardianError::ImageError(e) => Some(e),
             PrintGuardianError::DetectionError(e) => Some(e),
@@ -324,12 +537,75 @@ impl std::error::Error for PrintGuardianError {
     }
 }
 
-impl std::error::Error for ImageError {}
-impl std::error::Error for DetectionError {}
-impl std::error::Error for PrinterError {}
-impl std::error::Error for AlertError {}
-impl std::error::Error for ConfigError {}
-impl std::error::Error for NetworkError {}
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageError::DownloadFailed { source, .. } => Some(source),
+            ImageError::RetriesExhausted { source, .. } => Some(source.as_ref()),
+            ImageError::SaveFailed { source, .. } | ImageError::ReadFailed { source, .. } => {
+                Some(source)
+            }
+            ImageError::InvalidFormat { source } | ImageError::EncodeFailed { source } => {
+                Some(source)
+            }
+        }
+    }
+}
+
+impl Error for DetectionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DetectionError::ModelLoadFailed { source, .. } => Some(source.as_ref()),
+            DetectionError::LabelsLoadFailed { source, .. }
+            | DetectionError::WeightsSaveFailed { source, .. } => Some(source),
+            DetectionError::WeightsDownloadFailed { source, .. } => Some(source),
+            DetectionError::InferenceFailed { .. } => None,
+        }
+    }
+}
+
+impl Error for PrinterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PrinterError::ConnectionFailed { source, .. } => Some(source),
+            PrinterError::InvalidResponse { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Error for AlertError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AlertError::DeliveryFailed { failures, .. } => failures
+                .first()
+                .map(|(_, source)| source.as_ref() as &(dyn Error + 'static)),
+            AlertError::MessageFormatError { source }
+            | AlertError::SetupFailed { source }
+            | AlertError::QueueFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::FileReadError { source, .. } => Some(source),
+            ConfigError::InvalidValue { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Error for NetworkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NetworkError::RequestFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 // Conversion traits for easy error propagation
 impl From<ImageError> for PrintGuardianError {
@@ -0,0 +1,366 @@
+#![allow(dead_code)]
+use anyhow::{Context, Result};
+use darknet::BBox;
+use log::{debug, info};
+use serde_json::json;
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+    path::PathBuf,
+};
+
+use crate::detector::Detection;
+use crate::printer::PrinterService;
+
+/// A single failure event raised by the monitoring loop.
+///
+/// An event groups all significant detections found in one analysed frame,
+/// together with the frame itself so it can later be stored as a training
+/// sample if the user flags it as a false positive.
+#[derive(Debug, Clone)]
+pub struct FailureEvent {
+    /// Unique, monotonically increasing event identifier.
+    pub id: u64,
+
+    /// Local time at which the event was recorded.
+    pub timestamp: chrono::DateTime<chrono::Local>,
+
+    /// Detections that triggered the event.
+    pub detections: Vec<Detection>,
+
+    /// The processed (un-annotated) frame the detections were made on.
+    pub frame: Vec<u8>,
+
+    /// Whether this event caused the print to be paused.
+    pub paused: bool,
+
+    /// Whether the user acknowledged this event as a false positive.
+    pub false_positive: bool,
+}
+
+/// Failure acknowledgement and false positive feedback service.
+///
+/// Keeps a bounded history of recent failure events so users can mark them
+/// as false positives. Acknowledging an event resumes the print if the event
+/// paused it, suppresses detections in the same image region for the rest of
+/// the print, and stores the frame and boxes as a labeled negative sample.
+pub struct FeedbackService {
+    events: VecDeque<FailureEvent>,
+    suppressed_regions: Vec<BBox>,
+    negatives_dir: PathBuf,
+    max_events: usize,
+    next_event_id: u64,
+    acknowledged: u64,
+    acknowledging: HashSet<u64>,
+}
+
+impl FeedbackService {
+    /// Create a new FeedbackService.
+    ///
+    /// # Arguments
+    ///
+    /// * `negatives_dir` - Directory where false positive samples are stored
+    /// * `max_events` - Maximum number of recent events kept for acknowledgement
+    pub fn new(negatives_dir: PathBuf, max_events: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            suppressed_regions: Vec::new(),
+            negatives_dir,
+            max_events,
+            next_event_id: 1,
+            acknowledged: 0,
+            acknowledging: HashSet::new(),
+        }
+    }
+
+    /// Record a new failure event and return its identifier.
+    ///
+    /// The oldest event is dropped once `max_events` is exceeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The processed frame the detections were made on
+    /// * `detections` - The significant detections found in the frame
+    pub fn record_event(&mut self, frame: &[u8], detections: &[Detection]) -> u64 {
+        let id = self.next_event_id;
+        self.next_event_id += 1;
+
+        self.events.push_back(FailureEvent {
+            id,
+            timestamp: chrono::Local::now(),
+            detections: detections.to_vec(),
+            frame: frame.to_vec(),
+            paused: false,
+            false_positive: false,
+        });
+
+        while self.events.len() > self.max_events {
+            self.events.pop_front();
+        }
+
+        id
+    }
+
+    /// Mark an event as the one that caused the print to be paused.
+    pub fn mark_paused(&mut self, event_id: u64) {
+        if let Some(event) = self.events.iter_mut().find(|e| e.id == event_id) {
+            event.paused = true;
+        }
+    }
+
+    /// Acknowledge an event as a false positive.
+    ///
+    /// Suppresses the event's detection regions for the rest of the print,
+    /// stores the frame as a negative sample and resumes the print if the
+    /// event paused it. The event is only marked as acknowledged once the
+    /// sample is stored and the print resumed, so a failed acknowledgement
+    /// can be retried. Acknowledging the same event twice is a no-op.
+    ///
+    /// Callers sharing the service behind a mutex should use
+    /// [`FeedbackService::begin_acknowledgement`] instead, so the lock is not
+    /// held while the printer is contacted.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_id` - Identifier of the event to acknowledge
+    /// * `printer_service` - Printer service used to resume the print
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The event is unknown or no longer retained
+    /// - The negative sample cannot be written to disk
+    /// - Resuming the print fails
+    pub fn acknowledge_false_positive(
+        &mut self,
+        event_id: u64,
+        printer_service: &PrinterService,
+    ) -> Result<()> {
+        let Some(pending) = self.begin_acknowledgement(event_id)? else {
+            return Ok(());
+        };
+        if let Err(e) = pending.apply(printer_service) {
+            self.abort_acknowledgement(event_id);
+            return Err(e);
+        }
+        self.complete_acknowledgement(event_id);
+        Ok(())
+    }
+
+    /// Start acknowledging an event as a false positive.
+    ///
+    /// Returns the side effects still to run, or `None` if the event is
+    /// already acknowledged. The event is marked as in flight until
+    /// [`FeedbackService::complete_acknowledgement`] is called once
+    /// [`PendingAcknowledgement::apply`] succeeded, or
+    /// [`FeedbackService::abort_acknowledgement`] if it failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event is unknown or no longer retained, or
+    /// if another acknowledgement of it is still in flight.
+    pub fn begin_acknowledgement(
+        &mut self,
+        event_id: u64,
+    ) -> Result<Option<PendingAcknowledgement>> {
+        let event = self
+            .get_event(event_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown failure event #{}", event_id))?;
+
+        if event.false_positive {
+            info!("Event #{} is already marked as a false positive", event_id);
+            return Ok(None);
+        }
+        let pending = PendingAcknowledgement {
+            event: event.clone(),
+            negatives_dir: self.negatives_dir.clone(),
+        };
+        if !self.acknowledging.insert(event_id) {
+            anyhow::bail!("Event #{} is already being acknowledged", event_id);
+        }
+
+        Ok(Some(pending))
+    }
+
+    /// Mark an event as a false positive and suppress its detection regions.
+    ///
+    /// Does nothing if the event is unknown or already acknowledged.
+    pub fn complete_acknowledgement(&mut self, event_id: u64) {
+        self.acknowledging.remove(&event_id);
+        let Some(event) = self
+            .events
+            .iter_mut()
+            .find(|e| e.id == event_id && !e.false_positive)
+        else {
+            return;
+        };
+        event.false_positive = true;
+        self.acknowledged += 1;
+        self.suppressed_regions
+            .extend(event.detections.iter().map(|d| d.bbox));
+        info!(
+            "Event #{} marked as false positive; suppressing {} region(s) for the rest of the print",
+            event_id,
+            event.detections.len()
+        );
+    }
+
+    /// Give up an acknowledgement whose side effects failed, so it can be retried.
+    pub fn abort_acknowledgement(&mut self, event_id: u64) {
+        self.acknowledging.remove(&event_id);
+    }
+
+    /// Check whether an acknowledgement of an event is in flight.
+    pub fn is_acknowledging(&self, event_id: u64) -> bool {
+        self.acknowledging.contains(&event_id)
+    }
+
+    /// Get the number of events acknowledged as false positives since startup.
+    pub fn acknowledged_count(&self) -> u64 {
+        self.acknowledged
+    }
+
+    /// Check whether a detection falls inside a suppressed region.
+    ///
+    /// A detection is suppressed when its center lies within the bounding
+    /// box of a region previously acknowledged as a false positive.
+    pub fn is_suppressed(&self, detection: &Detection) -> bool {
+        self.suppressed_regions.iter().any(|region| {
+            (detection.center_x() - region.x).abs() <= region.w / 2.0
+                && (detection.center_y() - region.y).abs() <= region.h / 2.0
+        })
+    }
+
+    /// Remove detections that fall inside suppressed regions.
+    pub fn filter_suppressed(&self, detections: Vec<Detection>) -> Vec<Detection> {
+        let (kept, suppressed): (Vec<_>, Vec<_>) =
+            detections.into_iter().partition(|d| !self.is_suppressed(d));
+
+        if !suppressed.is_empty() {
+            debug!(
+                "Suppressed {} detection(s) in acknowledged false positive regions",
+                suppressed.len()
+            );
+        }
+
+        kept
+    }
+
+    /// Clear all suppressed regions.
+    ///
+    /// Called when a print job starts or ends so suppressions only apply to
+    /// the print they were acknowledged in. Pausing and resuming the print
+    /// keeps them.
+    pub fn clear_suppressions(&mut self) {
+        self.suppressed_regions.clear();
+    }
+
+    /// Get the currently suppressed regions.
+    pub fn get_suppressed_regions(&self) -> &[BBox] {
+        &self.suppressed_regions
+    }
+
+    /// Get a retained event by identifier.
+    pub fn get_event(&self, event_id: u64) -> Option<&FailureEvent> {
+        self.events.iter().find(|e| e.id == event_id)
+    }
+
+    /// Get all retained events, oldest first.
+    pub fn get_events(&self) -> impl Iterator<Item = &FailureEvent> {
+        self.events.iter()
+    }
+}
+
+/// A false positive acknowledgement whose side effects have not run yet.
+///
+/// Holds a copy of the event, so the sample can be stored and the print
+/// resumed without holding the lock of a shared [`FeedbackService`].
+pub struct PendingAcknowledgement {
+    event: FailureEvent,
+    negatives_dir: PathBuf,
+}
+
+impl PendingAcknowledgement {
+    /// Get the identifier of the event being acknowledged.
+    pub fn event_id(&self) -> u64 {
+        self.event.id
+    }
+
+    /// Store the negative sample and resume the print if the event paused it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The negative sample cannot be written to disk
+    /// - Resuming the print fails
+    pub fn apply(&self, printer_service: &PrinterService) -> Result<()> {
+        self.save_negative_sample()?;
+
+        if self.event.paused {
+            printer_service
+                .resume_print()
+                .context("Failed to resume print after false positive acknowledgement")?;
+            info!("Print resumed after false positive acknowledgement");
+        }
+
+        Ok(())
+    }
+
+    /// Store an event's frame and boxes as a labeled negative sample.
+    ///
+    /// Writes the frame image, an empty Darknet label file (no objects) and
+    /// a JSON sidecar describing the rejected detections.
+    fn save_negative_sample(&self) -> Result<()> {
+        let event = &self.event;
+        fs::create_dir_all(&self.negatives_dir)?;
+
+        let extension = image::guess_format(&event.frame)
+            .ok()
+            .and_then(|format| format.extensions_str().first().copied())
+            .unwrap_or("jpg");
+        let stem = format!(
+            "false_positive_{}_{}",
+            event.timestamp.format("%Y%m%d_%H%M%S"),
+            event.id
+        );
+
+        let boxes: Vec<_> = event
+            .detections
+            .iter()
+            .map(|d| {
+                json!({
+                    "label": d.label,
+                    "confidence": d.confidence,
+                    "x": d.center_x(),
+                    "y": d.center_y(),
+                    "w": d.width(),
+                    "h": d.height(),
+                })
+            })
+            .collect();
+        let metadata = json!({
+            "event_id": event.id,
+            "timestamp": event.timestamp.to_rfc3339(),
+            "label": "false_positive",
+            "detections": boxes,
+        });
+
+        fs::write(
+            self.negatives_dir.join(format!("{}.{}", stem, extension)),
+            &event.frame,
+        )?;
+        fs::write(self.negatives_dir.join(format!("{}.txt", stem)), "")?;
+        fs::write(
+            self.negatives_dir.join(format!("{}.json", stem)),
+            serde_json::to_string_pretty(&metadata)?,
+        )?;
+
+        info!(
+            "Saved false positive sample '{}' to {}",
+            stem,
+            self.negatives_dir.display()
+        );
+
+        Ok(())
+    }
+}
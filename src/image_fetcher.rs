@@ -1,5 +1,4 @@
 #![allow(dead_code)]
-use anyhow::Result;
 use log::{error, info, warn};
 use std::{thread, time::Duration};
 
@@ -9,6 +8,8 @@ use imageproc::drawing::draw_hollow_rect_mut;
 use imageproc::rect::Rect;
 
 use crate::detector::Detection;
+use crate::error::{AlertError, ErrorChain, ImageError, NetworkError};
+use crate::metrics;
 
 /// Image fetching service with retry logic and error handling.
 ///
@@ -23,6 +24,24 @@ pub struct ImageFetcher {
     retry_delay_seconds: u64,
     retry_count: u32,
     disconnect_alert_sent: bool,
+    camera_health: Vec<CameraHealth>,
+    last_fetched_index: Option<usize>,
+}
+
+/// Health information for a single camera endpoint.
+#[derive(Debug, Clone)]
+pub struct CameraHealth {
+    /// The camera image URL.
+    pub url: String,
+
+    /// Whether the most recent fetch from this camera succeeded.
+    pub healthy: bool,
+
+    /// Number of consecutive failed fetches from this camera.
+    pub consecutive_failures: u32,
+
+    /// Time of the last successful fetch, if any.
+    pub last_success: Option<chrono::DateTime<chrono::Local>>,
 }
 
 impl ImageFetcher {
@@ -34,6 +53,16 @@ impl ImageFetcher {
     /// * `max_retries` - Maximum number of retry attempts before giving up
     /// * `retry_delay_seconds` - Delay between retry attempts
     pub fn new(image_urls: Vec<String>, max_retries: u32, retry_delay_seconds: u64) -> Self {
+        let camera_health = image_urls
+            .iter()
+            .map(|url| CameraHealth {
+                url: url.clone(),
+                healthy: false,
+                consecutive_failures: 0,
+                last_success: None,
+            })
+            .collect();
+
         Self {
             image_urls,
             current_url_index: 0,
@@ -41,6 +70,8 @@ impl ImageFetcher {
             retry_delay_seconds,
             retry_count: 0,
             disconnect_alert_sent: false,
+            camera_health,
+            last_fetched_index: None,
         }
     }
 
@@ -60,18 +91,16 @@ impl ImageFetcher {
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - All retry attempts are exhausted
-    /// - Network connectivity issues persist
-    /// - The remote server returns error responses
+    /// Returns an [`ImageError::RetriesExhausted`] holding the last
+    /// failure if all retry attempts are exhausted.
     ///
     pub fn fetch_with_retry<F>(
         &mut self,
         mut alert_callback: F,
         url_index: Option<usize>,
-    ) -> Result<Vec<u8>>
+    ) -> Result<Vec<u8>, ImageError>
     where
-        F: FnMut(AlertType) -> Result<()>,
+        F: FnMut(AlertType) -> Result<(), AlertError>,
     {
         loop {
             match self.attempt_fetch(url_index) {
@@ -79,7 +108,7 @@ impl ImageFetcher {
                     // If we successfully got data after being disconnected, send recovery message
                     if self.disconnect_alert_sent {
                         if let Err(e) = alert_callback(AlertType::SystemRecovery) {
-                            error!("Failed to send recovery alert: {}", e);
+                            error!("Failed to send recovery alert: {}", ErrorChain(&e));
                         } else {
                             info!("Sent system recovery alert");
                         }
@@ -93,24 +122,29 @@ impl ImageFetcher {
                     let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
                     warn!(
                         "{}: Failed to fetch image (attempt {}): {}",
-                        timestamp, self.retry_count, e
+                        timestamp,
+                        self.retry_count,
+                        ErrorChain(&e)
                     );
 
                     if self.retry_count >= self.max_retries {
                         // Only send the disconnect alert if we haven't sent it already
                         if !self.disconnect_alert_sent {
                             if let Err(alert_err) = alert_callback(AlertType::SystemOffline) {
-                                error!("Failed to send disconnect alert: {}", alert_err);
+                                error!(
+                                    "Failed to send disconnect alert: {}",
+                                    ErrorChain(&alert_err)
+                                );
                             } else {
                                 info!("Sent system offline alert");
                                 self.disconnect_alert_sent = true;
                             }
                         }
 
-                        return Err(anyhow::anyhow!(
-                            "Failed to fetch image after {} retries",
-                            self.max_retries
-                        ));
+                        return Err(ImageError::RetriesExhausted {
+                            attempts: self.retry_count,
+                            source: Box::new(e),
+                        });
                     }
 
                     info!("Retrying in {} seconds...", self.retry_delay_seconds);
@@ -127,6 +161,7 @@ impl ImageFetcher {
     /// directly for testing or when custom retry logic is needed.
     ///
     /// Uses round-robin URL selection when multiple URLs are configured.
+    /// The outcome is recorded in the per-camera health information.
     ///
     /// # Returns
     ///
@@ -134,28 +169,52 @@ impl ImageFetcher {
     ///
     /// # Errors
     ///
-    /// Returns an error if:
+    /// Returns an [`ImageError::DownloadFailed`] if:
     /// - HTTP request fails
     /// - Server returns non-success status
     /// - Response body cannot be read
-    fn attempt_fetch(&mut self, url_index: Option<usize>) -> Result<Vec<u8>> {
+    fn attempt_fetch(&mut self, url_index: Option<usize>) -> Result<Vec<u8>, ImageError> {
         // Get current URL and advance to next for round-robin
-        let current_url = match url_index {
-            Some(index) => &self.image_urls[index],
-            None => &self.image_urls[self.current_url_index],
-        };
+        let index = url_index.unwrap_or(self.current_url_index);
         self.current_url_index = (self.current_url_index + 1) % self.image_urls.len();
+        self.last_fetched_index = Some(index);
+
+        let url = &self.image_urls[index];
+        let result = Self::fetch_url(url);
 
-        let response = reqwest::blocking::get(current_url)?;
+        let metrics = metrics::get();
+        metrics.image_fetch_attempts.with_label_values(&[url]).inc();
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "HTTP request failed with status: {}",
-                response.status()
-            ));
+        let health = &mut self.camera_health[index];
+        match result {
+            Ok(_) => {
+                health.healthy = true;
+                health.consecutive_failures = 0;
+                health.last_success = Some(chrono::Local::now());
+            }
+            Err(_) => {
+                metrics.image_fetch_failures.with_label_values(&[url]).inc();
+                health.healthy = false;
+                health.consecutive_failures += 1;
+            }
         }
 
-        let data = response.bytes()?;
+        result
+    }
+
+    /// Download the image bytes from a single URL.
+    fn fetch_url(url: &str) -> Result<Vec<u8>, ImageError> {
+        let download_failed = |source| ImageError::DownloadFailed {
+            url: url.to_string(),
+            source,
+        };
+        let response = reqwest::blocking::get(url)
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| download_failed(NetworkError::from_reqwest(url, e)))?;
+
+        let data = response
+            .bytes()
+            .map_err(|e| download_failed(NetworkError::from_reqwest(url, e)))?;
         Ok(data.to_vec())
     }
 
@@ -204,6 +263,16 @@ impl ImageFetcher {
         self.max_retries
     }
 
+    /// Get the index of the camera used by the most recent fetch attempt.
+    pub fn get_last_fetched_index(&self) -> Option<usize> {
+        self.last_fetched_index
+    }
+
+    /// Get the health information for each configured camera.
+    pub fn get_camera_health(&self) -> &[CameraHealth] {
+        &self.camera_health
+    }
+
     /// Convert fetched image bytes directly to a darknet Image.
     ///
     /// This method bypasses disk I/O by loading the image data directly
@@ -219,14 +288,13 @@ impl ImageFetcher {
     ///
     /// # Errors
     ///
-    /// Returns an error if:
+    /// Returns an [`ImageError::InvalidFormat`] if:
     /// - Image format is not supported
     /// - Image data is corrupted
-    /// - Memory allocation fails
     ///
-    pub fn bytes_to_darknet_image(image_data: &[u8]) -> Result<Image> {
+    pub fn bytes_to_darknet_image(image_data: &[u8]) -> Result<Image, ImageError> {
         // Load image from memory using the image crate
-        let dynamic_image: image::DynamicImage = image::load_from_memory(image_data)?;
+        let dynamic_image: image::DynamicImage = load_image(image_data)?;
 
         // Convert DynamicImage to darknet Image using the explicit From impl
         // Use the fully qualified type to ensure correct trait resolution
@@ -255,9 +323,9 @@ impl ImageFetcher {
         detections: &[Detection],
         image_width: u32,
         image_height: u32,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<Vec<u8>, ImageError> {
         // Load the image from memory
-        let dynamic_image = image::load_from_memory(image_data)?;
+        let dynamic_image = load_image(image_data)?;
 
         // Convert to RGB if it's not already
         let mut rgb_image = dynamic_image.to_rgb8();
@@ -301,7 +369,9 @@ impl ImageFetcher {
         let mut buffer = Vec::new();
         let mut cursor = std::io::Cursor::new(&mut buffer);
 
-        dynamic_annotated.write_to(&mut cursor, image::ImageFormat::Jpeg)?;
+        dynamic_annotated
+            .write_to(&mut cursor, image::ImageFormat::Jpeg)
+            .map_err(|source| ImageError::EncodeFailed { source })?;
 
         Ok(buffer)
     }
@@ -315,14 +385,17 @@ impl ImageFetcher {
     /// # Returns
     ///
     /// Transformed image as bytes in the same format as the input
-    pub fn apply_image_transformations(image_data: &[u8], flip_vertical: bool) -> Result<Vec<u8>> {
+    pub fn apply_image_transformations(
+        image_data: &[u8],
+        flip_vertical: bool,
+    ) -> Result<Vec<u8>, ImageError> {
         if !flip_vertical {
             // No transformations needed, return original data
             return Ok(image_data.to_vec());
         }
 
         // Load the image from memory
-        let dynamic_image = image::load_from_memory(image_data)?;
+        let dynamic_image = load_image(image_data)?;
 
         // Apply vertical flip
         let flipped_image = dynamic_image.flipv();
@@ -333,12 +406,19 @@ impl ImageFetcher {
 
         // Try to preserve the original format
         let format = image::guess_format(image_data).unwrap_or(image::ImageFormat::Jpeg);
-        flipped_image.write_to(&mut cursor, format)?;
+        flipped_image
+            .write_to(&mut cursor, format)
+            .map_err(|source| ImageError::EncodeFailed { source })?;
 
         Ok(buffer)
     }
 }
 
+/// Decode image bytes in any supported format.
+fn load_image(image_data: &[u8]) -> Result<image::DynamicImage, ImageError> {
+    image::load_from_memory(image_data).map_err(|source| ImageError::InvalidFormat { source })
+}
+
 /// Types of alerts that can be triggered by the image fetcher.
 pub enum AlertType {
     /// System has gone offline due to repeated fetch failures.
@@ -6,24 +6,63 @@
 //! # Core Components
 //!
 //! * [`config`] - Configuration management and command-line arguments
-//! * [`alerts`] - Discord webhook notification system
-//! * [`printer`] - Moonraker API integration for printer control
+//! * [`alerts`] - Alert dispatching to all configured notifiers
+//! * [`alert_queue`] - Persistent queue of alerts awaiting delivery
+//! * [`routing`] - Alert routing rules and quiet hours per notifier
+//! * [`templates`] - Localized, customizable alert message templates
+//! * [`notifiers`] - Notification backends (Discord, Telegram, Slack, ntfy, Gotify, webhook, email)
+//! * [`printer`] - Moonraker API integration for printer control with verified pauses
+//! * [`moonraker`] - Moonraker WebSocket client with a live printer state model
+//! * [`moonraker_auth`] - API key and login authentication for Moonraker
+//! * [`printer_status`] - Typed printer status from Moonraker's printer objects
+//! * [`report`] - End-of-print summary reports
+//! * [`digest`] - Scheduled daily or weekly printer statistics digest
 //! * [`image_fetcher`] - Robust image downloading with retry logic
+//! * [`clip`] - Rolling frame buffers and animated alert clips
 //! * [`detector`] - AI-powered print failure detection using YOLO/Darknet
+//! * [`feedback`] - Failure acknowledgement and false positive feedback
+//! * [`state`] - Monitoring state shared with the HTTP server
+//! * [`server`] - Built-in HTTP server with health, status and frame endpoints
+//! * [`api`] - Authenticated REST API for runtime control
+//! * [`metrics`] - Prometheus metrics
+//! * [`mqtt`] - MQTT bridge with Home Assistant discovery
 //! * [`error`] - Comprehensive error types and handling
 //!
 
+pub mod alert_queue;
 pub mod alerts;
+pub mod api;
+pub mod clip;
 pub mod config;
 pub mod detector;
+pub mod digest;
 pub mod error;
+pub mod feedback;
 pub mod image_fetcher;
+pub mod metrics;
+pub mod moonraker;
+pub mod moonraker_auth;
+pub mod mqtt;
+pub mod notifiers;
 pub mod printer;
+pub mod printer_status;
+pub mod report;
+pub mod routing;
+pub mod server;
+pub mod state;
+pub mod templates;
 
 // Re-export commonly used types for convenience
+pub use alert_queue::AlertQueue;
 pub use alerts::AlertService;
+pub use api::ControlApi;
 pub use config::Config;
 pub use detector::{Detection, FailureDetector};
 pub use error::PrintGuardianError;
+pub use feedback::{FailureEvent, FeedbackService};
 pub use image_fetcher::ImageFetcher;
+pub use mqtt::MqttBridge;
+pub use notifiers::{Alert, AlertKind, Notifier, Severity};
 pub use printer::PrinterService;
+pub use server::HttpServer;
+pub use state::{MonitorState, SharedState};
@@ -1,35 +1,69 @@
 use anyhow::Result;
 use log::{debug, error, info, warn};
-use std::{fs, thread, time::Duration};
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
 
 // Module declarations
+mod alert_queue;
 mod alerts;
+mod api;
+mod clip;
 mod config;
 mod detector;
+mod digest;
 mod error;
+mod feedback;
 mod image_fetcher;
+mod metrics;
+mod moonraker;
+mod moonraker_auth;
+mod mqtt;
+mod notifiers;
 mod printer;
+mod printer_status;
+mod report;
+mod routing;
+mod server;
+mod state;
+mod templates;
 
 // Import our modules
 use alerts::AlertService;
+use api::ControlApi;
+use clip::ClipRecorder;
 use config::{Config, constants};
 use detector::FailureDetector;
+use digest::DigestScheduler;
+use error::ErrorChain;
+use feedback::FeedbackService;
 use image_fetcher::{AlertType, ImageFetcher};
-use printer::PrinterService;
+use moonraker::MoonrakerSocket;
+use moonraker_auth::MoonrakerAuth;
+use mqtt::MqttBridge;
+use notifiers::PrintJob;
+use printer::{PauseOutcome, PrinterService};
+use printer_status::PrintState;
+use report::PrintReport;
+use server::HttpServer;
+use state::{DetectionSummary, EventLevel, MonitorState, SharedState};
 
 /// Print Guardian - AI-powered 3D print failure detection system.
 ///
 /// This application monitors 3D printer cameras in real-time using machine learning
 /// to detect print failures such as spaghetti, layer shifts, warping, and other issues.
 /// When failures are detected, it can automatically pause the printer and send alerts
-/// via Discord webhooks.
+/// via the configured notification backends (e.g. Discord webhooks).
 ///
 /// # Features
 ///
 /// * Real-time camera monitoring with retry logic
 /// * AI-powered print failure detection using YOLO/Darknet
 /// * Automatic printer pause on multiple failures
-/// * Discord webhook notifications with rich embeds
+/// * Notifications via Discord, Telegram, Slack, ntfy, Gotify, generic webhooks and email
 /// * Configurable detection thresholds
 /// * Robust error handling and recovery
 ///
@@ -37,8 +71,21 @@ use printer::PrinterService;
 ///
 /// Required:
 /// * `IMAGE_URL` - Camera image URL(s) for monitoring (single URL or comma-separated list for round-robin)
-/// * `DISCORD_WEBHOOK` - Discord webhook URL for alerts
 /// * `MOONRAKER_API_URL` - Moonraker API endpoint for printer control
+/// * `MOONRAKER_API_KEY` - Moonraker API key when authorization is enabled
+/// * `MOONRAKER_USERNAME` / `MOONRAKER_PASSWORD` - Moonraker login instead of an API key
+/// * `MOONRAKER_WEBSOCKET` - Follow the printer state over Moonraker's WebSocket (default: "true")
+/// * At least one alert backend:
+///   * `DISCORD_WEBHOOK` - Discord webhook URL for alerts
+///   * `DISCORD_CHANNELS` - Additional named Discord webhooks (`owner=url,log=url`)
+///   * `TELEGRAM_BOT_TOKEN` and `TELEGRAM_CHAT_IDS` - Telegram bot token and chat IDs
+///   * `SLACK_WEBHOOK_URL` - Slack incoming webhook URL
+///   * `SLACK_BOT_TOKEN` and `SLACK_CHANNEL_ID` - Slack bot token and channel ID (with a
+///     webhook, only used to upload images)
+///   * `NTFY_URL` - ntfy topic URL (with optional `NTFY_TOKEN`)
+///   * `GOTIFY_URL` and `GOTIFY_TOKEN` - Gotify server URL and application token
+///   * `WEBHOOK_URL` - Generic webhook URL with optional templates
+///   * `SMTP_HOST`, `SMTP_FROM` and `SMTP_TO` - SMTP server and email addresses
 ///
 /// Optional (with defaults):
 /// * `LABEL_FILE` - Path to label file (default: "./labels.txt")
@@ -48,6 +95,20 @@ use printer::PrinterService;
 /// * `OBJECTNESS_THRESHOLD` - Objectness threshold (default: "0.5")
 /// * `CLASS_PROB_THRESHOLD` - Class probability threshold (default: "0.5")
 /// * `FLIP_IMAGE` - Flip images vertically (default: "false")
+/// * `HTTP_BIND_ADDRESS` - HTTP server listen address, empty to disable (default: "127.0.0.1:8080")
+/// * `ALERT_ROUTES` - Alerts per notifier, e.g. `discord-log=status;telegram=critical` (default: all)
+/// * `LOCALE` - Language of alert texts: "en", "de", "fr" or "es" (default: "en")
+/// * `ALERT_TEMPLATES_DIR` - Directory with custom `<locale>.json` alert templates (default: none)
+/// * `QUIET_HOURS` - Quiet hours per notifier, e.g. `telegram=22:00-07:00` (default: none)
+/// * `UPLOAD_LIMITS` - Image upload limits per notifier, e.g. `discord=25000000` (default: backend limits)
+/// * `DIGEST_SCHEDULE` - Post a statistics digest, e.g. `daily 08:00` or `weekly mon 08:00` (default: disabled)
+/// * `DIGEST_NOTIFIERS` - Notifiers receiving the digest, e.g. `discord-farm` (default: all)
+/// * `CLIP_SECONDS` - Seconds of frames attached to failure and pause alerts as a GIF, 0 to disable (default: 30)
+/// * `CLIP_MAX_BYTES` - Maximum size of an alert clip (default: 8000000)
+/// * `PAUSE_VERIFY_SECONDS` - Time to wait for a pause to take effect, 0 to not verify (default: 30)
+/// * `PAUSE_ATTEMPTS` - Pause requests sent before escalating (default: 2)
+/// * `PAUSE_ESCALATION` - "none", "cancel" or "emergency_stop" if the printer does not pause (default: "none")
+/// * `MQTT_HOST` - MQTT broker for Home Assistant integration (default: disabled)
 ///
 /// # Usage
 ///
@@ -76,21 +137,48 @@ fn main() -> Result<()> {
         .init();
 
     // Load configuration from environment variables
-    let config = Config::load().expect(
-        "Failed to load configuration. Please ensure all required environment variables are set.",
-    );
+    let config = Config::load()
+        .inspect_err(|e| error!("Failed to load configuration: {}", ErrorChain(e)))?;
 
     info!("Print Guardian starting...");
     info!("Using Moonraker API URL: {}", config.moonraker_api_url);
+    info!("Monitoring printer '{}'", config.printer_name);
     info!(
         "Monitoring {} camera(s): {}",
         config.image_urls.len(),
         config.image_urls.join(", ")
     );
 
+    // Shared state for the HTTP server
+    let state: SharedState = Arc::new(Mutex::new(MonitorState::default()));
+
     // Initialize services
-    let alert_service = AlertService::new(config.discord_webhook.clone());
-    let printer_service = PrinterService::new(config.moonraker_api_url.clone());
+    let alert_service = AlertService::from_config(&config)?;
+    info!(
+        "Alert notifiers: {}",
+        alert_service.notifier_names().join(", ")
+    );
+    alert_service.start_worker()?;
+    let moonraker_auth = MoonrakerAuth::new(
+        &config.moonraker_api_url,
+        config.moonraker_credentials.clone(),
+    );
+    let mut printer_service =
+        PrinterService::new(config.moonraker_api_url.clone()).with_auth(moonraker_auth.clone());
+    if config.moonraker_websocket {
+        match MoonrakerSocket::new(&config.moonraker_api_url) {
+            Ok(socket) => {
+                let socket = socket.with_auth(moonraker_auth);
+                info!("Following printer state over {}", socket.url());
+                printer_service = printer_service.with_live_state(socket.state());
+                socket.start()?;
+            }
+            Err(e) => warn!(
+                "Moonraker WebSocket unavailable, polling the printer state over HTTP instead: {:#}",
+                e
+            ),
+        }
+    }
 
     // Download model weights if needed
     FailureDetector::ensure_weights_downloaded(&config.weights, constants::MODEL_WEIGHTS_URL)?;
@@ -105,6 +193,12 @@ fn main() -> Result<()> {
     )?;
 
     info!("Failure detector initialized");
+    {
+        let mut state = state::lock(&state);
+        state.model_loaded = true;
+        state.objectness_threshold = detector.get_objectness_threshold();
+        state.class_prob_threshold = detector.get_class_prob_threshold();
+    }
 
     // Initialize image fetcher
     let mut image_fetcher = ImageFetcher::new(
@@ -127,13 +221,54 @@ fn main() -> Result<()> {
         config.output_dir.display()
     );
 
+    // Initialize false positive feedback tracking
+    let feedback = Arc::new(Mutex::new(FeedbackService::new(
+        config.output_dir.join(constants::NEGATIVE_SAMPLES_DIR),
+        constants::MAX_FAILURE_EVENTS,
+    )));
+
+    // Start the HTTP server with the control API
+    if let Some(bind_address) = &config.http_bind_address {
+        let api = ControlApi::new(
+            state.clone(),
+            feedback.clone(),
+            printer_service.clone(),
+            config.api_token.clone(),
+        );
+        HttpServer::new(state.clone(), api)
+            .with_alert_image_dir(config.output_dir.join(constants::ALERT_IMAGE_DIR))
+            .start(bind_address)?;
+    }
+
+    // Start the MQTT bridge for Home Assistant if a broker is configured
+    if let Some(settings) = config.mqtt.clone() {
+        MqttBridge::new(state.clone(), printer_service.clone(), settings).start()?;
+    }
+
     // Create .ready file to indicate the application is fully initialized
     fs::write(".ready", "ready")?;
     info!("Application ready - created .ready file for healthcheck");
 
     // Main monitoring loop state
     let mut print_failures = 0;
-    let mut last_status_update = String::new();
+    // When the printer last ignored an automatic pause during the current job
+    let mut pause_ignored_at: Option<Instant> = None;
+    let mut last_status_update: Option<PrintState> = None;
+    let mut current_job: Option<PrintJob> = None;
+    let mut print_report: Option<PrintReport> = None;
+    let mut digest = config
+        .digest_schedule
+        .map(|schedule| DigestScheduler::new(&config.printer_name, schedule));
+    if let Some(digest) = &digest {
+        info!(
+            "Next digest due at {}",
+            digest.next_run().format("%Y-%m-%d %H:%M")
+        );
+    }
+    let mut last_progress_update: Option<Instant> = None;
+    let mut clips = config
+        .clip_window
+        .map(|window| ClipRecorder::new(window, config.clip_max_bytes));
 
     info!("Print Guardian initialized successfully. Starting monitoring loop...");
 
@@ -141,74 +276,260 @@ fn main() -> Result<()> {
         let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
         debug!("{}: Starting new monitoring iteration", timestamp);
 
+        // Apply requests made through the control API
+        let (monitoring_paused, detection_requested, alerts_snoozed) = {
+            let mut state = state::lock(&state);
+            state.last_heartbeat = Some(chrono::Local::now());
+            if let Some(threshold) = state.pending_objectness_threshold.take() {
+                detector.set_objectness_threshold(threshold);
+                info!("Objectness threshold set to {}", threshold);
+            }
+            if let Some(threshold) = state.pending_class_prob_threshold.take() {
+                detector.set_class_prob_threshold(threshold);
+                info!("Class probability threshold set to {}", threshold);
+            }
+            state.objectness_threshold = detector.get_objectness_threshold();
+            state.class_prob_threshold = detector.get_class_prob_threshold();
+            (
+                state.monitoring_paused,
+                std::mem::take(&mut state.detection_requested),
+                state.alerts_snoozed(),
+            )
+        };
+
+        metrics::get()
+            .failure_count
+            .with_label_values(&[&config.printer_name])
+            .set(print_failures as f64);
+
+        // Deliver held back alerts such as email digests
+        alert_service.flush();
+        {
+            let mut state = state::lock(&state);
+            state.alert_queue_depth = alert_service.queue_depth();
+            state.held_alerts = alert_service.held_alerts();
+        }
+
+        // Post the scheduled digest once its period is over
+        let acknowledged = state::lock(&feedback).acknowledged_count();
+        if let Some(digest) = &mut digest
+            && let Some(finished) = digest.poll(chrono::Local::now(), acknowledged)
+        {
+            match alert_service.send_fleet_digest_alert(&finished) {
+                Ok(()) => info!("Queued {} digest", finished.period),
+                Err(e) => error!(
+                    "Failed to queue {} digest: {}",
+                    finished.period,
+                    ErrorChain(&e)
+                ),
+            }
+        }
+
         // Check printer status and send alert with image if status changed
         let res = printer_service.get_printer_status();
         match res {
-            Ok(data) => {
-                let state = data["result"]["status"]["print_stats"]["state"]
-                    .as_str()
-                    .unwrap_or("unknown");
+            Ok(status) => {
+                let printer_state = status.state();
+
+                if let Some(digest) = &mut digest {
+                    digest.record_moonraker(true);
+                }
+                {
+                    let mut state = state::lock(&state);
+                    state.moonraker_reachable = true;
+                    state.moonraker_error = None;
+                    state.printer_state = printer_state.to_string();
+                    state.print_filename = status.print_stats.filename().map(str::to_string);
+                    state.print_progress = Some(status.progress());
+                }
 
-                if last_status_update != state {
+                if last_status_update != Some(printer_state) {
                     // when the print state changes reset the print failures count
                     print_failures = 0;
+                    {
+                        let mut state = state::lock(&state);
+                        state.failure_count = 0;
+                        state.push_event(
+                            EventLevel::Info,
+                            format!("Printer state changed to '{}'", printer_state),
+                        );
+                    }
+                    // Regions acknowledged as false positives stay suppressed for the
+                    // rest of the print, so only forget them when a job starts or ends.
+                    // Resuming after an acknowledgement must keep them.
+                    if !printer_state.is_active() || current_job.is_none() {
+                        state::lock(&feedback).clear_suppressions();
+                        pause_ignored_at = None;
+                    }
                     info!(
                         "Printer state changed to '{}'. Resetting print failures count.",
-                        state
+                        printer_state
                     );
 
                     // Fetch image for this status update
                     let image_data = match get_image_data(
                         &alert_service,
                         &mut image_fetcher,
+                        &state,
                         config.display_camera_index,
                     ) {
                         Some(value) => value,
                         None => continue,
                     };
 
-                    // send the status of the print to discord with the current processed image
-                    match alert_service
-                        .send_printer_status_alert(&data, Some(image_data.as_slice()))
-                    {
+                    // Track the print job so notifiers can keep a single message per print,
+                    // the status change that ends the job carries its final summary
+                    let job = match printer_state {
+                        PrintState::Printing | PrintState::Paused => Some(
+                            current_job
+                                .get_or_insert_with(|| PrintJob {
+                                    id: format!(
+                                        "{}-{}",
+                                        status.print_stats.filename().unwrap_or("unknown"),
+                                        chrono::Utc::now().timestamp()
+                                    ),
+                                    finished: false,
+                                })
+                                .clone(),
+                        ),
+                        _ => current_job.take().map(|job| PrintJob {
+                            finished: true,
+                            ..job
+                        }),
+                    };
+                    last_progress_update = Some(Instant::now());
+
+                    // send the status of the print to all notifiers with the current processed image
+                    let result = if alerts_snoozed {
+                        info!("Alerts are snoozed. Skipping printer status alert.");
+                        Ok(())
+                    } else {
+                        alert_service.send_printer_status_alert(
+                            &status,
+                            Some(image_data.as_slice()),
+                            job.as_ref(),
+                        )
+                    };
+                    // Delivery is retried by the alert queue, so the state change is
+                    // recorded even if the alert could not be queued
+                    match result {
                         Err(e) => {
-                            // If sending the alert fails, log the error and retry
-                            warn!("{}: Failed to send printer status alert: {}", timestamp, e);
-                            // Retry logic can be added here if needed
-                            if last_status_update.is_empty() {
-                                error!("Failed to send printer status alert: {}", e);
-                                thread::sleep(Duration::from_secs(constants::RETRY_DELAY_SECONDS));
-                                continue;
+                            error!(
+                                "{}: Failed to queue printer status alert: {}",
+                                timestamp,
+                                ErrorChain(&e)
+                            )
+                        }
+                        Ok(()) if !alerts_snoozed => {
+                            info!("Printer status alert queued with image")
+                        }
+                        Ok(()) => {}
+                    }
+
+                    // Keep a monitoring report per print job and summarize it when the job ends
+                    match &job {
+                        Some(job) if job.finished => {
+                            if let Some(digest) = &mut digest {
+                                digest.record_print_finished(printer_state.as_str());
+                            }
+                            if let Some(mut report) = print_report.take() {
+                                report.finish(printer_state.as_str());
+                                send_print_report(&report, &alert_service, &config, alerts_snoozed);
                             }
                         }
-                        Ok(()) => {
-                            info!("Printer status alert sent successfully with image");
-                            last_status_update = state.to_string();
+                        Some(job) if print_report.as_ref().is_none_or(|r| r.job() != job) => {
+                            print_report = Some(PrintReport::new(
+                                job.clone(),
+                                status.print_stats.filename().unwrap_or("unknown"),
+                            ));
                         }
+                        _ => {}
                     }
+                    last_status_update = Some(printer_state);
                 }
 
-                if state != "printing" {
+                // Update the print job's message with progress, ETA and the latest frame
+                if printer_state == PrintState::Printing
+                    && !alerts_snoozed
+                    && let Some(job) = &current_job
+                    && last_progress_update.is_none_or(|updated| {
+                        updated.elapsed()
+                            >= Duration::from_secs(constants::PRINT_PROGRESS_INTERVAL_SECONDS)
+                    })
+                {
+                    let (failure_score, frame) = {
+                        let state = state::lock(&state);
+                        (state.failure_score, state.latest_annotated_frame.clone())
+                    };
+                    if let Err(e) = alert_service.send_print_progress_alert(
+                        job,
+                        &status,
+                        failure_score,
+                        frame.as_deref(),
+                    ) {
+                        error!(
+                            "{}: Failed to queue print progress update: {}",
+                            timestamp,
+                            ErrorChain(&e)
+                        );
+                    }
+                    last_progress_update = Some(Instant::now());
+                }
+
+                if detection_requested {
+                    info!("Running one-off detection requested through control API.");
+                } else if printer_state != PrintState::Printing {
                     warn!("Printer is not currently printing. Skipping detection.");
                     thread::sleep(Duration::from_secs(constants::RETRY_DELAY_SECONDS));
                     continue;
+                } else if monitoring_paused {
+                    debug!("Monitoring is paused through control API. Skipping detection.");
+                    thread::sleep(Duration::from_secs(constants::RETRY_DELAY_SECONDS));
+                    continue;
                 }
             }
+            // Retrying with credentials Moonraker rejects is pointless, stop instead
+            Err(e) if e.is_fatal() => {
+                error!(
+                    "Failed to get printer status: {}. Check the Moonraker credentials.",
+                    ErrorChain(&e)
+                );
+                return Err(e.into());
+            }
             Err(e) => {
-                warn!("Failed to get printer status: {}", e);
+                if e.is_transient() {
+                    warn!("Failed to get printer status, retrying: {}", ErrorChain(&e));
+                } else {
+                    error!("Failed to get printer status: {}", ErrorChain(&e));
+                }
+                if let Some(digest) = &mut digest {
+                    digest.record_moonraker(false);
+                }
+                {
+                    let mut state = state::lock(&state);
+                    state.moonraker_reachable = false;
+                    state.moonraker_error = Some(ErrorChain(&e).to_string());
+                }
                 thread::sleep(Duration::from_secs(constants::RETRY_DELAY_SECONDS));
                 continue;
             }
         }
 
-        let image_data = match get_image_data(&alert_service, &mut image_fetcher, None) {
-            Some(value) => value,
-            None => continue,
+        let image_data = get_image_data(&alert_service, &mut image_fetcher, &state, None);
+        if let Some(report) = &mut print_report {
+            report.record_camera(image_data.is_some());
+        }
+        if let Some(digest) = &mut digest {
+            digest.record_camera(image_data.is_some());
+        }
+        let Some(image_data) = image_data else {
+            continue;
         };
 
+        let camera_index = image_fetcher.get_last_fetched_index().unwrap_or(0);
         debug!(
-            "{}: Fetched image from camera endpoints successfully",
-            timestamp
+            "{}: Fetched image from camera {} successfully",
+            timestamp, camera_index
         );
 
         // Apply image transformations if configured
@@ -218,7 +539,8 @@ fn main() -> Result<()> {
                 Err(e) => {
                     error!(
                         "{}: Failed to apply image transformations: {}",
-                        timestamp, e
+                        timestamp,
+                        ErrorChain(&e)
                     );
                     image_data // Fall back to original image
                 }
@@ -228,20 +550,29 @@ fn main() -> Result<()> {
         let darknet_image = match ImageFetcher::bytes_to_darknet_image(&processed_image_data) {
             Ok(image) => image,
             Err(e) => {
-                error!("{}: Failed to parse image from memory: {}", timestamp, e);
+                error!(
+                    "{}: Failed to parse image from memory: {}",
+                    timestamp,
+                    ErrorChain(&e)
+                );
                 continue;
             }
         };
 
         // Run failure detection directly on the image
+        let inference_started = Instant::now();
         let detections = match detector.detect_failures_from_image(&darknet_image) {
             Ok(detections) => detections,
             Err(e) => {
-                error!("{}: Detection failed: {}", timestamp, e);
+                error!("{}: Detection failed: {}", timestamp, ErrorChain(&e));
                 continue;
             }
         };
 
+        if let Some(digest) = &mut digest {
+            digest.record_inference(inference_started.elapsed());
+        }
+
         // get detection with max confidence
         let max_detection_prob = detections
             .iter()
@@ -249,7 +580,13 @@ fn main() -> Result<()> {
             .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
             .unwrap_or(0.0);
 
-        if detections.len() > 0 {
+        state::lock(&state).record_score(max_detection_prob / 100.0);
+        metrics::get()
+            .failure_score
+            .with_label_values(&[&config.printer_name])
+            .set((max_detection_prob / 100.0) as f64);
+
+        if !detections.is_empty() {
             info!(
                 "{}: Detected {} failures with max confidence {:.2}%",
                 timestamp,
@@ -258,8 +595,9 @@ fn main() -> Result<()> {
             );
         }
 
-        // Process detections
-        let significant_detections: Vec<_> = detections
+        // Process detections, ignoring regions acknowledged as false positives
+        let significant_detections: Vec<_> = state::lock(&feedback)
+            .filter_suppressed(detections)
             .into_iter()
             .filter(|d| d.exceeds_threshold(constants::ALERT_PROBABILITY_THRESHOLD))
             .collect();
@@ -279,6 +617,13 @@ fn main() -> Result<()> {
                 );
             }
 
+            // Record the failure event so it can be acknowledged as a false positive
+            let event_id =
+                state::lock(&feedback).record_event(&processed_image_data, &significant_detections);
+            if let Some(digest) = &mut digest {
+                digest.record_failure();
+            }
+
             // Annotate image with all detections
             let annotated_image = match ImageFetcher::annotate_image_with_detections(
                 &processed_image_data,
@@ -288,59 +633,177 @@ fn main() -> Result<()> {
             ) {
                 Ok(annotated) => Some(annotated),
                 Err(e) => {
-                    error!("{}: Failed to annotate image: {}", timestamp, e);
+                    error!(
+                        "{}: Failed to annotate image: {}",
+                        timestamp,
+                        ErrorChain(&e)
+                    );
                     None
                 }
             };
 
+            {
+                let mut state = state::lock(&state);
+                state.last_detection = significant_detections
+                    .iter()
+                    .max_by(|a, b| {
+                        a.confidence
+                            .partial_cmp(&b.confidence)
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .map(DetectionSummary::from_detection);
+                let annotated_frame = annotated_image
+                    .clone()
+                    .unwrap_or_else(|| processed_image_data.clone());
+                if let Some(clips) = &mut clips {
+                    clips.record(camera_index, annotated_frame.clone());
+                }
+                if let Some(report) = &mut print_report {
+                    report.record_frame(
+                        max_detection_prob / 100.0,
+                        significant_detections.len(),
+                        &annotated_frame,
+                    );
+                }
+                state.record_frame(camera_index, processed_image_data.clone(), annotated_frame);
+                state.push_event(
+                    EventLevel::Warning,
+                    format!(
+                        "Event #{}: detected {} failure(s) on camera {}, max confidence {:.1}%",
+                        event_id,
+                        significant_detections.len(),
+                        camera_index,
+                        max_detection_prob
+                    ),
+                );
+            }
+
+            // Animated clip of the last seconds, rendered once when the first alert needs it
+            let clip = std::cell::OnceCell::new();
+            let alert_image = || {
+                clip.get_or_init(|| clips.as_ref().and_then(|clips| clips.clip(camera_index)))
+                    .as_deref()
+                    .or(annotated_image.as_deref())
+            };
+
             // Process each detection for alerts and printer control
             for detection in significant_detections {
                 print_failures += 1;
 
-                // Check if we should pause the printer
-                if print_failures > constants::PRINT_FAILURE_THRESHOLD {
+                // Check if we should pause the printer. Once the printer ignored a
+                // pause, verifying again takes attempts × timeout and blocks the loop,
+                // so only retry after a cooldown.
+                let pause_cooling_down = pause_ignored_at.is_some_and(|at| {
+                    at.elapsed() < Duration::from_secs(constants::PAUSE_RETRY_COOLDOWN_SECONDS)
+                });
+                if print_failures > constants::PRINT_FAILURE_THRESHOLD && pause_cooling_down {
+                    debug!("Printer ignored the last pause, not pausing again yet");
+                } else if print_failures > constants::PRINT_FAILURE_THRESHOLD {
                     warn!(
                         "{}: Multiple print failures detected ({}). Pausing printer.",
                         timestamp, print_failures
                     );
-                    match printer_service.pause_print() {
-                        Ok(()) => {
-                            let pause_image = annotated_image.as_deref();
-                            if let Err(e) =
-                                alert_service.send_print_pause_alert(print_failures, pause_image)
-                            {
-                                error!("Failed to send pause alert: {}", e);
+                    metrics::get()
+                        .pauses
+                        .with_label_values(&[&config.printer_name])
+                        .inc();
+                    match printer_service.pause_print_verified(&config.pause_verification) {
+                        Ok(outcome) => {
+                            // Only a paused print is resumed when the event is acknowledged
+                            if let PauseOutcome::Paused { .. } = outcome {
+                                state::lock(&feedback).mark_paused(event_id);
+                            }
+                            if outcome.is_halted() {
+                                if let Some(report) = &mut print_report {
+                                    report.record_pause();
+                                }
+                                if let Some(digest) = &mut digest {
+                                    digest.record_pause();
+                                }
+                            }
+                            if let Err(e) = alert_service.send_print_pause_alert(
+                                print_failures,
+                                event_id,
+                                &outcome,
+                                alert_image(),
+                            ) {
+                                error!("Failed to queue pause alert: {}", ErrorChain(&e));
                             } else {
+                                if let Some(report) = &mut print_report {
+                                    report.record_alert();
+                                }
                                 info!(
-                                    "Print paused due to multiple failures. Alert sent to Discord with image."
+                                    "Pause outcome after multiple failures: {}. Pause alert queued with image.",
+                                    outcome.as_str()
                                 );
                             }
-                            print_failures = 0; // Reset after pausing
+                            let mut state = state::lock(&state);
+                            state.push_event(
+                                EventLevel::Critical,
+                                format!(
+                                    "Pause after {} failures (event #{}): {}",
+                                    print_failures,
+                                    event_id,
+                                    outcome.as_str()
+                                ),
+                            );
+                            // Keep counting while the print goes on, so the next failure
+                            // after the cooldown pauses again
+                            if outcome.is_halted() {
+                                state.failure_count = 0;
+                                print_failures = 0;
+                                pause_ignored_at = None;
+                            } else {
+                                pause_ignored_at = Some(Instant::now());
+                            }
                         }
                         Err(e) => {
-                            error!("Failed to pause print: {}", e);
+                            error!("Failed to pause print: {}", ErrorChain(&e));
+                            state::lock(&state).push_event(
+                                EventLevel::Critical,
+                                format!("Failed to pause print: {}", ErrorChain(&e)),
+                            );
                         }
                     }
                 }
 
                 // Send failure alert with annotated image
-                let image_data_ref = annotated_image.as_deref();
+                if alerts_snoozed {
+                    info!("Alerts are snoozed. Skipping print failure alert.");
+                    continue;
+                }
                 if let Err(e) = alert_service.send_print_failure_alert(
+                    event_id,
                     &detection.label,
                     detection.confidence_percent(),
                     detection.center_x(),
                     detection.center_y(),
                     detection.width(),
                     detection.height(),
-                    image_data_ref,
+                    alert_image(),
                 ) {
-                    error!("Failed to send Discord print failure alert: {}", e);
+                    error!("Failed to queue print failure alert: {}", ErrorChain(&e));
                 } else {
-                    info!("Sent Discord print failure alert with annotated image");
+                    if let Some(report) = &mut print_report {
+                        report.record_alert();
+                    }
+                    info!("Queued print failure alert with annotated image");
                 }
             }
+            state::lock(&state).failure_count = print_failures;
         } else {
             debug!("{}: No significant print failure detected.", timestamp);
+            if let Some(clips) = &mut clips {
+                clips.record(camera_index, processed_image_data.clone());
+            }
+            if let Some(report) = &mut print_report {
+                report.record_frame(max_detection_prob / 100.0, 0, &processed_image_data);
+            }
+            state::lock(&state).record_frame(
+                camera_index,
+                processed_image_data.clone(),
+                processed_image_data,
+            );
         }
 
         // Small delay before next iteration
@@ -348,17 +811,47 @@ fn main() -> Result<()> {
     }
 }
 
+/**
+ * Save the report of a finished print to the output directory and send its summary alert.
+ */
+fn send_print_report(
+    report: &PrintReport,
+    alert_service: &AlertService,
+    config: &Config,
+    alerts_snoozed: bool,
+) {
+    match report.save(&config.output_dir.join(constants::REPORTS_DIR)) {
+        Ok(dir) => info!("Print report saved to {}", dir.display()),
+        Err(e) => error!("Failed to save print report: {:#}", e),
+    }
+
+    if alerts_snoozed {
+        info!("Alerts are snoozed. Skipping print report alert.");
+        return;
+    }
+
+    let image = report
+        .render_summary_image()
+        .map_err(|e| warn!("Failed to render print report image: {:#}", e))
+        .ok();
+    match alert_service.send_print_report_alert(report, image.as_deref()) {
+        Ok(()) => info!("Print report alert queued"),
+        Err(e) => error!("Failed to queue print report alert: {}", ErrorChain(&e)),
+    }
+}
+
 /**
  * Fetch image data from the webcam URLs with retry logic.
  */
 fn get_image_data(
     alert_service: &AlertService,
     image_fetcher: &mut ImageFetcher,
+    state: &SharedState,
     url_index: Option<usize>,
 ) -> Option<Vec<u8>> {
     let image_urls_string = image_fetcher.get_image_urls_string();
     let max_retries = image_fetcher.get_max_retries();
-    let image_data = match image_fetcher.fetch_with_retry(
+    let image_data = image_fetcher.fetch_with_retry(
         |alert_type| match alert_type {
             AlertType::SystemOffline => {
                 alert_service.send_system_offline_alert(&image_urls_string, max_retries)
@@ -366,10 +859,13 @@ fn get_image_data(
             AlertType::SystemRecovery => alert_service.send_system_recovery_alert(),
         },
         url_index,
-    ) {
+    );
+    state::lock(state).cameras = image_fetcher.get_camera_health().to_vec();
+
+    let image_data = match image_data {
         Ok(data) => data,
         Err(e) => {
-            error!("Failed to fetch image: {}", e);
+            error!("Failed to fetch image: {}", ErrorChain(&e));
             thread::sleep(Duration::from_secs(constants::RETRY_DELAY_SECONDS));
             return None;
         }
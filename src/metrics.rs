@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+use anyhow::Result;
+use prometheus::{
+    Gauge, GaugeVec, Histogram, HistogramOpts, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Get the process-wide metrics registry.
+pub fn get() -> &'static Metrics {
+    &METRICS
+}
+
+/// Prometheus metrics collected across the application.
+///
+/// Metrics are registered in a dedicated registry and exposed in the
+/// Prometheus text format by the HTTP server's `/metrics` endpoint.
+pub struct Metrics {
+    registry: Registry,
+
+    /// Image fetch attempts per camera URL.
+    pub image_fetch_attempts: IntCounterVec,
+
+    /// Failed image fetches per camera URL.
+    pub image_fetch_failures: IntCounterVec,
+
+    /// Neural network inference duration.
+    pub inference_duration: Histogram,
+
+    /// Detections above the configured thresholds per label.
+    pub detections: IntCounterVec,
+
+    /// Alerts delivered per alert type.
+    pub alerts_sent: IntCounterVec,
+
+    /// Alerts that failed to deliver per alert type.
+    pub alerts_failed: IntCounterVec,
+
+    /// Alerts waiting in the delivery queue.
+    pub alert_queue_depth: Gauge,
+
+    /// Automatic pauses triggered by failure detection per printer, counted
+    /// once per decision regardless of retries.
+    pub pauses: IntCounterVec,
+
+    /// Failed Moonraker requests per endpoint.
+    pub moonraker_errors: IntCounterVec,
+
+    /// Highest detection confidence in the latest frame per printer.
+    pub failure_score: GaugeVec,
+
+    /// Failures counted towards the pause threshold per printer.
+    pub failure_count: GaugeVec,
+
+    /// Whether the monitoring loop considers the application ready (1) or not (0).
+    pub ready: Gauge,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("print_guardian".to_string()), None)
+            .expect("Failed to create metrics registry");
+
+        let metrics = Self {
+            image_fetch_attempts: IntCounterVec::new(
+                Opts::new(
+                    "image_fetch_attempts_total",
+                    "Image fetch attempts per camera",
+                ),
+                &["camera"],
+            )
+            .expect("Invalid metric"),
+            image_fetch_failures: IntCounterVec::new(
+                Opts::new(
+                    "image_fetch_failures_total",
+                    "Failed image fetches per camera",
+                ),
+                &["camera"],
+            )
+            .expect("Invalid metric"),
+            inference_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "inference_duration_seconds",
+                    "Neural network inference duration",
+                )
+                .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+            )
+            .expect("Invalid metric"),
+            detections: IntCounterVec::new(
+                Opts::new("detections_total", "Detections per label"),
+                &["label"],
+            )
+            .expect("Invalid metric"),
+            alerts_sent: IntCounterVec::new(
+                Opts::new("alerts_sent_total", "Alerts delivered per type"),
+                &["type"],
+            )
+            .expect("Invalid metric"),
+            alerts_failed: IntCounterVec::new(
+                Opts::new(
+                    "alerts_failed_total",
+                    "Alerts that failed to deliver per type",
+                ),
+                &["type"],
+            )
+            .expect("Invalid metric"),
+            alert_queue_depth: Gauge::new(
+                "alert_queue_depth",
+                "Alerts waiting in the delivery queue",
+            )
+            .expect("Invalid metric"),
+            pauses: IntCounterVec::new(
+                Opts::new("pauses_total", "Automatic print pauses triggered"),
+                &["printer"],
+            )
+            .expect("Invalid metric"),
+            moonraker_errors: IntCounterVec::new(
+                Opts::new(
+                    "moonraker_request_errors_total",
+                    "Failed Moonraker requests per endpoint",
+                ),
+                &["endpoint"],
+            )
+            .expect("Invalid metric"),
+            failure_score: GaugeVec::new(
+                Opts::new(
+                    "failure_score",
+                    "Highest detection confidence in the latest frame",
+                ),
+                &["printer"],
+            )
+            .expect("Invalid metric"),
+            failure_count: GaugeVec::new(
+                Opts::new(
+                    "failure_count",
+                    "Failures counted towards the pause threshold",
+                ),
+                &["printer"],
+            )
+            .expect("Invalid metric"),
+            ready: Gauge::new("ready", "Whether the application is ready").expect("Invalid metric"),
+            registry,
+        };
+
+        metrics.register_all().expect("Failed to register metrics");
+        metrics
+    }
+
+    fn register_all(&self) -> prometheus::Result<()> {
+        self.registry
+            .register(Box::new(self.image_fetch_attempts.clone()))?;
+        self.registry
+            .register(Box::new(self.image_fetch_failures.clone()))?;
+        self.registry
+            .register(Box::new(self.inference_duration.clone()))?;
+        self.registry.register(Box::new(self.detections.clone()))?;
+        self.registry.register(Box::new(self.alerts_sent.clone()))?;
+        self.registry
+            .register(Box::new(self.alerts_failed.clone()))?;
+        self.registry
+            .register(Box::new(self.alert_queue_depth.clone()))?;
+        self.registry.register(Box::new(self.pauses.clone()))?;
+        self.registry
+            .register(Box::new(self.moonraker_errors.clone()))?;
+        self.registry
+            .register(Box::new(self.failure_score.clone()))?;
+        self.registry
+            .register(Box::new(self.failure_count.clone()))?;
+        self.registry.register(Box::new(self.ready.clone()))?;
+        Ok(())
+    }
+
+    /// Record the outcome of an alert delivery and pass the result through.
+    pub fn record_alert<E>(&self, alert_type: &str, result: Result<(), E>) -> Result<(), E> {
+        match &result {
+            Ok(()) => self.alerts_sent.with_label_values(&[alert_type]).inc(),
+            Err(_) => self.alerts_failed.with_label_values(&[alert_type]).inc(),
+        }
+        result
+    }
+
+    /// Encode all metrics in the Prometheus text exposition format.
+    pub fn encode(&self) -> Result<String> {
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+}
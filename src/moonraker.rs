@@ -0,0 +1,416 @@
+#![allow(dead_code)]
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use serde_json::{Map, Value, json};
+use std::io::ErrorKind;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::config::constants;
+use crate::moonraker_auth::{MoonrakerAuth, MoonrakerCredentials};
+use crate::printer_status::PrinterStatus;
+use crate::state;
+
+/// Printer objects subscribed to in addition to the printer's heaters.
+pub const SUBSCRIBED_OBJECTS: &[&str] = &[
+    "webhooks",
+    "print_stats",
+    "virtual_sdcard",
+    "toolhead",
+    "heaters",
+];
+
+/// Printer state shared between the WebSocket client and its readers.
+pub type SharedPrinterState = Arc<Mutex<PrinterState>>;
+
+/// In-memory model of the printer, kept current by Moonraker's
+/// `notify_status_update` notifications.
+#[derive(Debug, Clone)]
+pub struct PrinterState {
+    /// Whether the WebSocket connection to Moonraker is established.
+    pub connected: bool,
+    /// State of Klippy as reported by Moonraker: `ready`, `startup`,
+    /// `shutdown`, `error` or `disconnected`.
+    pub klippy_state: String,
+    /// Latest status of every subscribed printer object.
+    pub objects: Map<String, Value>,
+    /// Time of the last status update.
+    pub updated_at: Option<Instant>,
+}
+
+impl Default for PrinterState {
+    fn default() -> Self {
+        Self {
+            connected: false,
+            klippy_state: "disconnected".to_string(),
+            objects: Map::new(),
+            updated_at: None,
+        }
+    }
+}
+
+impl PrinterState {
+    /// Check whether the model reflects a connected, ready printer.
+    pub fn is_ready(&self) -> bool {
+        self.connected && self.klippy_state == "ready" && !self.objects.is_empty()
+    }
+
+    /// Get the latest status of a printer object.
+    pub fn object(&self, name: &str) -> Option<&Value> {
+        self.objects.get(name)
+    }
+
+    /// Merge a status update into the model.
+    ///
+    /// Moonraker only sends the fields that changed, so fields missing from
+    /// the update keep their previous value.
+    pub fn apply_update(&mut self, status: &Value) {
+        let Some(status) = status.as_object() else {
+            return;
+        };
+        for (name, fields) in status {
+            let object = self
+                .objects
+                .entry(name.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            match (object.as_object_mut(), fields.as_object()) {
+                (Some(object), Some(fields)) => {
+                    for (field, value) in fields {
+                        object.insert(field.clone(), value.clone());
+                    }
+                }
+                _ => *object = fields.clone(),
+            }
+        }
+        self.updated_at = Some(Instant::now());
+    }
+
+    /// Get the typed printer status.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a printer object has an unexpected shape.
+    pub fn status(&self) -> Result<PrinterStatus, serde_json::Error> {
+        PrinterStatus::from_status(&Value::Object(self.objects.clone()))
+    }
+}
+
+/// WebSocket client for Moonraker's JSON-RPC API.
+///
+/// Subscribes to the print job, toolhead and heater objects and keeps a
+/// [`PrinterState`] up to date, so the monitoring loop reads the printer
+/// state from memory instead of polling. The connection is re-established
+/// automatically, and subscriptions are renewed whenever Klippy becomes
+/// ready again after a restart. With authentication enabled, every
+/// connection is authorized with a oneshot token.
+pub struct MoonrakerSocket {
+    url: String,
+    state: SharedPrinterState,
+    auth: MoonrakerAuth,
+}
+
+impl MoonrakerSocket {
+    /// Create a client for the Moonraker instance at `api_url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is not a valid `http://` or `https://` URL.
+    pub fn new(api_url: &str) -> Result<Self> {
+        Ok(Self {
+            url: websocket_url(api_url)?,
+            state: Arc::new(Mutex::new(PrinterState::default())),
+            auth: MoonrakerAuth::new(api_url, MoonrakerCredentials::None),
+        })
+    }
+
+    /// Authenticate connections, sharing the tokens with other users of `auth`.
+    pub fn with_auth(mut self, auth: MoonrakerAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Get the WebSocket URL the client connects to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the shared printer state.
+    pub fn state(&self) -> SharedPrinterState {
+        self.state.clone()
+    }
+
+    /// Connect to Moonraker and keep the printer state updated on a
+    /// background thread, reconnecting whenever the connection drops.
+    pub fn start(self) -> Result<thread::JoinHandle<()>> {
+        let handle = thread::Builder::new()
+            .name("moonraker".to_string())
+            .spawn(move || {
+                let mut outage_logged = false;
+                loop {
+                    let mut session =
+                        match Session::connect(&self.url, &self.auth, self.state.clone()) {
+                            Ok(session) => {
+                                info!("Connected to Moonraker WebSocket at {}", self.url);
+                                outage_logged = false;
+                                session
+                            }
+                            Err(e) => {
+                                // Only log the first failure of an outage
+                                if !outage_logged {
+                                    warn!("Failed to connect to Moonraker WebSocket: {:#}", e);
+                                    outage_logged = true;
+                                } else {
+                                    debug!("Failed to connect to Moonraker WebSocket: {:#}", e);
+                                }
+                                thread::sleep(Duration::from_secs(
+                                    constants::MOONRAKER_RECONNECT_SECONDS,
+                                ));
+                                continue;
+                            }
+                        };
+
+                    if let Err(e) = session.run() {
+                        warn!(
+                            "Moonraker WebSocket connection lost: {:#}. Reconnecting...",
+                            e
+                        );
+                    }
+                    state::lock(&self.state).connected = false;
+                    thread::sleep(Duration::from_secs(constants::MOONRAKER_RECONNECT_SECONDS));
+                }
+            })?;
+
+        Ok(handle)
+    }
+}
+
+/// Derive the WebSocket endpoint from the Moonraker API URL.
+///
+/// `http` URLs map to `ws` and `https` URLs to `wss`.
+///
+/// # Errors
+///
+/// Returns an error if the URL cannot be parsed or uses another scheme.
+pub fn websocket_url(api_url: &str) -> Result<String> {
+    let mut url = reqwest::Url::parse(api_url)
+        .with_context(|| format!("Invalid Moonraker URL '{}'", api_url))?;
+    let scheme = match url.scheme() {
+        "http" => "ws",
+        "https" => "wss",
+        _ => anyhow::bail!(
+            "Moonraker WebSocket requires an http:// or https:// URL, got '{}'",
+            api_url
+        ),
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| anyhow::anyhow!("Invalid Moonraker URL '{}'", api_url))?;
+    let path = format!("{}/websocket", url.path().trim_end_matches('/'));
+    url.set_path(&path);
+    Ok(url.to_string())
+}
+
+/// One WebSocket connection to Moonraker.
+struct Session {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    state: SharedPrinterState,
+    next_id: u64,
+    last_message: Instant,
+}
+
+impl Session {
+    fn connect(url: &str, auth: &MoonrakerAuth, state: SharedPrinterState) -> Result<Self> {
+        let mut parsed = reqwest::Url::parse(url)?;
+        if let Some(token) = auth.oneshot_token()? {
+            parsed.query_pairs_mut().append_pair("token", &token);
+        }
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| anyhow::anyhow!("Moonraker URL has no host"))?;
+        let port = parsed.port_or_known_default().unwrap_or(80);
+        let address = (host, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve {}", host))?;
+
+        let stream = TcpStream::connect_timeout(
+            &address,
+            Duration::from_secs(constants::MOONRAKER_CONNECT_TIMEOUT_SECONDS),
+        )?;
+        stream.set_read_timeout(Some(Duration::from_secs(
+            constants::MOONRAKER_READ_TIMEOUT_SECONDS,
+        )))?;
+        let (socket, _) = tungstenite::client_tls(parsed.as_str(), stream).map_err(|e| match e {
+            tungstenite::handshake::HandshakeError::Failure(tungstenite::Error::Http(response))
+                if matches!(response.status().as_u16(), 401 | 403) =>
+            {
+                auth.authentication_failed().into()
+            }
+            e => anyhow::anyhow!("WebSocket handshake failed: {}", e),
+        })?;
+
+        state::lock(&state).connected = true;
+        Ok(Self {
+            socket,
+            state,
+            next_id: 1,
+            last_message: Instant::now(),
+        })
+    }
+
+    /// Subscribe once Klippy is ready and process notifications until the
+    /// connection fails.
+    fn run(&mut self) -> Result<()> {
+        let info = self.call("server.info", Value::Null)?;
+        let klippy_state = info["klippy_state"].as_str().unwrap_or("disconnected");
+        state::lock(&self.state).klippy_state = klippy_state.to_string();
+        if klippy_state == "ready" {
+            self.subscribe()?;
+        } else {
+            info!(
+                "Klippy is in state '{}', waiting for it to become ready",
+                klippy_state
+            );
+        }
+
+        loop {
+            if let Some(message) = self.receive()? {
+                self.handle_notification(&message)?;
+            }
+        }
+    }
+
+    /// Subscribe to the printer objects, including every heater.
+    fn subscribe(&mut self) -> Result<()> {
+        let heaters = self.call(
+            "printer.objects.query",
+            json!({ "objects": { "heaters": ["available_heaters"] } }),
+        )?;
+        let heaters: Vec<String> = heaters["status"]["heaters"]["available_heaters"]
+            .as_array()
+            .map(|heaters| {
+                heaters
+                    .iter()
+                    .filter_map(|heater| heater.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let objects: Map<String, Value> = SUBSCRIBED_OBJECTS
+            .iter()
+            .map(|name| name.to_string())
+            .chain(heaters)
+            .map(|name| (name, Value::Null))
+            .collect();
+        let result = self.call("printer.objects.subscribe", json!({ "objects": objects }))?;
+
+        let mut state = state::lock(&self.state);
+        state.objects.clear();
+        state.apply_update(&result["status"]);
+        info!(
+            "Subscribed to Moonraker objects: {}",
+            state.objects.keys().cloned().collect::<Vec<_>>().join(", ")
+        );
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, message: &Value) -> Result<()> {
+        match message["method"].as_str() {
+            Some("notify_status_update") => {
+                state::lock(&self.state).apply_update(&message["params"][0]);
+            }
+            Some("notify_klippy_ready") => {
+                info!("Klippy is ready");
+                state::lock(&self.state).klippy_state = "ready".to_string();
+                // Klippy restarts drop all subscriptions
+                self.subscribe()?;
+            }
+            Some("notify_klippy_shutdown") => {
+                warn!("Klippy shut down");
+                state::lock(&self.state).klippy_state = "shutdown".to_string();
+            }
+            Some("notify_klippy_disconnected") => {
+                warn!("Klippy disconnected from Moonraker");
+                let mut state = state::lock(&self.state);
+                state.klippy_state = "disconnected".to_string();
+                state.objects.clear();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Send a JSON-RPC request and wait for its result, handling
+    /// notifications that arrive in the meantime.
+    fn call(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut request = json!({ "jsonrpc": "2.0", "method": method, "id": id });
+        if !params.is_null() {
+            request["params"] = params;
+        }
+        self.socket
+            .send(Message::Text(request.to_string()))
+            .with_context(|| format!("Failed to send {} request", method))?;
+
+        let deadline =
+            Instant::now() + Duration::from_secs(constants::MOONRAKER_IDLE_TIMEOUT_SECONDS);
+        while Instant::now() < deadline {
+            let Some(message) = self.receive()? else {
+                continue;
+            };
+            if message["id"].as_u64() == Some(id) {
+                if let Some(error) = message.get("error") {
+                    anyhow::bail!(
+                        "Moonraker {} request failed: {}",
+                        method,
+                        error["message"].as_str().unwrap_or("unknown error")
+                    );
+                }
+                return Ok(message["result"].clone());
+            }
+            self.handle_notification(&message)?;
+        }
+
+        anyhow::bail!("Moonraker {} request timed out", method)
+    }
+
+    /// Read the next JSON-RPC message.
+    ///
+    /// Returns `None` for control frames and read timeouts. Idle connections
+    /// are probed with a ping and dropped when Moonraker stays silent.
+    fn receive(&mut self) -> Result<Option<Value>> {
+        match self.socket.read() {
+            Ok(Message::Text(text)) => {
+                self.last_message = Instant::now();
+                match serde_json::from_str(&text) {
+                    Ok(message) => Ok(Some(message)),
+                    Err(e) => {
+                        debug!("Ignoring invalid Moonraker message: {}", e);
+                        Ok(None)
+                    }
+                }
+            }
+            Ok(Message::Close(_)) => anyhow::bail!("Moonraker closed the connection"),
+            Ok(_) => {
+                self.last_message = Instant::now();
+                Ok(None)
+            }
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                let idle = self.last_message.elapsed();
+                if idle > Duration::from_secs(constants::MOONRAKER_IDLE_TIMEOUT_SECONDS) {
+                    anyhow::bail!("No response from Moonraker for {}s", idle.as_secs());
+                }
+                self.socket.send(Message::Ping(Vec::new()))?;
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
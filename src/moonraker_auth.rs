@@ -0,0 +1,264 @@
+#![allow(dead_code)]
+use log::{debug, info};
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{BoxError, NetworkError, PrinterError};
+use crate::state;
+
+/// Credentials for Moonraker's `[authorization]` component.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MoonrakerCredentials {
+    /// No authentication, for trusted clients.
+    #[default]
+    None,
+    /// An API key sent in the `X-Api-Key` header.
+    ApiKey(String),
+    /// A Moonraker user, logged in through `/access/login`.
+    Login { username: String, password: String },
+}
+
+/// JSON Web Tokens of a logged in Moonraker user.
+#[derive(Debug, Clone)]
+struct Tokens {
+    access: String,
+    refresh: String,
+}
+
+/// Authenticates HTTP requests and WebSocket connections to Moonraker.
+///
+/// Requests carry either the API key or the access token of a logged in
+/// user. Access tokens expire after an hour; when Moonraker rejects one, it
+/// is refreshed with the refresh token, or the user logs in again, and the
+/// request is retried once. Requests that are still rejected fail with
+/// [`PrinterError::AuthenticationFailed`], which retrying will not fix.
+///
+/// Clones share the tokens, so the HTTP and WebSocket clients only log in once.
+#[derive(Debug, Clone)]
+pub struct MoonrakerAuth {
+    api_url: String,
+    credentials: MoonrakerCredentials,
+    tokens: Arc<Mutex<Option<Tokens>>>,
+    client: Client,
+}
+
+impl MoonrakerAuth {
+    /// Create an authenticator for the Moonraker instance at `api_url`.
+    pub fn new(api_url: &str, credentials: MoonrakerCredentials) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            credentials,
+            tokens: Arc::new(Mutex::new(None)),
+            client: Client::new(),
+        }
+    }
+
+    /// Check whether requests are authenticated.
+    pub fn is_enabled(&self) -> bool {
+        self.credentials != MoonrakerCredentials::None
+    }
+
+    /// Send an authenticated request.
+    ///
+    /// `build` creates the request and is called again when the request is
+    /// retried with a refreshed access token.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PrinterError::ConnectionFailed`] if Moonraker cannot be
+    /// reached, or a [`PrinterError::AuthenticationFailed`] if it rejects
+    /// the credentials.
+    pub fn send(
+        &self,
+        build: impl Fn(&Client) -> RequestBuilder,
+    ) -> Result<Response, PrinterError> {
+        let response = self.execute(self.authorize(build(&self.client))?)?;
+        if response.status() != StatusCode::UNAUTHORIZED
+            || !matches!(self.credentials, MoonrakerCredentials::Login { .. })
+        {
+            return self.check_authorized(response);
+        }
+
+        debug!("Moonraker rejected the access token, refreshing it");
+        self.renew_tokens()?;
+        let response = self.execute(self.authorize(build(&self.client))?)?;
+        self.check_authorized(response)
+    }
+
+    /// Get a oneshot token for authenticating a WebSocket connection.
+    ///
+    /// Returns `None` without credentials, since no token is needed then.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token cannot be requested or Moonraker
+    /// rejects the credentials.
+    pub fn oneshot_token(&self) -> Result<Option<String>, PrinterError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+
+        let endpoint = "access/oneshot_token";
+        let url = format!("{}/{}", self.api_url, endpoint);
+        let response = self.send(|client| client.get(&url))?;
+        if !response.status().is_success() {
+            return Err(api_error(endpoint, response));
+        }
+        let body = json_body(endpoint, response)?;
+        let token = body["result"]
+            .as_str()
+            .ok_or_else(|| invalid_response(endpoint, "no oneshot token in response"))?;
+        Ok(Some(token.to_string()))
+    }
+
+    /// Get the error returned when Moonraker rejects the credentials.
+    pub fn authentication_failed(&self) -> PrinterError {
+        PrinterError::AuthenticationFailed {
+            api_url: self.api_url.clone(),
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, PrinterError> {
+        Ok(match &self.credentials {
+            MoonrakerCredentials::None => request,
+            MoonrakerCredentials::ApiKey(key) => request.header("X-Api-Key", key),
+            MoonrakerCredentials::Login { .. } => {
+                let existing = state::lock(&self.tokens).clone();
+                let tokens = match existing {
+                    Some(tokens) => tokens,
+                    None => self.login()?,
+                };
+                request.bearer_auth(tokens.access)
+            }
+        })
+    }
+
+    /// Send a request, reporting failures to reach Moonraker as
+    /// [`PrinterError::ConnectionFailed`].
+    fn execute(&self, request: RequestBuilder) -> Result<Response, PrinterError> {
+        let connection_failed = |url: &str, e| PrinterError::ConnectionFailed {
+            api_url: self.api_url.clone(),
+            source: NetworkError::from_reqwest(url, e),
+        };
+        let request = request
+            .build()
+            .map_err(|e| connection_failed(&self.api_url, e))?;
+        let url = request.url().to_string();
+        self.client
+            .execute(request)
+            .map_err(|e| connection_failed(&url, e))
+    }
+
+    fn check_authorized(&self, response: Response) -> Result<Response, PrinterError> {
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(self.authentication_failed()),
+            _ => Ok(response),
+        }
+    }
+
+    /// Refresh the access token, logging in again if the refresh token
+    /// was rejected too.
+    fn renew_tokens(&self) -> Result<(), PrinterError> {
+        let refresh = state::lock(&self.tokens)
+            .as_ref()
+            .map(|tokens| tokens.refresh.clone());
+        if let Some(refresh) = refresh {
+            let url = format!("{}/access/refresh_jwt", self.api_url);
+            let response = self.execute(
+                self.client
+                    .post(url)
+                    .json(&json!({ "refresh_token": refresh })),
+            )?;
+            let status = response.status();
+            if status.is_success() {
+                let body = json_body("access/refresh_jwt", response)?;
+                if let Some(access) = body["result"]["token"].as_str() {
+                    let mut tokens = state::lock(&self.tokens);
+                    if let Some(tokens) = tokens.as_mut() {
+                        tokens.access = access.to_string();
+                    }
+                    return Ok(());
+                }
+            }
+            debug!(
+                "Moonraker rejected the refresh token (HTTP {}), logging in again",
+                status
+            );
+        }
+
+        self.login().map(|_| ())
+    }
+
+    fn login(&self) -> Result<Tokens, PrinterError> {
+        let MoonrakerCredentials::Login { username, password } = &self.credentials else {
+            return Err(self.authentication_failed());
+        };
+
+        let endpoint = "access/login";
+        let url = format!("{}/{}", self.api_url, endpoint);
+        let response = self.execute(self.client.post(url).json(&json!({
+            "username": username,
+            "password": password,
+            "source": "moonraker",
+        })))?;
+        let response = self.check_authorized(response)?;
+        if !response.status().is_success() {
+            return Err(api_error(endpoint, response));
+        }
+
+        let body = json_body(endpoint, response)?;
+        let token = |name: &str| {
+            body["result"][name]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| invalid_response(endpoint, format!("no {} in response", name)))
+        };
+        let tokens = Tokens {
+            access: token("token")?,
+            refresh: token("refresh_token")?,
+        };
+        info!("Logged in to Moonraker as '{}'", username);
+
+        *state::lock(&self.tokens) = Some(tokens.clone());
+        Ok(tokens)
+    }
+}
+
+/// Build the error for an unsuccessful Moonraker response, taking the
+/// message from Moonraker's `{"error": {"message": ...}}` body.
+pub(crate) fn api_error(endpoint: &str, response: Response) -> PrinterError {
+    let status = response.status();
+    let message = response
+        .json::<serde_json::Value>()
+        .ok()
+        .and_then(|body| body["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| {
+            status
+                .canonical_reason()
+                .unwrap_or("unknown error")
+                .to_string()
+        });
+    PrinterError::ApiError {
+        endpoint: endpoint.to_string(),
+        status: status.as_u16(),
+        message,
+    }
+}
+
+/// Read the JSON body of a Moonraker response.
+pub(crate) fn json_body(
+    endpoint: &str,
+    response: Response,
+) -> Result<serde_json::Value, PrinterError> {
+    response.json().map_err(|e| invalid_response(endpoint, e))
+}
+
+/// Build the error for a Moonraker response that cannot be understood.
+pub(crate) fn invalid_response(endpoint: &str, source: impl Into<BoxError>) -> PrinterError {
+    PrinterError::InvalidResponse {
+        endpoint: endpoint.to_string(),
+        source: source.into(),
+    }
+}
@@ -0,0 +1,442 @@
+#![allow(dead_code)]
+use anyhow::Result;
+use log::{debug, error, info, warn};
+use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS};
+use serde_json::json;
+use std::{
+    sync::{Arc, mpsc},
+    thread,
+    time::Duration,
+};
+
+use crate::config::constants;
+use crate::error::ErrorChain;
+use crate::printer::PrinterService;
+use crate::state::{self, EventLevel, SharedState};
+
+/// Connection settings for the MQTT broker.
+#[derive(Debug, Clone)]
+pub struct MqttSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Home Assistant discovery prefix, usually `homeassistant`.
+    pub discovery_prefix: String,
+    /// Unique ID of this printer, used in topics and entity IDs.
+    pub node_id: String,
+}
+
+/// MQTT bridge publishing monitoring state to Home Assistant.
+///
+/// All entities are announced through Home Assistant MQTT discovery and grouped
+/// into one device per printer:
+/// * Sensors for the failure score, last detection and printer state
+/// * A binary sensor for camera availability
+/// * An image entity showing the latest annotated frame
+/// * Buttons to pause or resume the print and to snooze alerts
+///
+/// Topics live under `print_guardian/<node_id>/`. Button presses arrive on
+/// `print_guardian/<node_id>/command/<action>` and are mapped onto
+/// [`PrinterService`] actions or the shared monitoring state. Commands run
+/// on their own thread, so a slow Moonraker does not stall the MQTT
+/// connection.
+///
+/// Like a snooze through the control API, a snooze from MQTT only lasts
+/// until Print Guardian restarts.
+pub struct MqttBridge {
+    state: SharedState,
+    printer_service: PrinterService,
+    settings: MqttSettings,
+}
+
+impl MqttBridge {
+    /// Create a new MqttBridge.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - Shared monitoring state to publish
+    /// * `printer_service` - Printer service used for button commands
+    /// * `settings` - Broker connection settings
+    pub fn new(
+        state: SharedState,
+        printer_service: PrinterService,
+        settings: MqttSettings,
+    ) -> Self {
+        Self {
+            state,
+            printer_service,
+            settings,
+        }
+    }
+
+    /// Base topic for this printer's state and commands.
+    pub fn base_topic(&self) -> String {
+        format!("print_guardian/{}", self.settings.node_id)
+    }
+
+    /// Connect to the broker and start the bridge on background threads.
+    ///
+    /// The connection is re-established automatically; discovery messages and
+    /// command subscriptions are renewed on every reconnect. Received commands
+    /// are handed to a separate thread, since they block on Moonraker.
+    pub fn start(self) -> Result<thread::JoinHandle<()>> {
+        let base_topic = self.base_topic();
+        let mut options = MqttOptions::new(
+            format!("print-guardian-{}", self.settings.node_id),
+            self.settings.host.clone(),
+            self.settings.port,
+        );
+        options
+            .set_keep_alive(Duration::from_secs(30))
+            .set_max_packet_size(
+                constants::MQTT_MAX_PACKET_SIZE,
+                constants::MQTT_MAX_PACKET_SIZE,
+            )
+            .set_last_will(LastWill::new(
+                format!("{}/availability", base_topic),
+                "offline",
+                QoS::AtLeastOnce,
+                true,
+            ));
+        if let (Some(username), Some(password)) = (&self.settings.username, &self.settings.password)
+        {
+            options.set_credentials(username, password);
+        }
+
+        // Discovery messages are queued from the event loop thread itself, so the
+        // request channel must hold all of them at once
+        let (client, mut connection) = Client::new(options, 32);
+
+        info!(
+            "Connecting to MQTT broker at {}:{}",
+            self.settings.host, self.settings.port
+        );
+
+        let publisher = client.clone();
+        let state = self.state.clone();
+        thread::Builder::new()
+            .name("mqtt-publisher".to_string())
+            .spawn(move || publish_state_loop(publisher, state, base_topic))?;
+
+        let bridge = Arc::new(self);
+        let (commands, received) = mpsc::channel::<(String, String)>();
+        let handler = bridge.clone();
+        thread::Builder::new()
+            .name("mqtt-commands".to_string())
+            .spawn(move || {
+                for (topic, payload) in received {
+                    handler.handle_command(&topic, &payload);
+                }
+            })?;
+
+        let handle = thread::Builder::new()
+            .name("mqtt".to_string())
+            .spawn(move || {
+                for notification in connection.iter() {
+                    match notification {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            info!("Connected to MQTT broker");
+                            if let Err(e) = bridge.announce(&client) {
+                                error!("Failed to announce MQTT entities: {}", e);
+                            }
+                        }
+                        Ok(Event::Incoming(Packet::Publish(publish))) => {
+                            let payload = String::from_utf8_lossy(&publish.payload);
+                            if commands
+                                .send((publish.topic.clone(), payload.trim().to_string()))
+                                .is_err()
+                            {
+                                error!("MQTT command handler stopped, ignoring {}", publish.topic);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            warn!("MQTT connection error: {}. Reconnecting...", e);
+                            thread::sleep(Duration::from_secs(constants::RETRY_DELAY_SECONDS));
+                        }
+                    }
+                }
+            })?;
+
+        Ok(handle)
+    }
+
+    /// Publish discovery configs and availability, and subscribe to commands.
+    fn announce(&self, client: &Client) -> Result<()> {
+        for (component, object_id, config) in self.discovery_configs() {
+            client.try_publish(
+                format!(
+                    "{}/{}/{}/{}/config",
+                    self.settings.discovery_prefix, component, self.settings.node_id, object_id
+                ),
+                QoS::AtLeastOnce,
+                true,
+                config.to_string(),
+            )?;
+        }
+
+        let base_topic = self.base_topic();
+        client.try_publish(
+            format!("{}/availability", base_topic),
+            QoS::AtLeastOnce,
+            true,
+            "online",
+        )?;
+        client.try_subscribe(format!("{}/command/+", base_topic), QoS::AtLeastOnce)?;
+        Ok(())
+    }
+
+    /// Build the Home Assistant discovery configs as `(component, object_id, config)`.
+    pub fn discovery_configs(&self) -> Vec<(&'static str, &'static str, serde_json::Value)> {
+        let base_topic = self.base_topic();
+        let node_id = &self.settings.node_id;
+        let device = json!({
+            "identifiers": [format!("print_guardian_{}", node_id)],
+            "name": format!("Print Guardian {}", node_id),
+            "manufacturer": "Print Guardian",
+            "model": "Print failure detection",
+        });
+        let availability_topic = format!("{}/availability", base_topic);
+        let state_topic = format!("{}/state", base_topic);
+
+        let entity = |object_id: &str, name: &str, extra: serde_json::Value| {
+            let mut config = json!({
+                "name": name,
+                "unique_id": format!("print_guardian_{}_{}", node_id, object_id),
+                "object_id": format!("print_guardian_{}_{}", node_id, object_id),
+                "availability_topic": availability_topic,
+                "device": device,
+            });
+            if let (Some(config), Some(extra)) = (config.as_object_mut(), extra.as_object()) {
+                config.extend(extra.clone());
+            }
+            config
+        };
+
+        let button = |action: &str, name: &str, icon: &str| {
+            entity(
+                action,
+                name,
+                json!({
+                    "command_topic": format!("{}/command/{}", base_topic, action),
+                    "payload_press": "PRESS",
+                    "icon": icon,
+                }),
+            )
+        };
+
+        vec![
+            (
+                "sensor",
+                "failure_score",
+                entity(
+                    "failure_score",
+                    "Failure score",
+                    json!({
+                        "state_topic": state_topic,
+                        "value_template": "{{ value_json.failure_score }}",
+                        "unit_of_measurement": "%",
+                        "state_class": "measurement",
+                        "icon": "mdi:alert-decagram",
+                    }),
+                ),
+            ),
+            (
+                "sensor",
+                "last_detection",
+                entity(
+                    "last_detection",
+                    "Last detection",
+                    json!({
+                        "state_topic": state_topic,
+                        "value_template": "{{ value_json.last_detection.label if value_json.last_detection else 'none' }}",
+                        "json_attributes_topic": format!("{}/last_detection", base_topic),
+                        "icon": "mdi:magnify-scan",
+                    }),
+                ),
+            ),
+            (
+                "sensor",
+                "printer_state",
+                entity(
+                    "printer_state",
+                    "Printer state",
+                    json!({
+                        "state_topic": state_topic,
+                        "value_template": "{{ value_json.printer_state }}",
+                        "icon": "mdi:printer-3d",
+                    }),
+                ),
+            ),
+            (
+                "binary_sensor",
+                "camera",
+                entity(
+                    "camera",
+                    "Camera",
+                    json!({
+                        "state_topic": state_topic,
+                        "value_template": "{{ 'ON' if value_json.camera_available else 'OFF' }}",
+                        "device_class": "connectivity",
+                    }),
+                ),
+            ),
+            (
+                "image",
+                "latest_frame",
+                entity(
+                    "latest_frame",
+                    "Latest frame",
+                    json!({
+                        "image_topic": format!("{}/image", base_topic),
+                        "content_type": "image/jpeg",
+                    }),
+                ),
+            ),
+            (
+                "button",
+                "pause",
+                button("pause", "Pause print", "mdi:pause"),
+            ),
+            (
+                "button",
+                "resume",
+                button("resume", "Resume print", "mdi:play"),
+            ),
+            (
+                "button",
+                "snooze",
+                button("snooze", "Snooze alerts", "mdi:bell-sleep"),
+            ),
+        ]
+    }
+
+    /// Handle a message on a command topic.
+    pub fn handle_command(&self, topic: &str, payload: &str) {
+        let Some(action) = topic
+            .strip_prefix(&format!("{}/command/", self.base_topic()))
+            .map(str::to_string)
+        else {
+            debug!("Ignoring MQTT message on {}", topic);
+            return;
+        };
+
+        info!("Received MQTT command '{}'", action);
+        match action.as_str() {
+            "pause" => match self.printer_service.pause_print() {
+                Ok(()) => state::lock(&self.state)
+                    .push_event(EventLevel::Warning, "Print paused through MQTT"),
+                Err(e) => error!(
+                    "Failed to pause print from MQTT command: {}",
+                    ErrorChain(&e)
+                ),
+            },
+            "resume" => match self.printer_service.resume_print() {
+                Ok(()) => state::lock(&self.state)
+                    .push_event(EventLevel::Info, "Print resumed through MQTT"),
+                Err(e) => error!(
+                    "Failed to resume print from MQTT command: {}",
+                    ErrorChain(&e)
+                ),
+            },
+            "snooze" => {
+                // Buttons send "PRESS", but a number of minutes is accepted as well
+                let minutes = payload
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|&m| m > 0)
+                    .unwrap_or(constants::MQTT_SNOOZE_MINUTES);
+                let until = chrono::Local::now() + chrono::Duration::minutes(minutes);
+                {
+                    let mut state = state::lock(&self.state);
+                    state.alerts_snoozed_until = Some(until);
+                    state.push_event(
+                        EventLevel::Info,
+                        format!("Alerts snoozed for {} minutes through MQTT", minutes),
+                    );
+                }
+                info!("Alerts snoozed until {} through MQTT", until.to_rfc3339());
+            }
+            other => warn!("Unknown MQTT command '{}'", other),
+        }
+    }
+}
+
+/// Build the JSON published on the state topic.
+pub fn state_payload(state: &crate::state::MonitorState) -> serde_json::Value {
+    json!({
+        "failure_score": (state.failure_score * 1000.0).round() / 10.0,
+        "failure_count": state.failure_count,
+        "last_detection": state.last_detection.as_ref().map(|d| d.to_json()),
+        "printer_state": state.printer_state,
+        "camera_available": state.any_camera_healthy(),
+        "monitoring_paused": state.monitoring_paused,
+        "alerts_snoozed": state.alerts_snoozed(),
+    })
+}
+
+/// Periodically publish the state and, when it changed, the latest frame.
+fn publish_state_loop(client: Client, state: SharedState, base_topic: String) {
+    let mut last_payload = String::new();
+    let mut last_frame_version = None;
+
+    loop {
+        let (payload, last_detection, frame) = {
+            let state = state::lock(&state);
+            let frame = (last_frame_version != Some(state.frame_version))
+                .then(|| {
+                    state
+                        .latest_annotated_frame
+                        .clone()
+                        .map(|f| (state.frame_version, f))
+                })
+                .flatten();
+            (
+                state_payload(&state).to_string(),
+                state.last_detection.as_ref().map(|d| d.to_json()),
+                frame,
+            )
+        };
+
+        // Messages are dropped rather than queued while the broker is unreachable;
+        // everything is retained and republished once it changes again
+        if payload != last_payload {
+            let published = client
+                .try_publish(
+                    format!("{}/state", base_topic),
+                    QoS::AtLeastOnce,
+                    true,
+                    payload.clone(),
+                )
+                .and_then(|_| {
+                    client.try_publish(
+                        format!("{}/last_detection", base_topic),
+                        QoS::AtLeastOnce,
+                        true,
+                        last_detection.unwrap_or(json!({})).to_string(),
+                    )
+                });
+            match published {
+                Ok(()) => last_payload = payload,
+                Err(e) => debug!("Failed to queue MQTT state update: {}", e),
+            }
+        }
+
+        if let Some((version, frame)) = frame {
+            match client.try_publish(
+                format!("{}/image", base_topic),
+                QoS::AtMostOnce,
+                true,
+                frame,
+            ) {
+                Ok(()) => last_frame_version = Some(version),
+                Err(e) => debug!("Failed to queue MQTT frame: {}", e),
+            }
+        }
+
+        thread::sleep(Duration::from_secs(
+            constants::MQTT_PUBLISH_INTERVAL_SECONDS,
+        ));
+    }
+}
@@ -0,0 +1,296 @@
+use anyhow::Result;
+use log::warn;
+use reqwest::blocking::multipart;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{Alert, AlertImage, Notifier, PrintJob, RateLimited};
+
+/// Largest attachment accepted by Discord webhooks on servers without boosts,
+/// leaving room for the rest of the request.
+const MAX_UPLOAD_BYTES: usize = 10_000_000;
+
+/// Discord webhook notifier sending rich embed messages.
+///
+/// Alerts belonging to a print job are kept in a single message per job:
+/// the first one is posted with `?wait=true` to learn the message ID, and
+/// later ones edit that message through the webhook's message endpoint.
+pub struct DiscordNotifier {
+    name: String,
+    webhook_url: String,
+    /// Message ID per print job ID.
+    job_messages: Mutex<HashMap<String, String>>,
+}
+
+impl DiscordNotifier {
+    /// Create a new DiscordNotifier with the provided webhook URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook_url` - A valid Discord webhook URL
+    ///
+    pub fn new(webhook_url: String) -> Self {
+        Self {
+            name: "discord".to_string(),
+            webhook_url,
+            job_messages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the destination name used for routing rules, logs and the alert queue.
+    ///
+    /// Needed to tell several Discord channels apart, e.g. `discord-owner`
+    /// and `discord-log`.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Build the Discord embed for an alert.
+    ///
+    /// When the alert has an image, the embed references it as an attachment
+    /// so Discord displays it inline.
+    fn build_embed(alert: &Alert) -> serde_json::Value {
+        let fields: Vec<_> = alert
+            .fields
+            .iter()
+            .map(|field| {
+                json!({
+                    "name": field.name,
+                    "value": field.value,
+                    "inline": field.inline,
+                })
+            })
+            .collect();
+
+        let mut embed = json!({
+            "title": alert.display_title(),
+            "description": alert.description,
+            "color": alert.color,
+            "timestamp": alert.timestamp.to_rfc3339(),
+            "fields": fields,
+            "footer": {
+                "text": "Print Guardian"
+            }
+        });
+
+        if let Some(image) = &alert.image {
+            embed["image"] = json!({
+                "url": format!("attachment://{}", image.filename)
+            });
+        }
+
+        json!({ "embeds": [embed] })
+    }
+
+    /// Execute a webhook request carrying the payload and an optional image.
+    ///
+    /// Requests with an image are sent as a multipart upload with the
+    /// payload in `payload_json` and the image in `files[0]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - Discord rate limits the request ([`RateLimited`])
+    fn execute(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+        payload: &serde_json::Value,
+        image: Option<&AlertImage>,
+    ) -> Result<reqwest::blocking::Response> {
+        let request = match image {
+            Some(image) => request.multipart(
+                multipart::Form::new()
+                    .text("payload_json", payload.to_string())
+                    .part(
+                        "files[0]",
+                        multipart::Part::bytes(image.data.clone())
+                            .file_name(image.filename.clone())
+                            .mime_str(&image.mime_type)?,
+                    ),
+            ),
+            None => request.json(payload),
+        };
+
+        let response = request.send()?;
+        check_rate_limit(&response)?;
+        Ok(response)
+    }
+
+    /// Post a new message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or Discord returns an error status.
+    fn send_alert(&self, payload: &serde_json::Value, image: Option<&AlertImage>) -> Result<()> {
+        let client = reqwest::blocking::Client::new();
+        let response = self.execute(client.post(&self.webhook_url), payload, image)?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to send Discord alert: HTTP {}",
+                response.status()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Post a new message and return its ID.
+    ///
+    /// With `?wait=true` Discord responds with the created message instead
+    /// of `204 No Content`. Once Discord accepted the message it counts as
+    /// delivered, so a response without a message ID returns `None` instead
+    /// of an error that would make the alert queue post it again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or Discord returns an error status.
+    fn create_message(
+        &self,
+        payload: &serde_json::Value,
+        image: Option<&AlertImage>,
+    ) -> Result<Option<String>> {
+        let client = reqwest::blocking::Client::new();
+        let request = client.post(&self.webhook_url).query(&[("wait", "true")]);
+        let response = self.execute(request, payload, image)?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to send Discord alert: HTTP {}",
+                response.status()
+            ));
+        }
+
+        let message_id = response
+            .json::<serde_json::Value>()
+            .ok()
+            .and_then(|message| message["id"].as_str().map(str::to_string));
+        if message_id.is_none() {
+            warn!("Discord did not return a message ID, the next update posts a new message");
+        }
+        Ok(message_id)
+    }
+
+    /// Edit a message previously posted through the webhook.
+    ///
+    /// A new image replaces the message's previous attachment. Returns
+    /// `false` if the message no longer exists, e.g. because it was deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or Discord returns an error status.
+    fn edit_message(
+        &self,
+        message_id: &str,
+        payload: &serde_json::Value,
+        image: Option<&AlertImage>,
+    ) -> Result<bool> {
+        let mut url = reqwest::Url::parse(&self.webhook_url)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Discord webhook URL"))?
+            .pop_if_empty()
+            .extend(["messages", message_id]);
+
+        let mut payload = payload.clone();
+        if let Some(image) = image {
+            payload["attachments"] = json!([{ "id": 0, "filename": image.filename }]);
+        }
+
+        let client = reqwest::blocking::Client::new();
+        let response = self.execute(client.patch(url), &payload, image)?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to edit Discord message: HTTP {}",
+                response.status()
+            ));
+        }
+
+        Ok(true)
+    }
+
+    /// Post or edit the message of a print job.
+    ///
+    /// The first update of a job creates its message; later updates edit it
+    /// until the job's final update, after which the job is forgotten.
+    fn send_job_update(
+        &self,
+        job: &PrintJob,
+        payload: &serde_json::Value,
+        image: Option<&AlertImage>,
+    ) -> Result<()> {
+        let message_id = self.lock_jobs().get(&job.id).cloned();
+        let edited = match &message_id {
+            Some(message_id) => self.edit_message(message_id, payload, image)?,
+            None => false,
+        };
+
+        if !edited && let Some(message_id) = self.create_message(payload, image)? {
+            self.lock_jobs().insert(job.id.clone(), message_id);
+        }
+        if job.finished {
+            self.lock_jobs().remove(&job.id);
+        }
+
+        Ok(())
+    }
+
+    fn lock_jobs(&self) -> std::sync::MutexGuard<'_, HashMap<String, String>> {
+        self.job_messages
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&self, alert: &Alert) -> Result<()> {
+        let payload = Self::build_embed(alert);
+
+        match &alert.job {
+            Some(job) => self.send_job_update(job, &payload, alert.image.as_ref()),
+            None => self.send_alert(&payload, alert.image.as_ref()),
+        }
+    }
+
+    fn updates_in_place(&self) -> bool {
+        true
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+}
+
+/// Turn a Discord `429 Too Many Requests` response into a [`RateLimited`] error.
+///
+/// Discord reports the wait time in the `Retry-After` header and, with
+/// sub-second precision, in the `retry_after` field of the JSON body. The
+/// header is used so the response body does not need to be consumed.
+fn check_rate_limit(response: &reqwest::blocking::Response) -> Result<()> {
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Ok(());
+    }
+
+    let retry_after = response
+        .headers()
+        .get("Retry-After")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or(1.0);
+
+    Err(RateLimited {
+        retry_after: Duration::from_secs_f64(retry_after.max(0.0)),
+    }
+    .into())
+}
@@ -0,0 +1,445 @@
+use anyhow::{Context, Result};
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use log::info;
+use serde_json::json;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{Alert, AlertKind, Notifier, Severity};
+use crate::alert_queue::AlertQueue;
+use crate::templates::MessageTemplates;
+
+/// Largest image attached to an email, so the base64 encoded message stays
+/// below the common 25 MB limit of mail providers.
+const MAX_UPLOAD_BYTES: usize = 15_000_000;
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587).
+    StartTls,
+    /// Implicit TLS from the start of the connection (usually port 465).
+    Tls,
+    /// Unencrypted connection, only suitable for local relays.
+    None,
+}
+
+impl SmtpSecurity {
+    /// Get the default port for the security mode.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::None => 25,
+        }
+    }
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" | "ssl" => Ok(SmtpSecurity::Tls),
+            "none" => Ok(SmtpSecurity::None),
+            other => Err(format!(
+                "unknown SMTP security mode '{}' (expected starttls, tls or none)",
+                other
+            )),
+        }
+    }
+}
+
+/// Connection and addressing settings for the SMTP notifier.
+#[derive(Debug, Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Non-critical alerts waiting to be sent as a digest.
+struct Digest {
+    interval: Duration,
+    alerts: Vec<Alert>,
+    first_queued: Option<Instant>,
+    /// On-disk copy of the pending alerts, so they survive a restart.
+    store: Option<AlertQueue>,
+    /// Store entries of the pending alerts.
+    stored: Vec<String>,
+}
+
+/// SMTP email notifier.
+///
+/// Sends HTML emails with the alert image inline and a plain-text fallback.
+/// In digest mode, alerts below [`Severity::Critical`] are collected and sent
+/// as a single email once the digest interval has passed since the first of
+/// them was queued; critical alerts are always sent immediately. With a
+/// digest directory (see [`EmailNotifier::with_digest_dir`]) pending digest
+/// alerts are kept on disk until the digest is sent.
+pub struct EmailNotifier {
+    transport: SmtpTransport,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    digest: Option<Mutex<Digest>>,
+    templates: MessageTemplates,
+}
+
+impl EmailNotifier {
+    /// Create a new EmailNotifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - SMTP server and addressing settings
+    /// * `digest_interval` - Batch non-critical alerts for this long, `None` sends every alert immediately
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an address cannot be parsed or the TLS setup fails.
+    pub fn new(settings: SmtpSettings, digest_interval: Option<Duration>) -> Result<Self> {
+        let mut builder = match settings.security {
+            SmtpSecurity::StartTls => SmtpTransport::starttls_relay(&settings.host)?,
+            SmtpSecurity::Tls => SmtpTransport::relay(&settings.host)?,
+            SmtpSecurity::None => SmtpTransport::builder_dangerous(&settings.host),
+        }
+        .port(settings.port)
+        .timeout(Some(Duration::from_secs(30)));
+
+        if let (Some(username), Some(password)) = (settings.username, settings.password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let from = settings
+            .from
+            .parse::<Mailbox>()
+            .with_context(|| format!("Invalid sender address '{}'", settings.from))?;
+        let to = settings
+            .to
+            .iter()
+            .map(|address| {
+                address
+                    .parse::<Mailbox>()
+                    .with_context(|| format!("Invalid recipient address '{}'", address))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+            digest: digest_interval.map(|interval| {
+                Mutex::new(Digest {
+                    interval,
+                    alerts: Vec::new(),
+                    first_queued: None,
+                    store: None,
+                    stored: Vec::new(),
+                })
+            }),
+            templates: MessageTemplates::default(),
+        })
+    }
+
+    /// Render the digest subject and introduction with the given templates
+    /// instead of the bundled English ones.
+    pub fn with_templates(mut self, templates: MessageTemplates) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Keep pending digest alerts in `dir` so they survive a restart.
+    ///
+    /// Alerts left over from a previous run are loaded and sent with the
+    /// next digest. Does nothing if digest mode is off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or read.
+    pub fn with_digest_dir(self, dir: impl Into<PathBuf>) -> Result<Self> {
+        let Some(digest) = &self.digest else {
+            return Ok(self);
+        };
+
+        let store = AlertQueue::open(dir)?;
+        {
+            let mut digest = lock(digest);
+            for entry in store.due() {
+                // Keep the interval running from when the oldest alert was queued
+                let age = (chrono::Utc::now() - entry.alert.timestamp)
+                    .to_std()
+                    .unwrap_or_default();
+                let queued = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+                digest.first_queued.get_or_insert(queued);
+                digest.stored.push(entry.id);
+                digest.alerts.push(entry.alert);
+            }
+            if !digest.alerts.is_empty() {
+                info!(
+                    "Loaded {} pending email digest alert(s)",
+                    digest.alerts.len()
+                );
+            }
+            digest.store = Some(store);
+        }
+        Ok(self)
+    }
+
+    /// Get the number of alerts waiting for the next digest.
+    pub fn pending_digest_alerts(&self) -> usize {
+        self.digest
+            .as_ref()
+            .map_or(0, |digest| lock(digest).alerts.len())
+    }
+
+    /// Send all pending digest alerts now, regardless of the digest interval.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the digest email could not be sent. The alerts
+    /// are kept and retried with the next digest.
+    pub fn send_digest(&self) -> Result<()> {
+        let Some(digest) = &self.digest else {
+            return Ok(());
+        };
+
+        let (alerts, stored) = {
+            let mut digest = lock(digest);
+            digest.first_queued = None;
+            (
+                std::mem::take(&mut digest.alerts),
+                std::mem::take(&mut digest.stored),
+            )
+        };
+        if alerts.is_empty() {
+            return Ok(());
+        }
+
+        let result = self
+            .templates
+            .render(
+                "email_digest",
+                AlertKind::PrinterStatus,
+                &json!({ "count": alerts.len() }),
+            )
+            .and_then(|intro| self.send_digest_email(&intro, &alerts));
+        let mut digest = lock(digest);
+        match &result {
+            Ok(()) => {
+                info!("Sent email digest with {} alert(s)", alerts.len());
+                if let Some(store) = &digest.store {
+                    for id in &stored {
+                        store.remove(id);
+                    }
+                }
+            }
+            Err(_) => {
+                // Put the alerts back so they are part of the next digest
+                let mut retained = alerts;
+                retained.append(&mut digest.alerts);
+                digest.alerts = retained;
+                let mut retained = stored;
+                retained.append(&mut digest.stored);
+                digest.stored = retained;
+                digest.first_queued.get_or_insert_with(Instant::now);
+            }
+        }
+        result
+    }
+
+    /// Send pending alerts as one digest email, introduced by the rendered
+    /// `email_digest` template.
+    fn send_digest_email(&self, intro: &Alert, alerts: &[Alert]) -> Result<()> {
+        let plain = std::iter::once(intro.description.clone())
+            .chain(alerts.iter().map(render_plain))
+            .collect::<Vec<_>>()
+            .join("\n\n----------------------------------------\n\n");
+        let html = std::iter::once(format!("<p>{}</p>", escape_html(&intro.description)))
+            .chain(
+                alerts
+                    .iter()
+                    .enumerate()
+                    .map(|(index, alert)| render_html(alert, &format!("image{}", index))),
+            )
+            .collect::<Vec<_>>()
+            .join("<hr>");
+        let images: Vec<_> = alerts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, alert)| {
+                alert
+                    .image
+                    .as_ref()
+                    .map(|image| (format!("image{}", index), image))
+            })
+            .collect();
+
+        self.send_email(&intro.title, plain, wrap_html(&html), &images)
+    }
+
+    /// Build and send a multipart email.
+    ///
+    /// The message is `multipart/alternative` with a plain-text part and a
+    /// `multipart/related` HTML part carrying the images referenced by `cid:`.
+    fn send_email(
+        &self,
+        subject: &str,
+        plain: String,
+        html: String,
+        images: &[(String, &super::AlertImage)],
+    ) -> Result<()> {
+        let mut related = MultiPart::related().singlepart(SinglePart::html(html));
+        for (content_id, image) in images {
+            related = related.singlepart(
+                Attachment::new_inline(content_id.clone())
+                    .body(image.data.clone(), ContentType::parse(&image.mime_type)?),
+            );
+        }
+
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject);
+        for recipient in &self.to {
+            builder = builder.to(recipient.clone());
+        }
+
+        let message = builder.multipart(
+            MultiPart::alternative()
+                .singlepart(SinglePart::plain(plain))
+                .multipart(related),
+        )?;
+
+        self.transport
+            .send(&message)
+            .context("Failed to send email")?;
+        Ok(())
+    }
+
+    /// Send a single alert as its own email.
+    fn send_alert_email(&self, alert: &Alert) -> Result<()> {
+        let images: Vec<_> = alert
+            .image
+            .iter()
+            .map(|image| ("image0".to_string(), image))
+            .collect();
+
+        self.send_email(
+            &alert.display_title(),
+            render_plain(alert),
+            wrap_html(&render_html(alert, "image0")),
+            &images,
+        )
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<()> {
+        if let Some(digest) = &self.digest
+            && alert.severity < Severity::Critical
+        {
+            let mut digest = lock(digest);
+            // A failure to store the alert is returned, so the alert queue keeps it
+            if let Some(store) = &digest.store {
+                let id = store.push(self.name(), alert)?;
+                digest.stored.push(id);
+            }
+            digest.alerts.push(alert.clone());
+            digest.first_queued.get_or_insert_with(Instant::now);
+            return Ok(());
+        }
+
+        self.send_alert_email(alert)
+    }
+
+    fn flush(&self) -> Result<()> {
+        let due = self.digest.as_ref().is_some_and(|digest| {
+            let digest = lock(digest);
+            digest
+                .first_queued
+                .is_some_and(|queued| queued.elapsed() >= digest.interval)
+        });
+
+        if due { self.send_digest() } else { Ok(()) }
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+}
+
+fn lock(digest: &Mutex<Digest>) -> std::sync::MutexGuard<'_, Digest> {
+    digest
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Render an alert as plain text.
+fn render_plain(alert: &Alert) -> String {
+    format!(
+        "{}\n{}\n\n{}",
+        alert.display_title(),
+        alert.timestamp.to_rfc2822(),
+        alert.plain_text()
+    )
+}
+
+/// Render an alert as an HTML fragment, referencing its image by content ID.
+fn render_html(alert: &Alert, content_id: &str) -> String {
+    let mut html = format!(
+        "<h2 style=\"border-left: 6px solid #{:06X}; padding-left: 8px\">{}</h2>\
+         <p style=\"color: #666\">{}</p><p>{}</p>",
+        alert.color,
+        escape_html(&alert.display_title()),
+        escape_html(&alert.timestamp.to_rfc2822()),
+        escape_html(&alert.description).replace('\n', "<br>")
+    );
+
+    if !alert.fields.is_empty() {
+        html.push_str("<table cellpadding=\"4\">");
+        for field in &alert.fields {
+            html.push_str(&format!(
+                "<tr><th align=\"left\">{}</th><td>{}</td></tr>",
+                escape_html(&field.name),
+                escape_html(&field.value)
+            ));
+        }
+        html.push_str("</table>");
+    }
+
+    if let Some(image) = &alert.image {
+        html.push_str(&format!(
+            "<p><img src=\"cid:{}\" alt=\"{}\" style=\"max-width: 100%\"></p>",
+            content_id,
+            escape_html(&image.filename)
+        ));
+    }
+
+    html
+}
+
+/// Wrap HTML fragments in a complete document.
+fn wrap_html(body: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><body style=\"font-family: sans-serif\">{}\
+         <p style=\"color: #999; font-size: small\">Print Guardian</p></body></html>",
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use base64::Engine;
+use serde_json::json;
+
+use super::{Alert, Notifier, PushPriority};
+
+/// Largest image embedded in a message, since base64 data URIs make every
+/// byte count in Gotify's database and clients.
+const MAX_UPLOAD_BYTES: usize = 1_000_000;
+
+/// Gotify push notifier.
+///
+/// Sends Markdown messages to a Gotify server. Gotify has no attachments,
+/// so the annotated image is embedded in the Markdown as a data URI.
+pub struct GotifyNotifier {
+    server_url: String,
+    app_token: String,
+    click_url: Option<String>,
+}
+
+impl GotifyNotifier {
+    /// Create a new GotifyNotifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `server_url` - Base URL of the Gotify server
+    /// * `app_token` - Application token used to publish messages
+    /// * `click_url` - Optional URL opened when the notification is tapped
+    ///
+    pub fn new(server_url: String, app_token: String, click_url: Option<String>) -> Self {
+        Self {
+            server_url: server_url.trim_end_matches('/').to_string(),
+            app_token,
+            click_url,
+        }
+    }
+
+    /// Map a push priority to Gotify's 0 to 10 scale.
+    fn priority(priority: PushPriority) -> u8 {
+        match priority {
+            PushPriority::Low => 2,
+            PushPriority::Default => 5,
+            PushPriority::High => 7,
+            PushPriority::Urgent => 10,
+        }
+    }
+
+    /// Build the Gotify message payload for an alert.
+    pub fn build_message(&self, alert: &Alert) -> serde_json::Value {
+        let mut message = alert.description.clone();
+        if !alert.fields.is_empty() {
+            message.push('\n');
+            for field in &alert.fields {
+                message.push_str(&format!("\n**{}:** {}  ", field.name, field.value));
+            }
+        }
+        if let Some(image) = &alert.image {
+            message.push_str(&format!(
+                "\n\n![{}](data:{};base64,{})",
+                image.filename,
+                image.mime_type,
+                base64::engine::general_purpose::STANDARD.encode(&image.data)
+            ));
+        }
+
+        let mut extras = json!({
+            "client::display": { "contentType": "text/markdown" }
+        });
+        if let Some(click_url) = &self.click_url {
+            extras["client::notification"] = json!({ "click": { "url": click_url } });
+        }
+
+        json!({
+            "title": alert.display_title(),
+            "message": message,
+            "priority": Self::priority(alert.kind.push_priority()),
+            "extras": extras,
+        })
+    }
+}
+
+impl Notifier for GotifyNotifier {
+    fn name(&self) -> &str {
+        "gotify"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<()> {
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .post(format!("{}/message", self.server_url))
+            .header("X-Gotify-Key", &self.app_token)
+            .json(&self.build_message(alert))
+            .send()?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to send Gotify notification: HTTP {}",
+                response.status()
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+}
@@ -0,0 +1,689 @@
+#![allow(dead_code)]
+//! Alert delivery backends.
+//!
+//! Every backend implements the [`Notifier`] trait and receives the same
+//! backend-neutral [`Alert`]. The [`AlertService`](crate::alerts::AlertService)
+//! fans each alert out to all configured notifiers.
+
+use anyhow::Result;
+use base64::Engine;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::config::{Config, constants};
+use crate::templates::MessageTemplates;
+
+pub mod discord;
+pub mod email;
+pub mod gotify;
+pub mod ntfy;
+pub mod slack;
+pub mod telegram;
+pub mod webhook;
+
+pub use discord::DiscordNotifier;
+pub use email::{EmailNotifier, SmtpSecurity, SmtpSettings};
+pub use gotify::GotifyNotifier;
+pub use ntfy::NtfyNotifier;
+pub use slack::SlackNotifier;
+pub use telegram::TelegramNotifier;
+pub use webhook::{WebhookNotifier, WebhookSettings};
+
+/// The kind of event an alert reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    /// A print failure was detected.
+    PrintFailure,
+    /// The print was paused after repeated failures.
+    PrintPaused,
+    /// The printer state changed.
+    PrinterStatus,
+    /// Image fetching failed repeatedly and monitoring is offline.
+    SystemOffline,
+    /// Image fetching recovered after being offline.
+    SystemRecovery,
+    /// Progress of the current print job, only delivered to notifiers that
+    /// update a message in place.
+    PrintProgress,
+    /// Summary report of a finished print.
+    PrintReport,
+    /// Scheduled daily or weekly digest of the printer's statistics.
+    FleetDigest,
+}
+
+impl AlertKind {
+    /// Every alert kind.
+    pub const ALL: [AlertKind; 8] = [
+        AlertKind::PrintFailure,
+        AlertKind::PrintPaused,
+        AlertKind::PrinterStatus,
+        AlertKind::SystemOffline,
+        AlertKind::SystemRecovery,
+        AlertKind::PrintProgress,
+        AlertKind::PrintReport,
+        AlertKind::FleetDigest,
+    ];
+
+    /// Get the short identifier of the alert kind, used in logs and metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::PrintFailure => "failure",
+            AlertKind::PrintPaused => "pause",
+            AlertKind::PrinterStatus => "status",
+            AlertKind::SystemOffline => "offline",
+            AlertKind::SystemRecovery => "recovery",
+            AlertKind::PrintProgress => "progress",
+            AlertKind::PrintReport => "report",
+            AlertKind::FleetDigest => "digest",
+        }
+    }
+
+    /// Parse an alert kind from its short identifier.
+    pub fn from_name(kind: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == kind)
+    }
+
+    /// Get the default severity of the alert kind.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            AlertKind::PrintFailure => Severity::Warning,
+            AlertKind::PrintPaused | AlertKind::SystemOffline => Severity::Critical,
+            AlertKind::PrinterStatus
+            | AlertKind::SystemRecovery
+            | AlertKind::PrintProgress
+            | AlertKind::PrintReport
+            | AlertKind::FleetDigest => Severity::Info,
+        }
+    }
+
+    /// Get the default sidebar color (as a hex value) of the alert kind.
+    pub fn default_color(&self) -> u32 {
+        match self {
+            AlertKind::PrintFailure => 0xFFA500, // Orange
+            AlertKind::PrintPaused | AlertKind::SystemOffline => 0xFF0000, // Red
+            AlertKind::PrinterStatus | AlertKind::PrintProgress | AlertKind::PrintReport => {
+                0x0099FF // Blue
+            }
+            AlertKind::SystemRecovery => 0x00FF00, // Green
+            AlertKind::FleetDigest => 0x9B59B6,    // Purple
+        }
+    }
+
+    /// Get the push notification priority of the alert kind.
+    pub fn push_priority(&self) -> PushPriority {
+        match self {
+            AlertKind::PrintPaused | AlertKind::SystemOffline => PushPriority::Urgent,
+            AlertKind::PrintFailure => PushPriority::High,
+            AlertKind::SystemRecovery | AlertKind::PrintReport => PushPriority::Default,
+            AlertKind::PrinterStatus | AlertKind::PrintProgress | AlertKind::FleetDigest => {
+                PushPriority::Low
+            }
+        }
+    }
+
+    /// Get the default emoji of the alert kind.
+    pub fn default_emoji(&self) -> &'static str {
+        match self {
+            AlertKind::PrintFailure => "⚠️",
+            AlertKind::PrintPaused | AlertKind::SystemOffline => "🚨",
+            AlertKind::PrinterStatus => "ℹ️",
+            AlertKind::SystemRecovery => "✅",
+            AlertKind::PrintProgress => "🖨️",
+            AlertKind::PrintReport => "📋",
+            AlertKind::FleetDigest => "📊",
+        }
+    }
+}
+
+/// How urgently an alert needs attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    /// Get the lowercase name of the severity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// Parse a severity from its lowercase name.
+    pub fn from_name(severity: &str) -> Option<Self> {
+        [Severity::Info, Severity::Warning, Severity::Critical]
+            .into_iter()
+            .find(|s| s.as_str() == severity)
+    }
+}
+
+/// Priority of a push notification, mapped to each push service's own scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PushPriority {
+    Low,
+    Default,
+    High,
+    Urgent,
+}
+
+/// A named value attached to an alert (e.g. "Confidence": "87.50%").
+#[derive(Debug, Clone)]
+pub struct AlertField {
+    pub name: String,
+    pub value: String,
+    /// Whether the field can be displayed next to other inline fields.
+    pub inline: bool,
+}
+
+/// An image attached to an alert.
+#[derive(Debug, Clone)]
+pub struct AlertImage {
+    /// Encoded image bytes.
+    pub data: Vec<u8>,
+    /// File name used when uploading the image.
+    pub filename: String,
+    /// MIME type of the image data.
+    pub mime_type: String,
+}
+
+impl AlertImage {
+    /// Create an image attachment named `<prefix>_<unix timestamp>.<ext>`.
+    ///
+    /// The extension and MIME type are derived from the image data,
+    /// defaulting to JPEG.
+    pub fn new(data: Vec<u8>, filename_prefix: &str) -> Self {
+        let (extension, mime_type) = match image::guess_format(&data) {
+            Ok(image::ImageFormat::Png) => ("png", "image/png"),
+            Ok(image::ImageFormat::Gif) => ("gif", "image/gif"),
+            Ok(image::ImageFormat::WebP) => ("webp", "image/webp"),
+            _ => ("jpg", "image/jpeg"),
+        };
+
+        Self {
+            data,
+            filename: format!(
+                "{}_{}.{}",
+                filename_prefix,
+                chrono::Utc::now().timestamp(),
+                extension
+            ),
+            mime_type: mime_type.to_string(),
+        }
+    }
+
+    /// Get a version of the image no larger than `max_bytes`.
+    ///
+    /// Images that already fit are returned unchanged. Larger ones are
+    /// re-encoded as JPEG with decreasing quality and size until they fit;
+    /// animated GIFs are reduced to their latest frame. Returns `None` if
+    /// the image cannot be decoded or does not fit even at the smallest size.
+    pub fn fit(&self, max_bytes: usize) -> Option<AlertImage> {
+        if self.data.len() <= max_bytes {
+            return Some(self.clone());
+        }
+
+        let image = match image::guess_format(&self.data) {
+            Ok(image::ImageFormat::Gif) => {
+                use image::AnimationDecoder;
+                let decoder =
+                    image::codecs::gif::GifDecoder::new(std::io::Cursor::new(&self.data)).ok()?;
+                let frame = decoder.into_frames().collect_frames().ok()?.pop()?;
+                image::DynamicImage::ImageRgba8(frame.into_buffer())
+            }
+            _ => image::load_from_memory(&self.data).ok()?,
+        };
+        let image = image::DynamicImage::ImageRgb8(image.to_rgb8());
+
+        let mut width = image.width();
+        loop {
+            let scaled = if width < image.width() {
+                image.resize(width, u32::MAX, image::imageops::FilterType::Triangle)
+            } else {
+                image.clone()
+            };
+            for quality in constants::UPLOAD_JPEG_QUALITIES {
+                let mut data = Vec::new();
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, quality)
+                    .encode_image(&scaled)
+                    .ok()?;
+                if data.len() <= max_bytes {
+                    let stem = self
+                        .filename
+                        .rsplit_once('.')
+                        .map_or(self.filename.as_str(), |(stem, _)| stem);
+                    return Some(AlertImage {
+                        data,
+                        filename: format!("{}.jpg", stem),
+                        mime_type: "image/jpeg".to_string(),
+                    });
+                }
+            }
+
+            if width <= constants::UPLOAD_MIN_WIDTH {
+                return None;
+            }
+            width = (width * 3 / 4).max(constants::UPLOAD_MIN_WIDTH);
+        }
+    }
+}
+
+/// The detection an alert is about, for backends that need structured data.
+#[derive(Debug, Clone)]
+pub struct AlertDetection {
+    /// The type of failure detected (e.g. "spaghetti").
+    pub label: String,
+    /// Confidence percentage (0.0 to 100.0).
+    pub confidence: f32,
+    /// Bounding box center and size.
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+/// The print job an alert belongs to.
+///
+/// Backends that support it keep a single message per job and update it
+/// in place instead of posting every status change as a new message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintJob {
+    /// Identifier of the job, unique across prints.
+    pub id: String,
+    /// Whether this is the final update of the job.
+    pub finished: bool,
+}
+
+/// A backend-neutral alert.
+///
+/// Notifiers decide how to render each part; `color` and `emoji` are
+/// presentation hints that backends without such concepts may ignore.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub severity: Severity,
+    pub title: String,
+    pub description: String,
+    pub fields: Vec<AlertField>,
+    pub image: Option<AlertImage>,
+    pub detection: Option<AlertDetection>,
+    pub color: u32,
+    pub emoji: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub job: Option<PrintJob>,
+}
+
+impl Alert {
+    /// Create a new alert with the default severity, color and emoji of its kind.
+    pub fn new(kind: AlertKind, title: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            kind,
+            severity: kind.default_severity(),
+            title: title.into(),
+            description: description.into(),
+            fields: Vec::new(),
+            image: None,
+            detection: None,
+            color: kind.default_color(),
+            emoji: kind.default_emoji().to_string(),
+            timestamp: chrono::Utc::now(),
+            job: None,
+        }
+    }
+
+    /// Attach an inline field.
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push(AlertField {
+            name: name.into(),
+            value: value.into(),
+            inline: true,
+        });
+        self
+    }
+
+    /// Attach an image, if one is available.
+    pub fn with_image(mut self, image_data: Option<&[u8]>, filename_prefix: &str) -> Self {
+        self.image = image_data.map(|data| AlertImage::new(data.to_vec(), filename_prefix));
+        self
+    }
+
+    /// Attach the detection the alert is about.
+    pub fn with_detection(mut self, detection: AlertDetection) -> Self {
+        self.detection = Some(detection);
+        self
+    }
+
+    /// Associate the alert with a print job, if there is one.
+    pub fn with_job(mut self, job: Option<&PrintJob>) -> Self {
+        self.job = job.cloned();
+        self
+    }
+
+    /// Get the title prefixed with the alert's emoji.
+    pub fn display_title(&self) -> String {
+        if self.emoji.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} {}", self.emoji, self.title)
+        }
+    }
+
+    /// Serialize the alert to JSON, embedding the image as base64.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "kind": self.kind.as_str(),
+            "severity": self.severity.as_str(),
+            "title": self.title,
+            "description": self.description,
+            "fields": self.fields.iter().map(|f| json!({
+                "name": f.name,
+                "value": f.value,
+                "inline": f.inline,
+            })).collect::<Vec<_>>(),
+            "image": self.image.as_ref().map(|image| json!({
+                "data": base64::engine::general_purpose::STANDARD.encode(&image.data),
+                "filename": image.filename,
+                "mime_type": image.mime_type,
+            })),
+            "detection": self.detection.as_ref().map(|d| json!({
+                "label": d.label,
+                "confidence": d.confidence,
+                "x": d.x,
+                "y": d.y,
+                "w": d.w,
+                "h": d.h,
+            })),
+            "color": self.color,
+            "emoji": self.emoji,
+            "timestamp": self.timestamp.to_rfc3339(),
+            "job": self.job.as_ref().map(|job| json!({
+                "id": job.id,
+                "finished": job.finished,
+            })),
+        })
+    }
+
+    /// Deserialize an alert written by [`Alert::to_json`].
+    ///
+    /// Returns `None` if the JSON is not a valid alert.
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let string = |v: &serde_json::Value| v.as_str().map(str::to_string);
+        let float = |v: &serde_json::Value| v.as_f64().map(|f| f as f32);
+
+        let image = match &value["image"] {
+            serde_json::Value::Null => None,
+            image => Some(AlertImage {
+                data: base64::engine::general_purpose::STANDARD
+                    .decode(image["data"].as_str()?)
+                    .ok()?,
+                filename: string(&image["filename"])?,
+                mime_type: string(&image["mime_type"])?,
+            }),
+        };
+
+        let detection = match &value["detection"] {
+            serde_json::Value::Null => None,
+            d => Some(AlertDetection {
+                label: string(&d["label"])?,
+                confidence: float(&d["confidence"])?,
+                x: float(&d["x"])?,
+                y: float(&d["y"])?,
+                w: float(&d["w"])?,
+                h: float(&d["h"])?,
+            }),
+        };
+
+        let job = match &value["job"] {
+            serde_json::Value::Null => None,
+            job => Some(PrintJob {
+                id: string(&job["id"])?,
+                finished: job["finished"].as_bool().unwrap_or(false),
+            }),
+        };
+
+        let fields = value["fields"]
+            .as_array()?
+            .iter()
+            .map(|f| {
+                Some(AlertField {
+                    name: string(&f["name"])?,
+                    value: string(&f["value"])?,
+                    inline: f["inline"].as_bool().unwrap_or(true),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            kind: AlertKind::from_name(value["kind"].as_str()?)?,
+            severity: Severity::from_name(value["severity"].as_str()?)?,
+            title: string(&value["title"])?,
+            description: string(&value["description"])?,
+            fields,
+            image,
+            detection,
+            color: value["color"].as_u64()? as u32,
+            emoji: string(&value["emoji"])?,
+            timestamp: chrono::DateTime::parse_from_rfc3339(value["timestamp"].as_str()?)
+                .ok()?
+                .with_timezone(&chrono::Utc),
+            job,
+        })
+    }
+
+    /// Render the description followed by one `Name: value` line per field.
+    ///
+    /// Used by backends that only support plain text bodies.
+    pub fn plain_text(&self) -> String {
+        let mut text = self.description.clone();
+        if !self.fields.is_empty() {
+            text.push('\n');
+            for field in &self.fields {
+                text.push_str(&format!("\n{}: {}", field.name, field.value));
+            }
+        }
+        text
+    }
+}
+
+/// Error returned by a notifier when the backend rate-limits requests.
+///
+/// The alert queue waits at least `retry_after` before sending anything
+/// else to the same notifier.
+#[derive(Debug)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rate limited, retry after {:.1}s",
+            self.retry_after.as_secs_f64()
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Steps of a multi-step delivery that already succeeded.
+///
+/// Notifiers that deliver an alert in several requests, e.g. a message and
+/// an image upload or one message per chat, record every completed step.
+/// The alert queue stores the progress with the entry, so a retry only
+/// repeats the steps that failed instead of posting the alert again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeliveryProgress {
+    completed: BTreeSet<String>,
+}
+
+impl DeliveryProgress {
+    /// Create progress with the given steps already completed.
+    pub fn new(completed: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            completed: completed.into_iter().collect(),
+        }
+    }
+
+    /// Check whether a step already completed.
+    pub fn is_completed(&self, step: &str) -> bool {
+        self.completed.contains(step)
+    }
+
+    /// Get the completed steps.
+    pub fn steps(&self) -> impl Iterator<Item = &str> {
+        self.completed.iter().map(String::as_str)
+    }
+
+    /// Run a step unless it already completed, recording it if it succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the step.
+    pub fn run(&mut self, step: &str, send: impl FnOnce() -> Result<()>) -> Result<()> {
+        if self.is_completed(step) {
+            return Ok(());
+        }
+        send()?;
+        self.completed.insert(step.to_string());
+        Ok(())
+    }
+}
+
+/// A destination that alerts can be delivered to.
+pub trait Notifier: Send + Sync {
+    /// Short, human-readable name of the backend, used in logs.
+    fn name(&self) -> &str;
+
+    /// Deliver an alert.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alert could not be delivered.
+    fn send(&self, alert: &Alert) -> Result<()>;
+
+    /// Deliver an alert, skipping the steps an earlier attempt completed.
+    ///
+    /// Used by the alert queue so retries don't repeat what was already
+    /// delivered. Notifiers that deliver an alert in a single request don't
+    /// need to implement this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alert could not be delivered. `progress`
+    /// holds the steps that completed before the failure.
+    fn send_resumable(&self, alert: &Alert, _progress: &mut DeliveryProgress) -> Result<()> {
+        self.send(alert)
+    }
+
+    /// Deliver alerts the notifier has held back, if they are due.
+    ///
+    /// Called periodically by the monitoring loop. Notifiers that send every
+    /// alert immediately don't need to implement this.
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether the notifier keeps one message per print job and edits it.
+    ///
+    /// Only such notifiers receive [`AlertKind::PrintProgress`] alerts.
+    fn updates_in_place(&self) -> bool {
+        false
+    }
+
+    /// Largest image in bytes the backend accepts, `None` for no limit.
+    ///
+    /// Larger images are compressed or dropped before they are handed to
+    /// the notifier, see [`AlertImage::fit`].
+    fn max_upload_bytes(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Build the notifiers enabled by the configuration.
+///
+/// New backends are added here so the rest of the application does not need
+/// to know which channels are enabled.
+///
+/// # Errors
+///
+/// Returns an error if a configured backend cannot be set up.
+pub fn from_config(config: &Config) -> Result<Vec<Box<dyn Notifier>>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+
+    if let Some(webhook_url) = &config.discord_webhook {
+        notifiers.push(Box::new(DiscordNotifier::new(webhook_url.clone())));
+    }
+
+    for (channel, webhook_url) in &config.discord_channels {
+        notifiers.push(Box::new(
+            DiscordNotifier::new(webhook_url.clone()).with_name(format!("discord-{}", channel)),
+        ));
+    }
+
+    if let Some(bot_token) = &config.telegram_bot_token {
+        notifiers.push(Box::new(TelegramNotifier::new(
+            bot_token.clone(),
+            config.telegram_chat_ids.clone(),
+        )));
+    }
+
+    let slack_bot = config
+        .slack_bot_token
+        .clone()
+        .zip(config.slack_channel_id.clone());
+    match (&config.slack_webhook_url, slack_bot) {
+        (Some(webhook_url), bot) => {
+            let mut notifier = SlackNotifier::from_webhook(webhook_url.clone());
+            if let Some((bot_token, channel_id)) = bot {
+                notifier = notifier.with_bot(bot_token, channel_id);
+            }
+            notifiers.push(Box::new(notifier));
+        }
+        (None, Some((bot_token, channel_id))) => {
+            notifiers.push(Box::new(SlackNotifier::new(bot_token, channel_id)));
+        }
+        (None, None) => {}
+    }
+
+    if let Some(topic_url) = &config.ntfy_url {
+        notifiers.push(Box::new(NtfyNotifier::new(
+            topic_url.clone(),
+            config.ntfy_token.clone(),
+            config.printer_web_url.clone(),
+        )));
+    }
+
+    if let (Some(server_url), Some(app_token)) = (&config.gotify_url, &config.gotify_token) {
+        notifiers.push(Box::new(GotifyNotifier::new(
+            server_url.clone(),
+            app_token.clone(),
+            config.printer_web_url.clone(),
+        )));
+    }
+
+    if let Some(settings) = &config.webhook {
+        notifiers.push(Box::new(
+            WebhookNotifier::new(settings.clone())?
+                .with_image_dir(config.output_dir.join(constants::ALERT_IMAGE_DIR))?
+                .with_image_signing_key(config.api_token.clone()),
+        ));
+    }
+
+    if let Some(settings) = &config.smtp {
+        notifiers.push(Box::new(
+            EmailNotifier::new(settings.clone(), config.smtp_digest_interval)?
+                .with_templates(MessageTemplates::load(
+                    &config.locale,
+                    config.templates_dir.as_deref(),
+                )?)
+                .with_digest_dir(config.output_dir.join(constants::EMAIL_DIGEST_DIR))?,
+        ));
+    }
+
+    Ok(notifiers)
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+
+use super::{Alert, Notifier, PushPriority};
+
+/// Largest attachment accepted by the public ntfy.sh server.
+const MAX_UPLOAD_BYTES: usize = 15_000_000;
+
+/// ntfy push notifier.
+///
+/// Publishes to a topic URL (e.g. `https://ntfy.sh/my-printer`). Alert
+/// metadata is sent as query parameters so non-ASCII titles survive, and
+/// the annotated image, if any, is uploaded as the message attachment.
+pub struct NtfyNotifier {
+    topic_url: String,
+    access_token: Option<String>,
+    click_url: Option<String>,
+}
+
+impl NtfyNotifier {
+    /// Create a new NtfyNotifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_url` - Full URL of the topic to publish to
+    /// * `access_token` - Optional access token for protected topics
+    /// * `click_url` - Optional URL opened when the notification is tapped
+    ///
+    pub fn new(topic_url: String, access_token: Option<String>, click_url: Option<String>) -> Self {
+        Self {
+            topic_url,
+            access_token,
+            click_url,
+        }
+    }
+
+    /// Map a push priority to ntfy's 1 (min) to 5 (max) scale.
+    fn priority(priority: PushPriority) -> u8 {
+        match priority {
+            PushPriority::Low => 2,
+            PushPriority::Default => 3,
+            PushPriority::High => 4,
+            PushPriority::Urgent => 5,
+        }
+    }
+
+    /// Build the publish query parameters for an alert.
+    pub fn query_params(&self, alert: &Alert) -> Vec<(&'static str, String)> {
+        let mut tags = vec![alert.kind.as_str().to_string()];
+        if !alert.emoji.is_empty() {
+            tags.insert(0, emoji_tag(&alert.emoji));
+        }
+
+        let mut params = vec![
+            ("title", alert.title.clone()),
+            ("message", alert.plain_text()),
+            (
+                "priority",
+                Self::priority(alert.kind.push_priority()).to_string(),
+            ),
+            ("tags", tags.join(",")),
+        ];
+
+        if let Some(click_url) = &self.click_url {
+            params.push(("click", click_url.clone()));
+        }
+        if let Some(image) = &alert.image {
+            params.push(("filename", image.filename.clone()));
+        }
+
+        params
+    }
+}
+
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &str {
+        "ntfy"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<()> {
+        let client = reqwest::blocking::Client::new();
+
+        // With an attachment the request body is the file, otherwise the
+        // message is carried by the query parameter alone
+        let mut request = match &alert.image {
+            Some(image) => client.put(&self.topic_url).body(image.data.clone()),
+            None => client.post(&self.topic_url),
+        }
+        .query(&self.query_params(alert));
+
+        if let Some(token) = &self.access_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send()?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to send ntfy notification: HTTP {}",
+                response.status()
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+}
+
+/// Translate an alert emoji to the ntfy tag that renders as the same emoji.
+///
+/// Covers the default emoji of every alert kind, with or without the emoji
+/// variation selector. Unknown emojis are passed through and shown as a
+/// plain text tag.
+fn emoji_tag(emoji: &str) -> String {
+    match emoji.trim_end_matches('\u{fe0f}') {
+        "⚠" => "warning",
+        "🚨" => "rotating_light",
+        "ℹ" => "information_source",
+        "✅" => "white_check_mark",
+        "🖨" => "printer",
+        "📋" => "clipboard",
+        "📊" => "bar_chart",
+        _ => emoji,
+    }
+    .to_string()
+}
@@ -0,0 +1,303 @@
+use anyhow::Result;
+use log::debug;
+use reqwest::blocking::multipart;
+use serde_json::json;
+
+use super::{Alert, DeliveryProgress, Notifier};
+
+/// Default Slack Web API base URL.
+const DEFAULT_API_URL: &str = "https://slack.com/api";
+
+/// Maximum number of fields Slack allows in a single section block.
+const MAX_SECTION_FIELDS: usize = 10;
+
+/// Slack's file size limit.
+const MAX_UPLOAD_BYTES: usize = 1_000_000_000;
+
+/// Slack notifier posting Block Kit messages.
+///
+/// Messages are posted to an incoming webhook or, without one, with a bot
+/// token. Incoming webhooks cannot upload files, so images are only sent
+/// when a bot token is configured as well. The bot needs the `chat:write`
+/// and `files:write` scopes and must be a member of the channel.
+pub struct SlackNotifier {
+    api_url: String,
+    webhook_url: Option<String>,
+    bot: Option<SlackBot>,
+}
+
+/// Bot credentials used for the Web API.
+struct SlackBot {
+    token: String,
+    channel_id: String,
+}
+
+impl SlackNotifier {
+    /// Create a new SlackNotifier posting messages and images with a bot token.
+    ///
+    /// # Arguments
+    ///
+    /// * `bot_token` - Slack bot user OAuth token (`xoxb-...`)
+    /// * `channel_id` - ID of the channel alerts are posted to
+    ///
+    pub fn new(bot_token: String, channel_id: String) -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            webhook_url: None,
+            bot: Some(SlackBot {
+                token: bot_token,
+                channel_id,
+            }),
+        }
+    }
+
+    /// Create a new SlackNotifier posting messages to an incoming webhook.
+    ///
+    /// Images are not sent unless a bot is added with [`SlackNotifier::with_bot`].
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook_url` - Incoming webhook URL (`https://hooks.slack.com/services/...`)
+    ///
+    pub fn from_webhook(webhook_url: String) -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            webhook_url: Some(webhook_url),
+            bot: None,
+        }
+    }
+
+    /// Upload images with a bot token, posting them to the given channel.
+    pub fn with_bot(mut self, bot_token: String, channel_id: String) -> Self {
+        self.bot = Some(SlackBot {
+            token: bot_token,
+            channel_id,
+        });
+        self
+    }
+
+    /// Use a different Web API base URL.
+    pub fn with_api_url(mut self, api_url: String) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Build the Block Kit blocks for an alert.
+    ///
+    /// The layout mirrors the Discord embed: a header with the alert's emoji
+    /// and title, the description, the alert fields and a context footer.
+    pub fn build_blocks(alert: &Alert) -> serde_json::Value {
+        let mut blocks = vec![
+            json!({
+                "type": "header",
+                "text": {
+                    "type": "plain_text",
+                    "text": truncate(&alert.display_title(), 150),
+                    "emoji": true,
+                }
+            }),
+            json!({
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": escape_mrkdwn(&alert.description),
+                }
+            }),
+        ];
+
+        for fields in alert.fields.chunks(MAX_SECTION_FIELDS) {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|field| {
+                    json!({
+                        "type": "mrkdwn",
+                        "text": format!(
+                            "*{}:*\n{}",
+                            escape_mrkdwn(&field.name),
+                            escape_mrkdwn(&field.value)
+                        ),
+                    })
+                })
+                .collect();
+            blocks.push(json!({ "type": "section", "fields": fields }));
+        }
+
+        blocks.push(json!({
+            "type": "context",
+            "elements": [{
+                "type": "mrkdwn",
+                "text": format!("Print Guardian • {}", alert.timestamp.to_rfc3339()),
+            }]
+        }));
+
+        serde_json::Value::Array(blocks)
+    }
+
+    fn method_url(&self, method: &str) -> String {
+        format!("{}/{}", self.api_url, method)
+    }
+
+    /// Post the Block Kit message to the incoming webhook or the bot's channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The Slack API returns an error
+    fn post_message(&self, alert: &Alert) -> Result<()> {
+        let client = reqwest::blocking::Client::new();
+        let mut message = json!({
+            "text": alert.display_title(),
+            "blocks": Self::build_blocks(alert),
+        });
+
+        if let Some(webhook_url) = &self.webhook_url {
+            // Incoming webhooks answer with a plain text body such as `invalid_blocks`
+            let response = client.post(webhook_url).json(&message).send()?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(anyhow::anyhow!(
+                    "Slack webhook failed: HTTP {}: {}",
+                    status,
+                    response.text().unwrap_or_default()
+                ));
+            }
+            return Ok(());
+        }
+
+        let Some(bot) = &self.bot else {
+            return Err(anyhow::anyhow!(
+                "Slack needs an incoming webhook or a bot token"
+            ));
+        };
+        message["channel"] = json!(bot.channel_id);
+        let response = client
+            .post(self.method_url("chat.postMessage"))
+            .bearer_auth(&bot.token)
+            .json(&message)
+            .send()?;
+
+        check_response(response, "chat.postMessage").map(|_| ())
+    }
+
+    /// Upload an image and share it in the channel.
+    ///
+    /// Uses Slack's external upload flow: request an upload URL, send the
+    /// file contents to it and complete the upload into the channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any step of the upload fails.
+    fn upload_image(
+        &self,
+        bot: &SlackBot,
+        title: &str,
+        image_data: &[u8],
+        filename: &str,
+    ) -> Result<()> {
+        let client = reqwest::blocking::Client::new();
+
+        let response = client
+            .post(self.method_url("files.getUploadURLExternal"))
+            .bearer_auth(&bot.token)
+            .form(&[
+                ("filename", filename.to_string()),
+                ("length", image_data.len().to_string()),
+            ])
+            .send()?;
+        let upload = check_response(response, "files.getUploadURLExternal")?;
+
+        let upload_url = upload["upload_url"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Slack did not return an upload URL"))?;
+        let file_id = upload["file_id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Slack did not return a file ID"))?;
+
+        let form = multipart::Form::new().part(
+            "file",
+            multipart::Part::bytes(image_data.to_vec()).file_name(filename.to_string()),
+        );
+        let response = client.post(upload_url).multipart(form).send()?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to upload image to Slack: HTTP {}",
+                response.status()
+            ));
+        }
+
+        let response = client
+            .post(self.method_url("files.completeUploadExternal"))
+            .bearer_auth(&bot.token)
+            .json(&json!({
+                "files": [{ "id": file_id, "title": title }],
+                "channel_id": bot.channel_id,
+            }))
+            .send()?;
+        check_response(response, "files.completeUploadExternal").map(|_| ())
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<()> {
+        self.send_resumable(alert, &mut DeliveryProgress::default())
+    }
+
+    fn send_resumable(&self, alert: &Alert, progress: &mut DeliveryProgress) -> Result<()> {
+        progress.run("message", || self.post_message(alert))?;
+
+        match (&alert.image, &self.bot) {
+            (Some(image), Some(bot)) => progress.run("image", || {
+                self.upload_image(bot, &alert.title, &image.data, &image.filename)
+            })?,
+            (Some(_), None) => debug!("Slack has no bot token, not uploading the alert image"),
+            (None, _) => {}
+        }
+
+        Ok(())
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+}
+
+/// Check a Web API response and return its body.
+///
+/// Slack reports most errors with HTTP 200 and `"ok": false`.
+fn check_response(
+    response: reqwest::blocking::Response,
+    method: &str,
+) -> Result<serde_json::Value> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Slack {} failed: HTTP {}", method, status));
+    }
+
+    let body: serde_json::Value = response.json()?;
+    if body["ok"].as_bool() != Some(true) {
+        return Err(anyhow::anyhow!(
+            "Slack {} failed: {}",
+            method,
+            body["error"].as_str().unwrap_or("unknown error")
+        ));
+    }
+
+    Ok(body)
+}
+
+/// Escape the control characters of Slack's mrkdwn format.
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Truncate text to a maximum number of characters.
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
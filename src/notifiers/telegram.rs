@@ -0,0 +1,230 @@
+use anyhow::Result;
+use reqwest::blocking::multipart;
+use serde_json::json;
+
+use super::{Alert, AlertImage, DeliveryProgress, Notifier};
+
+/// Maximum length of a photo caption accepted by the Telegram Bot API.
+const MAX_CAPTION_LENGTH: usize = 1024;
+
+/// Largest photo accepted by the Telegram Bot API's `sendPhoto`.
+const MAX_UPLOAD_BYTES: usize = 10_000_000;
+
+/// Default Telegram Bot API base URL.
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+
+/// Telegram Bot API notifier sending Markdown formatted messages.
+///
+/// Alerts with an image are sent with `sendPhoto` (or `sendAnimation` for
+/// animated GIF clips) using the formatted text as caption, all other alerts
+/// with `sendMessage`. Every alert is delivered to each configured chat; a
+/// retry only sends to the chats that failed.
+pub struct TelegramNotifier {
+    api_url: String,
+    bot_token: String,
+    chat_ids: Vec<String>,
+}
+
+impl TelegramNotifier {
+    /// Create a new TelegramNotifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `bot_token` - Token of the bot as issued by @BotFather
+    /// * `chat_ids` - IDs of the chats (users, groups or channels) to send alerts to
+    ///
+    pub fn new(bot_token: String, chat_ids: Vec<String>) -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            bot_token,
+            chat_ids,
+        }
+    }
+
+    /// Use a different Bot API base URL, e.g. a self-hosted Bot API server.
+    pub fn with_api_url(mut self, api_url: String) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Format an alert as a Telegram Markdown message.
+    ///
+    /// The layout mirrors the Discord embed: a bold title with the alert's
+    /// emoji, the description, and one bold-labelled line per field.
+    pub fn format_message(alert: &Alert) -> String {
+        let mut text = format!(
+            "*{}*\n\n{}",
+            escape_markdown(&alert.display_title()),
+            escape_markdown(&alert.description)
+        );
+
+        if !alert.fields.is_empty() {
+            text.push('\n');
+            for field in &alert.fields {
+                text.push_str(&format!(
+                    "\n*{}:* {}",
+                    escape_markdown(&field.name),
+                    escape_markdown(&field.value)
+                ));
+            }
+        }
+
+        text.push_str("\n\n_Print Guardian_");
+        text
+    }
+
+    fn method_url(&self, method: &str) -> String {
+        format!("{}/bot{}/{}", self.api_url, self.bot_token, method)
+    }
+
+    /// Send a text message to a chat.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The Telegram API returns an error
+    fn send_message(&self, chat_id: &str, text: &str) -> Result<()> {
+        let client = reqwest::blocking::Client::new();
+        let response = client
+            .post(self.method_url("sendMessage"))
+            .json(&json!({
+                "chat_id": chat_id,
+                "text": text,
+                "parse_mode": "Markdown",
+            }))
+            .send()?;
+
+        check_response(response, "sendMessage")
+    }
+
+    /// Send a photo, or an animation for GIF clips, with a caption to a chat.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The Telegram API returns an error
+    fn send_photo(&self, chat_id: &str, caption: &str, image: &AlertImage) -> Result<()> {
+        let client = reqwest::blocking::Client::new();
+        let (method, field) = if image.mime_type == "image/gif" {
+            ("sendAnimation", "animation")
+        } else {
+            ("sendPhoto", "photo")
+        };
+
+        let form = multipart::Form::new()
+            .text("chat_id", chat_id.to_string())
+            .text("caption", caption.to_string())
+            .text("parse_mode", "Markdown")
+            .part(
+                field,
+                multipart::Part::bytes(image.data.clone())
+                    .file_name(image.filename.clone())
+                    .mime_str(&image.mime_type)?,
+            );
+
+        let response = client
+            .post(self.method_url(method))
+            .multipart(form)
+            .send()?;
+
+        check_response(response, method)
+    }
+
+    /// Deliver an alert to a single chat, skipping steps that already completed.
+    fn send_to_chat(
+        &self,
+        chat_id: &str,
+        alert: &Alert,
+        text: &str,
+        progress: &mut DeliveryProgress,
+    ) -> Result<()> {
+        let photo_step = format!("{}/photo", chat_id);
+        let message_step = format!("{}/message", chat_id);
+
+        let Some(image) = &alert.image else {
+            return progress.run(&message_step, || self.send_message(chat_id, text));
+        };
+
+        if text.chars().count() <= MAX_CAPTION_LENGTH {
+            return progress.run(&photo_step, || self.send_photo(chat_id, text, image));
+        }
+
+        // Captions are limited, so send the photo with the title only and
+        // the full text as a separate message
+        let caption = format!("*{}*", escape_markdown(&alert.display_title()));
+        progress.run(&photo_step, || self.send_photo(chat_id, &caption, image))?;
+        progress.run(&message_step, || self.send_message(chat_id, text))
+    }
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<()> {
+        self.send_resumable(alert, &mut DeliveryProgress::default())
+    }
+
+    fn send_resumable(&self, alert: &Alert, progress: &mut DeliveryProgress) -> Result<()> {
+        let text = Self::format_message(alert);
+
+        let failures: Vec<String> = self
+            .chat_ids
+            .iter()
+            .filter_map(|chat_id| {
+                self.send_to_chat(chat_id, alert, &text, progress)
+                    .err()
+                    .map(|e| format!("chat {}: {:#}", chat_id, e))
+            })
+            .collect();
+
+        if !failures.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Failed to send Telegram alert: {}",
+                failures.join("; ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn max_upload_bytes(&self) -> Option<usize> {
+        Some(MAX_UPLOAD_BYTES)
+    }
+}
+
+/// Check a Bot API response, extracting Telegram's error description on failure.
+fn check_response(response: reqwest::blocking::Response, method: &str) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let description = response
+        .json::<serde_json::Value>()
+        .ok()
+        .and_then(|body| body["description"].as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    Err(anyhow::anyhow!(
+        "Telegram {} failed: HTTP {} {}",
+        method,
+        status,
+        description
+    ))
+}
+
+/// Escape the characters that have a meaning in Telegram's legacy Markdown mode.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '_' | '*' | '`' | '[') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
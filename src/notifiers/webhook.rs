@@ -0,0 +1,327 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use handlebars::{Handlebars, handlebars_helper};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{Alert, Notifier};
+use crate::config::constants;
+
+/// Header carrying the HMAC-SHA256 signature of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Print-Guardian-Signature";
+
+/// Settings for the generic webhook notifier.
+///
+/// The method, body and header values are Handlebars templates rendered
+/// over the alert context built by [`WebhookNotifier::context`].
+#[derive(Debug, Clone)]
+pub struct WebhookSettings {
+    pub url: String,
+    /// Template for the HTTP method, e.g. `POST` or `{{#if image_base64}}PUT{{else}}POST{{/if}}`.
+    pub method_template: String,
+    /// Template for the request body, `None` sends the whole context as JSON.
+    pub body_template: Option<String>,
+    /// Header name to value template.
+    pub header_templates: BTreeMap<String, String>,
+    /// Secret used to sign the body, `None` disables signing.
+    pub secret: Option<String>,
+    /// Name of the monitored printer, available as `printer`.
+    pub printer_name: String,
+    /// Externally reachable base URL of the HTTP server, used for `image_url`.
+    pub public_url: Option<String>,
+}
+
+/// A webhook request rendered from the templates.
+#[derive(Debug, Clone)]
+pub struct RenderedRequest {
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Generic webhook notifier with templated requests.
+///
+/// Body templates escape interpolated values for JSON strings, so
+/// `"label": "{{label}}"` is always valid JSON. Use triple braces with the
+/// `json` helper to insert objects, e.g. `"bbox": {{{json bbox}}}`.
+///
+/// When a secret is configured the rendered body is signed with HMAC-SHA256
+/// and the signature is sent as `X-Print-Guardian-Signature: sha256=<hex>`.
+///
+/// With an image directory, the image of every alert is kept there and
+/// `image_url` links to it as `/alerts/<file>` on the HTTP server, so the
+/// link keeps showing the frame of that alert. With a signing key (the API
+/// token), the link carries a signature that grants access to that image only.
+pub struct WebhookNotifier {
+    settings: WebhookSettings,
+    body_templates: Handlebars<'static>,
+    text_templates: Handlebars<'static>,
+    image_dir: Option<PathBuf>,
+    image_signing_key: Option<String>,
+}
+
+handlebars_helper!(json_helper: |value: Json| serde_json::to_string(value).unwrap_or_default());
+
+impl WebhookNotifier {
+    /// Create a new WebhookNotifier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the templates cannot be parsed.
+    pub fn new(settings: WebhookSettings) -> Result<Self> {
+        let mut body_templates = Handlebars::new();
+        body_templates.set_strict_mode(false);
+        body_templates.register_escape_fn(escape_json);
+        body_templates.register_helper("json", Box::new(json_helper));
+        if let Some(template) = &settings.body_template {
+            body_templates
+                .register_template_string("body", template)
+                .context("Invalid webhook body template")?;
+        }
+
+        let mut text_templates = Handlebars::new();
+        text_templates.register_escape_fn(handlebars::no_escape);
+        text_templates.register_helper("json", Box::new(json_helper));
+        text_templates
+            .register_template_string("method", &settings.method_template)
+            .context("Invalid webhook method template")?;
+        for (name, template) in &settings.header_templates {
+            text_templates
+                .register_template_string(&header_template_name(name), template)
+                .with_context(|| format!("Invalid webhook header template for {}", name))?;
+        }
+
+        Ok(Self {
+            settings,
+            body_templates,
+            text_templates,
+            image_dir: None,
+            image_signing_key: None,
+        })
+    }
+
+    /// Keep alert images in `dir`, served by the HTTP server under `/alerts/`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn with_image_dir(mut self, dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create alert image directory {}", dir.display()))?;
+        self.image_dir = Some(dir);
+        Ok(self)
+    }
+
+    /// Sign image links with the given key, usually the API token.
+    pub fn with_image_signing_key(mut self, key: Option<String>) -> Self {
+        self.image_signing_key = key;
+        self
+    }
+
+    /// Get the file name an alert's image is stored under, `None` without an image.
+    ///
+    /// The name is derived from the alert's time and image, so retries of
+    /// the same alert link to the same file.
+    pub fn image_file_name(alert: &Alert) -> Option<String> {
+        let image = alert.image.as_ref()?;
+        let extension = image
+            .filename
+            .rsplit_once('.')
+            .map_or("jpg", |(_, extension)| extension);
+        let digest = hex::encode(Sha256::digest(&image.data));
+        Some(format!(
+            "{:013}-{}.{}",
+            alert.timestamp.timestamp_millis(),
+            &digest[..16],
+            extension
+        ))
+    }
+
+    /// Compute the signature granting access to a stored alert image.
+    pub fn image_signature(key: &str, file_name: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key size");
+        mac.update(file_name.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// Get the link to an alert's stored image.
+    fn image_url(&self, alert: &Alert) -> Option<String> {
+        let public_url = self.settings.public_url.as_ref()?;
+        self.image_dir.as_ref()?;
+        let file_name = Self::image_file_name(alert)?;
+        let url = format!("{}/alerts/{}", public_url.trim_end_matches('/'), file_name);
+        Some(match &self.image_signing_key {
+            Some(key) => format!("{}?sig={}", url, Self::image_signature(key, &file_name)),
+            None => url,
+        })
+    }
+
+    /// Store an alert's image so `image_url` resolves, keeping only the
+    /// most recent [`constants::ALERT_IMAGE_RETENTION`] images.
+    fn store_image(&self, alert: &Alert) -> Result<()> {
+        let (Some(dir), Some(image), Some(file_name)) =
+            (&self.image_dir, &alert.image, Self::image_file_name(alert))
+        else {
+            return Ok(());
+        };
+
+        let path = dir.join(&file_name);
+        if !path.exists() {
+            let tmp_path = path.with_extension("tmp");
+            fs::write(&tmp_path, &image.data)
+                .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+            fs::rename(&tmp_path, &path)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        let mut stored: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext != "tmp"))
+            .collect();
+        if stored.len() > constants::ALERT_IMAGE_RETENTION {
+            stored.sort();
+            for old in &stored[..stored.len() - constants::ALERT_IMAGE_RETENTION] {
+                let _ = fs::remove_file(old);
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the template context for an alert.
+    pub fn context(&self, alert: &Alert) -> serde_json::Value {
+        let fields: serde_json::Map<_, _> = alert
+            .fields
+            .iter()
+            .map(|field| (field.name.clone(), json!(field.value)))
+            .collect();
+
+        let detection = alert.detection.as_ref();
+        json!({
+            "printer": self.settings.printer_name,
+            "kind": alert.kind.as_str(),
+            "severity": alert.severity.as_str(),
+            "title": alert.title,
+            "description": alert.description,
+            "emoji": alert.emoji,
+            "color": format!("#{:06X}", alert.color),
+            "timestamp": alert.timestamp.to_rfc3339(),
+            "fields": fields,
+            "label": detection.map(|d| d.label.clone()),
+            "confidence": detection.map(|d| d.confidence),
+            "bbox": detection.map(|d| json!({ "x": d.x, "y": d.y, "w": d.w, "h": d.h })),
+            "image_base64": alert
+                .image
+                .as_ref()
+                .map(|image| base64::engine::general_purpose::STANDARD.encode(&image.data)),
+            "image_mime_type": alert.image.as_ref().map(|image| image.mime_type.clone()),
+            "image_filename": alert.image.as_ref().map(|image| image.filename.clone()),
+            "image_url": self.image_url(alert),
+        })
+    }
+
+    /// Render the method, headers and body for an alert.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering a template fails.
+    pub fn render(&self, alert: &Alert) -> Result<RenderedRequest> {
+        let context = self.context(alert);
+
+        let method = self
+            .text_templates
+            .render("method", &context)?
+            .trim()
+            .to_uppercase();
+
+        let headers = self
+            .settings
+            .header_templates
+            .keys()
+            .map(|name| {
+                let value = self
+                    .text_templates
+                    .render(&header_template_name(name), &context)?;
+                Ok((name.clone(), value.trim().to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let body = if self.settings.body_template.is_some() {
+            self.body_templates.render("body", &context)?
+        } else {
+            context.to_string()
+        };
+
+        Ok(RenderedRequest {
+            method,
+            headers,
+            body,
+        })
+    }
+
+    /// Compute the signature header value for a body.
+    pub fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key size");
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<()> {
+        self.store_image(alert)?;
+        let RenderedRequest {
+            method,
+            headers,
+            body,
+        } = self.render(alert)?;
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .with_context(|| format!("Invalid webhook method '{}'", method))?;
+
+        let client = reqwest::blocking::Client::new();
+        let mut request = client.request(method, &self.settings.url);
+
+        let has_content_type = headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("Content-Type"));
+        if !has_content_type {
+            request = request.header("Content-Type", "application/json");
+        }
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(secret) = &self.settings.secret {
+            request = request.header(SIGNATURE_HEADER, Self::sign(secret, body.as_bytes()));
+        }
+
+        let response = request.body(body).send()?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to send webhook: HTTP {}",
+                response.status()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn header_template_name(name: &str) -> String {
+    format!("header:{}", name)
+}
+
+/// Escape a value for use inside a JSON string literal.
+fn escape_json(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
@@ -1,12 +1,116 @@
 #![allow(dead_code)]
-use anyhow::Result;
+use log::{debug, info, warn};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::constants;
+use crate::error::{ErrorChain, PrinterError};
+use crate::metrics;
+use crate::moonraker::SharedPrinterState;
+use crate::moonraker_auth::{self, MoonrakerAuth, MoonrakerCredentials};
+use crate::printer_status::{PrintState, PrinterStatus};
+use crate::state;
+
+/// Stronger action taken when the printer does not pause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PauseEscalation {
+    /// Only report that the print kept going.
+    #[default]
+    None,
+    /// Cancel the print job.
+    Cancel,
+    /// Trigger an `M112` emergency stop, shutting Klipper down.
+    EmergencyStop,
+}
+
+impl FromStr for PauseEscalation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(PauseEscalation::None),
+            "cancel" => Ok(PauseEscalation::Cancel),
+            "emergency_stop" | "m112" => Ok(PauseEscalation::EmergencyStop),
+            other => Err(format!(
+                "unknown escalation '{}' (expected none, cancel or emergency_stop)",
+                other
+            )),
+        }
+    }
+}
+
+/// How a pause is confirmed before it is considered effective.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PauseVerification {
+    /// Time to wait for `print_stats.state` to become `paused` after each
+    /// pause request, zero to trust the API response.
+    pub timeout: Duration,
+    /// Number of pause requests sent before escalating.
+    pub attempts: u32,
+    /// Action taken when the printer still has not paused.
+    pub escalation: PauseEscalation,
+}
+
+impl Default for PauseVerification {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(constants::DEFAULT_PAUSE_VERIFY_SECONDS),
+            attempts: constants::DEFAULT_PAUSE_ATTEMPTS,
+            escalation: PauseEscalation::None,
+        }
+    }
+}
+
+/// Result of pausing a print with [`PrinterService::pause_print_verified`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseOutcome {
+    /// The printer reported `paused` after the given number of pause requests.
+    Paused { attempts: u32 },
+    /// The print ended without pausing, e.g. because Klipper shut down.
+    Stopped { state: PrintState },
+    /// The printer did not pause, so the print was cancelled.
+    Cancelled,
+    /// The printer did not pause, so an emergency stop was triggered.
+    EmergencyStopped,
+    /// The printer did not pause and is still printing.
+    StillPrinting,
+}
+
+impl PauseOutcome {
+    /// Get the short identifier of the outcome, used in logs and templates.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PauseOutcome::Paused { .. } => "paused",
+            PauseOutcome::Stopped { .. } => "stopped",
+            PauseOutcome::Cancelled => "cancelled",
+            PauseOutcome::EmergencyStopped => "emergency_stopped",
+            PauseOutcome::StillPrinting => "still_printing",
+        }
+    }
+
+    /// Check whether the print no longer runs.
+    pub fn is_halted(&self) -> bool {
+        *self != PauseOutcome::StillPrinting
+    }
+}
 
 /// Printer control service for interacting with Moonraker API.
 ///
 /// This service provides methods to control 3D printer operations through
 /// the Moonraker API, such as pausing prints when failures are detected.
+/// With a live state from the Moonraker WebSocket, the printer status is
+/// read from memory and HTTP is only used while the WebSocket is down.
+/// Requests are authenticated with [`MoonrakerAuth`] when Moonraker's
+/// `[authorization]` component requires it.
+///
+/// Failures are reported as [`PrinterError`]s, so callers can tell
+/// rejected credentials from an unreachable or restarting Moonraker.
+#[derive(Debug, Clone)]
 pub struct PrinterService {
     pub api_url: String,
+    live_state: Option<SharedPrinterState>,
+    auth: MoonrakerAuth,
 }
 
 impl PrinterService {
@@ -17,7 +121,24 @@ impl PrinterService {
     /// * `api_url` - Base URL for the Moonraker API (e.g., "http://printer.local:7125")
     ///
     pub fn new(api_url: String) -> Self {
-        Self { api_url }
+        Self {
+            auth: MoonrakerAuth::new(&api_url, MoonrakerCredentials::None),
+            api_url,
+            live_state: None,
+        }
+    }
+
+    /// Authenticate requests, sharing the tokens with other users of `auth`.
+    pub fn with_auth(mut self, auth: MoonrakerAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Read the printer status from a state kept current by a
+    /// [`MoonrakerSocket`](crate::moonraker::MoonrakerSocket).
+    pub fn with_live_state(mut self, live_state: SharedPrinterState) -> Self {
+        self.live_state = Some(live_state);
+        self
     }
 
     /// Pause the current print job.
@@ -33,20 +154,101 @@ impl PrinterService {
     /// - The Moonraker API returns an error status
     /// - The printer is not currently printing
     ///
-    pub fn pause_print(&self) -> Result<()> {
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!("{}/printer/print/pause", self.api_url))
-            .send()?;
+    pub fn pause_print(&self) -> Result<(), PrinterError> {
+        self.post_action("printer/print/pause")
+    }
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to pause print: HTTP {}",
-                response.status()
-            ));
+    /// Pause the current print job and confirm that it paused.
+    ///
+    /// Klipper acknowledges a pause request even when it cannot act on it,
+    /// e.g. without a `PAUSE` macro or during a long move. After each
+    /// request, the printer state is polled until `print_stats.state` is
+    /// `paused` or `verification.timeout` has passed. The request is
+    /// repeated up to `verification.attempts` times, then the print is
+    /// cancelled or emergency stopped if configured. Failed requests are
+    /// logged and count as attempts that did not take effect, without
+    /// waiting for the printer. An emergency stop is only reported once
+    /// Klippy confirms that it shut down.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PrinterError::AuthenticationFailed`] if Moonraker rejects
+    /// the credentials. With a zero timeout the pause is not verified and any
+    /// failed request is returned.
+    pub fn pause_print_verified(
+        &self,
+        verification: &PauseVerification,
+    ) -> Result<PauseOutcome, PrinterError> {
+        if verification.timeout.is_zero() {
+            self.pause_print()?;
+            return Ok(PauseOutcome::Paused { attempts: 1 });
         }
 
-        Ok(())
+        for attempt in 1..=verification.attempts.max(1) {
+            match self.pause_print() {
+                Ok(()) => {}
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(e) => {
+                    warn!("Pause request {} failed: {}", attempt, ErrorChain(&e));
+                    continue;
+                }
+            }
+            match self
+                .wait_for_state(verification.timeout, |state| state != PrintState::Printing)?
+            {
+                Some(PrintState::Paused) => return Ok(PauseOutcome::Paused { attempts: attempt }),
+                Some(state) => return Ok(PauseOutcome::Stopped { state }),
+                None => warn!(
+                    "Printer did not pause within {}s of pause request {}",
+                    verification.timeout.as_secs_f64(),
+                    attempt
+                ),
+            }
+        }
+
+        match verification.escalation {
+            PauseEscalation::None => Ok(PauseOutcome::StillPrinting),
+            PauseEscalation::Cancel => {
+                warn!("Printer ignored the pause, cancelling the print");
+                match self.cancel_print() {
+                    Ok(()) => {}
+                    Err(e) if e.is_fatal() => return Err(e),
+                    Err(e) => warn!("Cancel request failed: {}", ErrorChain(&e)),
+                }
+                let cancelled =
+                    self.wait_for_state(verification.timeout, |state| !state.is_active())?;
+                Ok(match cancelled {
+                    Some(_) => PauseOutcome::Cancelled,
+                    None => PauseOutcome::StillPrinting,
+                })
+            }
+            PauseEscalation::EmergencyStop => {
+                warn!("Printer ignored the pause, triggering an emergency stop");
+                match self.emergency_stop() {
+                    Ok(()) => {}
+                    Err(e) if e.is_fatal() => return Err(e),
+                    Err(e) => {
+                        warn!("Emergency stop request failed: {}", ErrorChain(&e));
+                        return Ok(PauseOutcome::StillPrinting);
+                    }
+                }
+                let stopped = self.wait_for_status(verification.timeout, |status| {
+                    status.webhooks.state == "shutdown" || !status.state().is_active()
+                })?;
+                Ok(match stopped {
+                    Some(status) if status.webhooks.state == "shutdown" => {
+                        PauseOutcome::EmergencyStopped
+                    }
+                    Some(status) => PauseOutcome::Stopped {
+                        state: status.state(),
+                    },
+                    None => {
+                        warn!("Klippy did not report a shutdown after the emergency stop");
+                        PauseOutcome::StillPrinting
+                    }
+                })
+            }
+        }
     }
 
     /// Resume the current print job.
@@ -59,20 +261,8 @@ impl PrinterService {
     /// - The HTTP request fails
     /// - The Moonraker API returns an error status
     /// - The printer is not currently paused
-    pub fn resume_print(&self) -> Result<()> {
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!("{}/printer/print/resume", self.api_url))
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to resume print: HTTP {}",
-                response.status()
-            ));
-        }
-
-        Ok(())
+    pub fn resume_print(&self) -> Result<(), PrinterError> {
+        self.post_action("printer/print/resume")
     }
 
     /// Cancel the current print job.
@@ -86,54 +276,140 @@ impl PrinterService {
     /// - The HTTP request fails
     /// - The Moonraker API returns an error status
     /// - There is no active print job to cancel
-    pub fn cancel_print(&self) -> Result<()> {
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .post(format!("{}/printer/print/cancel", self.api_url))
-            .send()?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to cancel print: HTTP {}",
-                response.status()
-            ));
-        }
+    pub fn cancel_print(&self) -> Result<(), PrinterError> {
+        self.post_action("printer/print/cancel")
+    }
 
-        Ok(())
+    /// Trigger an emergency stop.
+    ///
+    /// Equivalent to `M112`: Klipper halts the printer immediately and
+    /// must be restarted before it can print again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP request fails
+    /// - The Moonraker API returns an error status
+    pub fn emergency_stop(&self) -> Result<(), PrinterError> {
+        self.post_action("printer/emergency_stop")
     }
 
     /// Get the current printer status.
     ///
     /// Retrieves the current status of the printer including print state,
-    /// temperatures, and other relevant information.
-    ///
-    /// # Returns
-    ///
-    /// Returns a JSON value containing the printer status information.
+    /// print progress, heater temperatures and Klippy's state.
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - The HTTP request fails
     /// - The Moonraker API returns an error status
-    /// - JSON parsing fails
-    pub fn get_printer_status(&self) -> Result<serde_json::Value> {
-        let client = reqwest::blocking::Client::new();
-        let response = client
-            .get(format!(
-                "{}/printer/objects/query?webhooks&print_stats",
-                self.api_url
-            ))
-            .send()?;
+    /// - The response cannot be parsed
+    pub fn get_printer_status(&self) -> Result<PrinterStatus, PrinterError> {
+        if let Some(live_state) = &self.live_state {
+            let live_state = state::lock(live_state);
+            if live_state.is_ready() {
+                return live_state
+                    .status()
+                    .map_err(|e| moonraker_auth::invalid_response("notify_status_update", e));
+            }
+        }
+
+        let result = self.query_printer_status();
+        if result.is_err() {
+            metrics::get()
+                .moonraker_errors
+                .with_label_values(&["objects/query"])
+                .inc();
+        }
+        result
+    }
+
+    fn query_printer_status(&self) -> Result<PrinterStatus, PrinterError> {
+        let endpoint = "printer/objects/query";
+        let url = format!(
+            "{}/{}?webhooks&print_stats&virtual_sdcard&toolhead&heaters&extruder&heater_bed",
+            self.api_url, endpoint
+        );
+        let response = self.auth.send(|client| client.get(&url))?;
 
         if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to get printer status: HTTP {}",
-                response.status()
-            ));
+            return Err(moonraker_auth::api_error(endpoint, response));
         }
 
-        let status: serde_json::Value = response.json()?;
-        Ok(status)
+        let response = moonraker_auth::json_body(endpoint, response)?;
+        PrinterStatus::from_query_response(&response)
+            .map_err(|e| moonraker_auth::invalid_response(endpoint, e))
+    }
+
+    /// Poll the printer state until `done` accepts it or `timeout` has passed.
+    ///
+    /// Returns the accepted state, or `None` on timeout. Failed status
+    /// queries are retried, unless the credentials are rejected.
+    fn wait_for_state(
+        &self,
+        timeout: Duration,
+        done: impl Fn(PrintState) -> bool,
+    ) -> Result<Option<PrintState>, PrinterError> {
+        Ok(self
+            .wait_for_status(timeout, |status| done(status.state()))?
+            .map(|status| status.state()))
+    }
+
+    /// Poll the printer status until `done` accepts it or `timeout` has passed.
+    ///
+    /// Returns the accepted status, or `None` on timeout. Failed status
+    /// queries are retried, unless the credentials are rejected.
+    fn wait_for_status(
+        &self,
+        timeout: Duration,
+        done: impl Fn(&PrinterStatus) -> bool,
+    ) -> Result<Option<PrinterStatus>, PrinterError> {
+        let deadline = Instant::now() + timeout;
+        let poll_interval = Duration::from_millis(constants::PAUSE_VERIFY_POLL_INTERVAL_MILLIS);
+
+        loop {
+            match self.get_printer_status() {
+                Ok(status) if done(&status) => {
+                    info!("Printer state is now {}", status.state());
+                    return Ok(Some(status));
+                }
+                Ok(_) => {}
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(e) => debug!("Failed to check printer state: {}", ErrorChain(&e)),
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            thread::sleep(poll_interval.min(remaining));
+        }
+    }
+
+    /// Send a print control request (pause, resume, cancel, emergency stop)
+    /// to Moonraker.
+    ///
+    /// Failed requests are counted in the Moonraker error metrics.
+    fn post_action(&self, endpoint: &str) -> Result<(), PrinterError> {
+        let result = self.send_action(endpoint);
+        if result.is_err() {
+            metrics::get()
+                .moonraker_errors
+                .with_label_values(&[endpoint.trim_start_matches("printer/")])
+                .inc();
+        }
+        result
+    }
+
+    fn send_action(&self, endpoint: &str) -> Result<(), PrinterError> {
+        let url = format!("{}/{}", self.api_url, endpoint);
+        let response = self.auth.send(|client| client.post(&url))?;
+
+        if !response.status().is_success() {
+            return Err(moonraker_auth::api_error(endpoint, response));
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,231 @@
+#![allow(dead_code)]
+use serde::Deserialize;
+use serde::de::Error as _;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// State of the print job, from Klipper's `print_stats.state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrintState {
+    Standby,
+    Printing,
+    Paused,
+    Complete,
+    Cancelled,
+    Error,
+    /// Any state this version does not know about.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl PrintState {
+    /// Get the name of the state as reported by Klipper.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrintState::Standby => "standby",
+            PrintState::Printing => "printing",
+            PrintState::Paused => "paused",
+            PrintState::Complete => "complete",
+            PrintState::Cancelled => "cancelled",
+            PrintState::Error => "error",
+            PrintState::Unknown => "unknown",
+        }
+    }
+
+    /// Check whether a print job is in progress, printing or paused.
+    pub fn is_active(&self) -> bool {
+        matches!(self, PrintState::Printing | PrintState::Paused)
+    }
+}
+
+impl fmt::Display for PrintState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Layer information reported by the slicer through `SET_PRINT_STATS_INFO`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PrintStatsInfo {
+    pub current_layer: Option<u32>,
+    pub total_layer: Option<u32>,
+}
+
+/// Klipper's `print_stats` object.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PrintStats {
+    pub state: PrintState,
+    /// File being printed, empty when no file is loaded.
+    pub filename: String,
+    /// Seconds since the print started, including pauses.
+    pub total_duration: f64,
+    /// Seconds spent actually printing.
+    pub print_duration: f64,
+    /// Filament used in millimeters.
+    pub filament_used: f64,
+    /// Error message of the last print, if any.
+    pub message: String,
+    pub info: PrintStatsInfo,
+}
+
+impl PrintStats {
+    /// Get the file being printed, `None` when no file is loaded.
+    pub fn filename(&self) -> Option<&str> {
+        (!self.filename.is_empty()).then_some(self.filename.as_str())
+    }
+}
+
+/// Klipper's `virtual_sdcard` object.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VirtualSdcard {
+    /// Progress through the file from 0.0 to 1.0.
+    pub progress: f64,
+    pub is_active: bool,
+    pub file_position: u64,
+}
+
+/// Klipper's `webhooks` object, the state of the Klippy host.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Webhooks {
+    /// `ready`, `startup`, `shutdown` or `error`.
+    pub state: String,
+    /// Human readable description of the state, e.g. the shutdown reason.
+    pub state_message: String,
+}
+
+/// Klipper's `toolhead` object.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Toolhead {
+    /// Axes that are homed, e.g. `xyz`.
+    pub homed_axes: String,
+    /// Commanded position as `[x, y, z, e]`.
+    pub position: Vec<f64>,
+    pub max_velocity: f64,
+}
+
+/// A heater such as `extruder`, `heater_bed` or `heater_generic chamber`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Heater {
+    /// Current temperature in °C.
+    pub temperature: f64,
+    /// Target temperature in °C, 0 when off.
+    pub target: f64,
+    /// Heater power from 0.0 to 1.0.
+    pub power: f64,
+}
+
+/// Printer objects listed in the `heaters` object.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Heaters {
+    available_heaters: Vec<String>,
+}
+
+/// Printer objects as found in the `status` of Moonraker responses.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct StatusObjects {
+    webhooks: Webhooks,
+    print_stats: PrintStats,
+    virtual_sdcard: VirtualSdcard,
+    toolhead: Option<Toolhead>,
+    heaters: Option<Heaters>,
+}
+
+/// Typed status of the printer, built from Moonraker's printer objects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrinterStatus {
+    pub webhooks: Webhooks,
+    pub print_stats: PrintStats,
+    pub virtual_sdcard: VirtualSdcard,
+    /// Toolhead state, `None` if it was not queried.
+    pub toolhead: Option<Toolhead>,
+    /// Heaters by object name.
+    pub heaters: BTreeMap<String, Heater>,
+}
+
+impl PrinterStatus {
+    /// Build the status from a `/printer/objects/query` response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response has no `result.status` object or an
+    /// object has an unexpected shape.
+    pub fn from_query_response(response: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        let status = &response["result"]["status"];
+        if !status.is_object() {
+            return Err(serde_json::Error::missing_field("status"));
+        }
+        Self::from_status(status)
+    }
+
+    /// Build the status from a map of printer objects, as found in the
+    /// `status` of query and subscription responses.
+    ///
+    /// Heaters are taken from the objects listed in `heaters.available_heaters`,
+    /// falling back to `extruder` and `heater_bed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an object has an unexpected shape.
+    pub fn from_status(status: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        let objects = StatusObjects::deserialize(status)?;
+
+        let heater_names = objects
+            .heaters
+            .map(|heaters| heaters.available_heaters)
+            .unwrap_or_else(|| vec!["extruder".to_string(), "heater_bed".to_string()]);
+        let mut heaters = BTreeMap::new();
+        for name in heater_names {
+            if let Some(heater) = status.get(&name) {
+                let heater = Heater::deserialize(heater).map_err(|e| {
+                    serde_json::Error::custom(format!("invalid heater '{}': {}", name, e))
+                })?;
+                heaters.insert(name, heater);
+            }
+        }
+
+        Ok(Self {
+            webhooks: objects.webhooks,
+            print_stats: objects.print_stats,
+            virtual_sdcard: objects.virtual_sdcard,
+            toolhead: objects.toolhead,
+            heaters,
+        })
+    }
+
+    /// Get the state of the print job.
+    pub fn state(&self) -> PrintState {
+        self.print_stats.state
+    }
+
+    /// Get the progress of the print from 0.0 to 1.0.
+    pub fn progress(&self) -> f64 {
+        self.virtual_sdcard.progress
+    }
+
+    /// Get the current and total layer, when the slicer reports them.
+    pub fn layer(&self) -> Option<(u32, u32)> {
+        let info = &self.print_stats.info;
+        Some((info.current_layer?, info.total_layer?))
+    }
+
+    /// Get a heater by object name.
+    pub fn heater(&self, name: &str) -> Option<&Heater> {
+        self.heaters.get(name)
+    }
+
+    /// Get the message describing Klippy's state, `None` when empty.
+    pub fn klippy_message(&self) -> Option<&str> {
+        let message = self.webhooks.state_message.trim();
+        (!message.is_empty()).then_some(message)
+    }
+}
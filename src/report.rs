@@ -0,0 +1,402 @@
+#![allow(dead_code)]
+use anyhow::{Context, Result};
+use image::{DynamicImage, Rgb, RgbImage};
+use imageproc::drawing::{draw_filled_rect_mut, draw_line_segment_mut};
+use imageproc::rect::Rect;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::constants;
+use crate::notifiers::PrintJob;
+
+/// One of the most suspicious frames of a print.
+#[derive(Debug, Clone)]
+pub struct SuspiciousFrame {
+    /// Failure score of the frame (0.0 to 1.0).
+    pub score: f32,
+    /// Seconds since the start of the print.
+    pub offset_seconds: f32,
+    /// Annotated JPEG frame.
+    pub frame: Vec<u8>,
+}
+
+/// Monitoring history of a single print, summarized when the print ends.
+///
+/// The monitoring loop feeds every analysed frame, detection, alert, pause
+/// and camera outage of the current print job into the report. When the job
+/// finishes, the report is rendered to a chart of the failure score over time
+/// and a mosaic of the most suspicious frames, sent as a summary alert and
+/// saved to the output directory.
+pub struct PrintReport {
+    job: PrintJob,
+    filename: String,
+    started_at: chrono::DateTime<chrono::Local>,
+    started: Instant,
+    finished_after: Option<Duration>,
+    final_state: Option<String>,
+    frames_analysed: u64,
+    detections: u64,
+    alerts_sent: u32,
+    pauses: u32,
+    /// Failure score per analysed frame as (seconds since start, score).
+    scores: Vec<(f32, f32)>,
+    /// Seconds since start of frames with significant detections.
+    detection_offsets: Vec<f32>,
+    suspicious_frames: Vec<SuspiciousFrame>,
+    camera_down_since: Option<Instant>,
+    camera_downtime: Duration,
+}
+
+impl PrintReport {
+    /// Start the report of a print job.
+    pub fn new(job: PrintJob, filename: impl Into<String>) -> Self {
+        Self {
+            job,
+            filename: filename.into(),
+            started_at: chrono::Local::now(),
+            started: Instant::now(),
+            finished_after: None,
+            final_state: None,
+            frames_analysed: 0,
+            detections: 0,
+            alerts_sent: 0,
+            pauses: 0,
+            scores: Vec::new(),
+            detection_offsets: Vec::new(),
+            suspicious_frames: Vec::new(),
+            camera_down_since: None,
+            camera_downtime: Duration::ZERO,
+        }
+    }
+
+    /// Get the print job the report belongs to.
+    pub fn job(&self) -> &PrintJob {
+        &self.job
+    }
+
+    /// Record an analysed frame and its failure score.
+    ///
+    /// # Arguments
+    ///
+    /// * `score` - Highest detection confidence in the frame (0.0 to 1.0)
+    /// * `detections` - Number of significant detections in the frame
+    /// * `annotated_frame` - The annotated frame, kept if it is among the most suspicious ones
+    pub fn record_frame(&mut self, score: f32, detections: usize, annotated_frame: &[u8]) {
+        let offset = self.elapsed().as_secs_f32();
+        self.frames_analysed += 1;
+        self.scores.push((offset, score));
+        if detections > 0 {
+            self.detections += detections as u64;
+            self.detection_offsets.push(offset);
+        }
+
+        if score <= 0.0 {
+            return;
+        }
+        let is_suspicious = self.suspicious_frames.len() < constants::REPORT_MOSAIC_FRAMES
+            || self
+                .suspicious_frames
+                .last()
+                .is_some_and(|least| score > least.score);
+        if is_suspicious {
+            self.suspicious_frames.push(SuspiciousFrame {
+                score,
+                offset_seconds: offset,
+                frame: annotated_frame.to_vec(),
+            });
+            self.suspicious_frames
+                .sort_by(|a, b| b.score.total_cmp(&a.score));
+            self.suspicious_frames
+                .truncate(constants::REPORT_MOSAIC_FRAMES);
+        }
+    }
+
+    /// Record an alert queued for the print.
+    pub fn record_alert(&mut self) {
+        self.alerts_sent += 1;
+    }
+
+    /// Record a pause triggered by the monitor.
+    pub fn record_pause(&mut self) {
+        self.pauses += 1;
+    }
+
+    /// Record whether the camera delivered an image, to track camera downtime.
+    pub fn record_camera(&mut self, healthy: bool) {
+        match (healthy, self.camera_down_since) {
+            (false, None) => self.camera_down_since = Some(Instant::now()),
+            (true, Some(since)) => {
+                self.camera_downtime += since.elapsed();
+                self.camera_down_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Mark the print as finished with the printer's final state, e.g. `complete`.
+    pub fn finish(&mut self, final_state: &str) {
+        self.record_camera(true);
+        self.finished_after = Some(self.started.elapsed());
+        self.final_state = Some(final_state.to_string());
+    }
+
+    /// Get the file name of the printed file.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Get the final printer state, or `unknown` if the print has not finished.
+    pub fn final_state(&self) -> &str {
+        self.final_state.as_deref().unwrap_or("unknown")
+    }
+
+    /// Get the time since the start of the print, up to its end once finished.
+    pub fn elapsed(&self) -> Duration {
+        self.finished_after
+            .unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// Get the number of frames analysed during the print.
+    pub fn frames_analysed(&self) -> u64 {
+        self.frames_analysed
+    }
+
+    /// Get the number of significant detections during the print.
+    pub fn detections(&self) -> u64 {
+        self.detections
+    }
+
+    /// Get the highest failure score seen during the print (0.0 to 1.0).
+    pub fn max_score(&self) -> f32 {
+        self.scores
+            .iter()
+            .map(|(_, score)| *score)
+            .fold(0.0, f32::max)
+    }
+
+    /// Get the number of alerts queued for the print.
+    pub fn alerts_sent(&self) -> u32 {
+        self.alerts_sent
+    }
+
+    /// Get the number of pauses triggered during the print.
+    pub fn pauses(&self) -> u32 {
+        self.pauses
+    }
+
+    /// Get the total time the camera delivered no images.
+    pub fn camera_downtime(&self) -> Duration {
+        self.camera_downtime
+            + self
+                .camera_down_since
+                .map(|since| since.elapsed())
+                .unwrap_or_default()
+    }
+
+    /// Get the most suspicious frames, highest score first.
+    pub fn suspicious_frames(&self) -> &[SuspiciousFrame] {
+        &self.suspicious_frames
+    }
+
+    /// Render the failure score over time as a PNG chart.
+    ///
+    /// The score is drawn as a blue line, the alert threshold as a red line
+    /// and every frame with significant detections as a red tick at the top.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chart cannot be encoded.
+    pub fn render_chart(&self) -> Result<Vec<u8>> {
+        encode(
+            DynamicImage::ImageRgb8(self.draw_chart()),
+            image::ImageFormat::Png,
+        )
+    }
+
+    /// Render the most suspicious frames as a JPEG mosaic, `None` without any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a frame cannot be decoded or the mosaic cannot be encoded.
+    pub fn render_mosaic(&self) -> Result<Option<Vec<u8>>> {
+        self.draw_mosaic()?
+            .map(|mosaic| encode(DynamicImage::ImageRgb8(mosaic), image::ImageFormat::Jpeg))
+            .transpose()
+    }
+
+    /// Render the image attached to the summary alert: the chart above the mosaic.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a frame cannot be decoded or the image cannot be encoded.
+    pub fn render_summary_image(&self) -> Result<Vec<u8>> {
+        let chart = self.draw_chart();
+        let Some(mosaic) = self.draw_mosaic()? else {
+            return encode(DynamicImage::ImageRgb8(chart), image::ImageFormat::Jpeg);
+        };
+
+        let mut combined = RgbImage::from_pixel(
+            chart.width(),
+            chart.height() + mosaic.height(),
+            Rgb([255, 255, 255]),
+        );
+        image::imageops::replace(&mut combined, &chart, 0, 0);
+        image::imageops::replace(&mut combined, &mosaic, 0, chart.height() as i64);
+        encode(DynamicImage::ImageRgb8(combined), image::ImageFormat::Jpeg)
+    }
+
+    /// Serialize the report's figures to JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "job_id": self.job.id,
+            "filename": self.filename,
+            "started_at": self.started_at.to_rfc3339(),
+            "duration_seconds": self.elapsed().as_secs(),
+            "final_state": self.final_state(),
+            "frames_analysed": self.frames_analysed,
+            "detections": self.detections,
+            "max_failure_score": self.max_score(),
+            "alerts_sent": self.alerts_sent,
+            "pauses": self.pauses,
+            "camera_downtime_seconds": self.camera_downtime().as_secs(),
+            "suspicious_frames": self.suspicious_frames.iter().map(|frame| json!({
+                "score": frame.score,
+                "offset_seconds": frame.offset_seconds,
+            })).collect::<Vec<_>>(),
+            "scores": self.scores,
+        })
+    }
+
+    /// Save the report to `<dir>/<job id>/` as `report.json`, `chart.png` and
+    /// `mosaic.jpg` (if there are suspicious frames).
+    ///
+    /// Returns the directory the report was saved to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the report cannot be rendered or written.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let name: String = self
+            .job
+            .id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let dir = dir.join(name);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create report directory {}", dir.display()))?;
+
+        std::fs::write(
+            dir.join("report.json"),
+            serde_json::to_string_pretty(&self.to_json())?,
+        )?;
+        std::fs::write(dir.join("chart.png"), self.render_chart()?)?;
+        if let Some(mosaic) = self.render_mosaic()? {
+            std::fs::write(dir.join("mosaic.jpg"), mosaic)?;
+        }
+
+        Ok(dir)
+    }
+
+    fn draw_chart(&self) -> RgbImage {
+        let (width, height) = (
+            constants::REPORT_CHART_WIDTH,
+            constants::REPORT_CHART_HEIGHT,
+        );
+        let margin = 10.0;
+        let mut chart = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+
+        let plot_width = width as f32 - 2.0 * margin;
+        let plot_height = height as f32 - 2.0 * margin;
+        let duration = self.elapsed().as_secs_f32().max(1.0);
+        let point = |offset: f32, score: f32| {
+            (
+                margin + offset / duration * plot_width,
+                margin + (1.0 - score.clamp(0.0, 1.0)) * plot_height,
+            )
+        };
+
+        // Axes and alert threshold
+        let grey = Rgb([160, 160, 160]);
+        draw_line_segment_mut(&mut chart, point(0.0, 0.0), point(duration, 0.0), grey);
+        draw_line_segment_mut(&mut chart, point(0.0, 0.0), point(0.0, 1.0), grey);
+        let threshold = constants::ALERT_PROBABILITY_THRESHOLD;
+        draw_line_segment_mut(
+            &mut chart,
+            point(0.0, threshold),
+            point(duration, threshold),
+            Rgb([230, 120, 120]),
+        );
+
+        // Frames with significant detections
+        let red = Rgb([220, 0, 0]);
+        for offset in &self.detection_offsets {
+            let (x, _) = point(*offset, 0.0);
+            draw_filled_rect_mut(
+                &mut chart,
+                Rect::at(x as i32, 0).of_size(2, margin as u32),
+                red,
+            );
+        }
+
+        // Failure score
+        let blue = Rgb([0, 100, 230]);
+        for pair in self.scores.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            draw_line_segment_mut(
+                &mut chart,
+                point(start.0, start.1),
+                point(end.0, end.1),
+                blue,
+            );
+        }
+
+        chart
+    }
+
+    fn draw_mosaic(&self) -> Result<Option<RgbImage>> {
+        if self.suspicious_frames.is_empty() {
+            return Ok(None);
+        }
+
+        let columns = constants::REPORT_MOSAIC_COLUMNS;
+        let rows = self.suspicious_frames.len().div_ceil(columns);
+        let tile_width = constants::REPORT_CHART_WIDTH / columns as u32;
+        let tile_height = tile_width * 9 / 16;
+        let mut mosaic = RgbImage::from_pixel(
+            tile_width * columns as u32,
+            tile_height * rows as u32,
+            Rgb([0, 0, 0]),
+        );
+
+        for (index, frame) in self.suspicious_frames.iter().enumerate() {
+            let tile = image::load_from_memory(&frame.frame)
+                .context("Failed to decode suspicious frame")?
+                .resize(
+                    tile_width,
+                    tile_height,
+                    image::imageops::FilterType::Triangle,
+                )
+                .to_rgb8();
+            // Center each tile in its cell
+            let x = (index % columns) as u32 * tile_width + (tile_width - tile.width()) / 2;
+            let y = (index / columns) as u32 * tile_height + (tile_height - tile.height()) / 2;
+            image::imageops::replace(&mut mosaic, &tile, x as i64, y as i64);
+        }
+
+        Ok(Some(mosaic))
+    }
+}
+
+fn encode(image: DynamicImage, format: image::ImageFormat) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut data), format)?;
+    Ok(data)
+}
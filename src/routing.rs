@@ -0,0 +1,265 @@
+#![allow(dead_code)]
+use chrono::{DateTime, Local, NaiveTime};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::notifiers::{Alert, AlertKind, Severity};
+
+/// Maximum number of held alerts listed individually in a quiet hours summary.
+const MAX_SUMMARY_FIELDS: usize = 20;
+
+/// Selects which alerts a destination receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertSelector {
+    /// Every alert.
+    All,
+    /// Alerts of one kind, e.g. `failure` or `status`.
+    Kind(AlertKind),
+    /// Alerts of one severity, e.g. `critical`.
+    Severity(Severity),
+}
+
+impl AlertSelector {
+    /// Check whether the selector matches an alert.
+    pub fn matches(&self, alert: &Alert) -> bool {
+        match self {
+            AlertSelector::All => true,
+            AlertSelector::Kind(kind) => alert.kind == *kind,
+            AlertSelector::Severity(severity) => alert.severity == *severity,
+        }
+    }
+}
+
+impl FromStr for AlertSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if s == "all" {
+            return Ok(AlertSelector::All);
+        }
+        AlertKind::from_name(&s)
+            .map(AlertSelector::Kind)
+            .or_else(|| Severity::from_name(&s).map(AlertSelector::Severity))
+            .ok_or_else(|| {
+                format!(
+                    "unknown alert selector '{}' (expected all, an alert type such as failure, pause, status, offline, recovery, report or digest, or a severity such as info, warning or critical)",
+                    s
+                )
+            })
+    }
+}
+
+/// Daily time window, in local time, during which non-critical alerts are held.
+///
+/// Windows may wrap around midnight, e.g. `22:00-07:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Check whether a time of day falls within the quiet hours.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// Get the next end of the quiet hours after the given time.
+    pub fn next_end(&self, now: DateTime<Local>) -> DateTime<Local> {
+        let mut date = now.date_naive();
+        if now.time() >= self.end {
+            date = date.succ_opt().unwrap_or(date);
+        }
+        date.and_time(self.end)
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap_or_else(|| now + chrono::Duration::hours(1))
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("invalid quiet hours '{}' (expected HH:MM-HH:MM)", s))?;
+        let parse = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .map_err(|e| format!("invalid time '{}': {}", time.trim(), e))
+        };
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+/// Routing rules and quiet hours per alert destination.
+///
+/// Destinations are notifier names such as `telegram` or `discord-log`.
+/// A destination without a route receives every alert; one with a route
+/// only receives alerts matching at least one of its selectors. During a
+/// destination's quiet hours, alerts below [`Severity::Critical`] are held
+/// and later sent as a single summary. Fleet digests can be limited to a set
+/// of destinations of their own.
+#[derive(Debug, Clone, Default)]
+pub struct RoutingRules {
+    routes: BTreeMap<String, Vec<AlertSelector>>,
+    quiet_hours: BTreeMap<String, QuietHours>,
+    digest_destinations: Option<Vec<String>>,
+}
+
+impl RoutingRules {
+    /// Restrict a destination to the alerts matching any of the selectors.
+    pub fn with_route(
+        mut self,
+        destination: impl Into<String>,
+        selectors: Vec<AlertSelector>,
+    ) -> Self {
+        self.routes.insert(destination.into(), selectors);
+        self
+    }
+
+    /// Set the quiet hours of a destination.
+    pub fn with_quiet_hours(
+        mut self,
+        destination: impl Into<String>,
+        quiet_hours: QuietHours,
+    ) -> Self {
+        self.quiet_hours.insert(destination.into(), quiet_hours);
+        self
+    }
+
+    /// Send fleet digests only to the given destinations.
+    pub fn with_digest_destinations(mut self, destinations: Vec<String>) -> Self {
+        self.digest_destinations = Some(destinations);
+        self
+    }
+
+    /// Add routes from a specification like `discord-owner=failure,critical;discord-log=status`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first invalid entry.
+    pub fn parse_routes(mut self, spec: &str) -> Result<Self, String> {
+        for (destination, selectors) in parse_entries(spec)? {
+            let selectors = selectors
+                .split(',')
+                .filter(|selector| !selector.trim().is_empty())
+                .map(AlertSelector::from_str)
+                .collect::<Result<Vec<_>, _>>()?;
+            self = self.with_route(destination, selectors);
+        }
+        Ok(self)
+    }
+
+    /// Add quiet hours from a specification like `telegram=22:00-07:00;discord-owner=23:00-06:30`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first invalid entry.
+    pub fn parse_quiet_hours(mut self, spec: &str) -> Result<Self, String> {
+        for (destination, window) in parse_entries(spec)? {
+            self = self.with_quiet_hours(destination, window.parse()?);
+        }
+        Ok(self)
+    }
+
+    /// Get every destination referenced by a route or quiet hours.
+    pub fn destinations(&self) -> Vec<&str> {
+        let mut destinations: Vec<&str> = self
+            .routes
+            .keys()
+            .chain(self.quiet_hours.keys())
+            .chain(self.digest_destinations.iter().flatten())
+            .map(String::as_str)
+            .collect();
+        destinations.sort_unstable();
+        destinations.dedup();
+        destinations
+    }
+
+    /// Check whether a destination should receive an alert.
+    pub fn accepts(&self, destination: &str, alert: &Alert) -> bool {
+        if alert.kind == AlertKind::FleetDigest
+            && let Some(destinations) = &self.digest_destinations
+            && !destinations.iter().any(|d| d == destination)
+        {
+            return false;
+        }
+        self.routes
+            .get(destination)
+            .is_none_or(|selectors| selectors.iter().any(|s| s.matches(alert)))
+    }
+
+    /// Check whether a destination is in its quiet hours at the given time.
+    pub fn is_quiet(&self, destination: &str, time: NaiveTime) -> bool {
+        self.quiet_hours
+            .get(destination)
+            .is_some_and(|quiet_hours| quiet_hours.contains(time))
+    }
+
+    /// Get the end of a destination's current quiet hours, `None` if it is
+    /// not in its quiet hours.
+    pub fn quiet_until(&self, destination: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.quiet_hours
+            .get(destination)
+            .filter(|quiet_hours| quiet_hours.contains(now.time()))
+            .map(|quiet_hours| quiet_hours.next_end(now))
+    }
+
+    /// Check whether an alert should be held for a destination at the given time.
+    ///
+    /// Progress updates edit an existing message silently and are never held.
+    pub fn holds(&self, destination: &str, alert: &Alert, time: NaiveTime) -> bool {
+        alert.severity < Severity::Critical
+            && alert.kind != AlertKind::PrintProgress
+            && self.is_quiet(destination, time)
+    }
+}
+
+/// Add alerts held during quiet hours to a summary alert.
+///
+/// The summary lists every held alert with its local time and carries the
+/// most recent image, if any.
+pub fn summarize(mut summary: Alert, alerts: &[Alert]) -> Alert {
+    for alert in alerts.iter().take(MAX_SUMMARY_FIELDS) {
+        let first_line = alert.description.lines().next().unwrap_or_default();
+        summary = summary.with_field(
+            format!(
+                "{} {}",
+                alert
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M"),
+                alert.title
+            ),
+            first_line,
+        );
+    }
+    if alerts.len() > MAX_SUMMARY_FIELDS {
+        summary = summary.with_field("…", format!("+{}", alerts.len() - MAX_SUMMARY_FIELDS));
+    }
+
+    summary.image = alerts.iter().rev().find_map(|alert| alert.image.clone());
+    summary
+}
+
+/// Split a `name=value;name=value` specification into its entries.
+pub(crate) fn parse_entries(spec: &str) -> Result<Vec<(String, String)>, String> {
+    spec.split(';')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (destination, value) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid entry '{}' (expected name=value)", entry.trim()))?;
+            Ok((destination.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
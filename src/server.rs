@@ -0,0 +1,339 @@
+#![allow(dead_code)]
+use anyhow::Result;
+use log::{debug, error, info};
+use std::{
+    io::{Cursor, Write},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::api::ControlApi;
+use crate::config::constants;
+use crate::metrics;
+use crate::state::{self, SharedState};
+
+pub(crate) type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+/// Single-page dashboard served at `/`, with no external assets.
+const DASHBOARD_HTML: &str = include_str!("../assets/dashboard.html");
+
+/// Embedded HTTP server exposing health, status and image endpoints.
+///
+/// Endpoints:
+/// * `GET /` - Live web dashboard
+/// * `GET /stream` - Server-Sent Events feeding the dashboard
+/// * `GET /cameras/{index}/latest-annotated.jpg` - Most recent annotated frame per camera
+/// * `GET /healthz` - Liveness: the monitoring loop is still iterating
+/// * `GET /readyz` - Readiness: model loaded, camera and Moonraker reachable
+/// * `GET /status` - JSON snapshot of printer, detection and camera state
+/// * `GET /latest.jpg` - Most recent processed frame
+/// * `GET /latest-annotated.jpg` - Most recent frame with detections drawn
+/// * `GET /metrics` - Prometheus metrics
+/// * `GET /alerts/{file}` - Image linked from a webhook alert
+/// * `/api/...` - Authenticated control API, see [`ControlApi`]
+///
+/// When an API token is configured, the stream, status and image endpoints
+/// require it as well, see [`ControlApi::may_read`].
+pub struct HttpServer {
+    state: SharedState,
+    api: ControlApi,
+    stream_clients: Arc<AtomicUsize>,
+    alert_image_dir: Option<PathBuf>,
+}
+
+impl HttpServer {
+    /// Create a new HttpServer serving the given shared monitoring state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - Shared monitoring state
+    /// * `api` - Control API handling requests under `/api/`
+    pub fn new(state: SharedState, api: ControlApi) -> Self {
+        Self {
+            state,
+            api,
+            stream_clients: Arc::new(AtomicUsize::new(0)),
+            alert_image_dir: None,
+        }
+    }
+
+    /// Serve the images linked from webhook alerts from `dir` under `/alerts/`.
+    pub fn with_alert_image_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.alert_image_dir = Some(dir.into());
+        self
+    }
+
+    /// Bind to the given address and serve requests on a pool of background
+    /// threads, so a request waiting on the printer does not block the
+    /// health checks and the dashboard.
+    ///
+    /// # Arguments
+    ///
+    /// * `bind_address` - Address to listen on (e.g., "127.0.0.1:8080")
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub fn start(self, bind_address: &str) -> Result<thread::JoinHandle<()>> {
+        let server = Server::http(bind_address).map_err(|e| {
+            anyhow::anyhow!("Failed to bind HTTP server to {}: {}", bind_address, e)
+        })?;
+
+        info!("HTTP server listening on {}", bind_address);
+
+        let handle = thread::Builder::new()
+            .name("http-server".to_string())
+            .spawn(move || {
+                thread::scope(|scope| {
+                    for worker in 0..constants::HTTP_WORKER_THREADS {
+                        let spawned = thread::Builder::new()
+                            .name(format!("http-worker-{}", worker))
+                            .spawn_scoped(scope, || {
+                                for request in server.incoming_requests() {
+                                    self.handle_request(request);
+                                }
+                            });
+                        if let Err(e) = spawned {
+                            error!("Failed to start HTTP worker: {}", e);
+                        }
+                    }
+                });
+            })?;
+
+        Ok(handle)
+    }
+
+    /// Route a single request and send the response.
+    fn handle_request(&self, mut request: Request) {
+        let path = request.url().split('?').next().unwrap_or("").to_string();
+        debug!("HTTP {} {}", request.method(), path);
+
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let protected = matches!(
+            path.as_str(),
+            "/stream" | "/status" | "/latest.jpg" | "/latest-annotated.jpg"
+        ) || segments.first() == Some(&"cameras");
+
+        if protected && !self.api.may_read(&request) {
+            let response = json_response(
+                401,
+                &serde_json::json!({ "error": "missing or invalid API token" }),
+            );
+            if let Err(e) = request.respond(response) {
+                error!("Failed to send HTTP response: {}", e);
+            }
+            return;
+        }
+
+        if request.method() == &Method::Get && path == "/stream" {
+            self.stream(request);
+            return;
+        }
+
+        let response = match (request.method(), path.as_str()) {
+            (Method::Get, "/") => Response::from_string(DASHBOARD_HTML)
+                .with_header(header("Content-Type", "text/html; charset=utf-8")),
+            (Method::Get, "/healthz") => self.healthz(),
+            (Method::Get, "/readyz") => self.readyz(),
+            (Method::Get, "/status") => self.status(),
+            (Method::Get, "/metrics") => self.metrics(),
+            (Method::Get, "/latest.jpg") => {
+                image_response(state::lock(&self.state).latest_frame.clone())
+            }
+            (Method::Get, "/latest-annotated.jpg") => {
+                image_response(state::lock(&self.state).latest_annotated_frame.clone())
+            }
+            (Method::Get, _) if matches!(segments.as_slice(), ["alerts", _]) => {
+                if self.api.may_read_alert_image(&request, segments[1]) {
+                    self.alert_image(segments[1])
+                } else {
+                    json_response(
+                        401,
+                        &serde_json::json!({ "error": "missing or invalid signature" }),
+                    )
+                }
+            }
+            (Method::Get, _)
+                if matches!(segments.as_slice(), ["cameras", _, "latest-annotated.jpg"]) =>
+            {
+                let frame = segments[1].parse::<usize>().ok().and_then(|index| {
+                    state::lock(&self.state)
+                        .camera_frames
+                        .get(index)
+                        .cloned()
+                        .flatten()
+                });
+                image_response(frame)
+            }
+            (_, path) if path.starts_with("/api/") => self.api.handle(&mut request, path),
+            _ => json_response(404, &serde_json::json!({ "error": "not found" })),
+        };
+
+        if let Err(e) = request.respond(response) {
+            error!("Failed to send HTTP response: {}", e);
+        }
+    }
+
+    /// Stream dashboard updates as Server-Sent Events on a dedicated thread.
+    ///
+    /// The response is written directly to the connection so each event is
+    /// flushed immediately. The thread exits once the client disconnects.
+    /// At most [`constants::MAX_STREAM_CLIENTS`] clients are served at once,
+    /// further clients get a 503.
+    fn stream(&self, request: Request) {
+        if self.stream_clients.fetch_add(1, Ordering::SeqCst) >= constants::MAX_STREAM_CLIENTS {
+            self.stream_clients.fetch_sub(1, Ordering::SeqCst);
+            let response = json_response(
+                503,
+                &serde_json::json!({ "error": "too many dashboard clients" }),
+            );
+            if let Err(e) = request.respond(response) {
+                error!("Failed to send HTTP response: {}", e);
+            }
+            return;
+        }
+
+        let state = self.state.clone();
+        let clients = self.stream_clients.clone();
+        let spawned = thread::Builder::new()
+            .name("http-stream".to_string())
+            .spawn(move || {
+                let _client = StreamClient(clients);
+                let mut writer = request.into_writer();
+                let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n";
+                if writer.write_all(headers.as_bytes()).is_err() {
+                    return;
+                }
+
+                loop {
+                    let payload = state::lock(&state).dashboard_json();
+                    let event = format!("data: {}\n\n", payload);
+                    if writer.write_all(event.as_bytes()).is_err() || writer.flush().is_err() {
+                        debug!("Dashboard stream client disconnected");
+                        return;
+                    }
+                    thread::sleep(Duration::from_secs(
+                        constants::DASHBOARD_UPDATE_INTERVAL_SECONDS,
+                    ));
+                }
+            });
+
+        if let Err(e) = spawned {
+            error!("Failed to start dashboard stream: {}", e);
+        }
+    }
+
+    /// Serve a stored alert image. Only plain file names are accepted, so
+    /// requests cannot leave the image directory.
+    fn alert_image(&self, file_name: &str) -> HttpResponse {
+        let valid = !file_name.starts_with('.')
+            && file_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        let image = self
+            .alert_image_dir
+            .as_ref()
+            .filter(|_| valid)
+            .and_then(|dir| std::fs::read(dir.join(file_name)).ok());
+        match image {
+            Some(data) => image_response(Some(data)),
+            None => json_response(404, &serde_json::json!({ "error": "not found" })),
+        }
+    }
+
+    /// Report liveness. Before the first loop iteration, while the model is
+    /// still downloading and loading, the service reports `starting` with a
+    /// 200 so container health checks do not restart it.
+    fn healthz(&self) -> HttpResponse {
+        let state = state::lock(&self.state);
+        if state.last_heartbeat.is_none() {
+            return json_response(200, &serde_json::json!({ "alive": true, "starting": true }));
+        }
+
+        let alive = state.is_alive(constants::LIVENESS_TIMEOUT_SECONDS);
+        let status = if alive { 200 } else { 503 };
+        json_response(
+            status,
+            &serde_json::json!({ "alive": alive, "starting": false }),
+        )
+    }
+
+    fn readyz(&self) -> HttpResponse {
+        let state = state::lock(&self.state);
+        let status = if state.is_ready() { 200 } else { 503 };
+        json_response(status, &state.readiness_json())
+    }
+
+    fn status(&self) -> HttpResponse {
+        json_response(200, &state::lock(&self.state).status_json())
+    }
+
+    fn metrics(&self) -> HttpResponse {
+        metrics::get()
+            .ready
+            .set(if state::lock(&self.state).is_ready() {
+                1.0
+            } else {
+                0.0
+            });
+
+        match metrics::get().encode() {
+            Ok(body) => Response::from_string(body)
+                .with_header(header("Content-Type", "text/plain; version=0.0.4")),
+            Err(e) => {
+                error!("Failed to encode metrics: {}", e);
+                json_response(
+                    500,
+                    &serde_json::json!({ "error": "failed to encode metrics" }),
+                )
+            }
+        }
+    }
+}
+
+/// Releases a dashboard stream slot when the client thread exits.
+struct StreamClient(Arc<AtomicUsize>);
+
+impl Drop for StreamClient {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Build a response header from static strings.
+pub(crate) fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("Invalid HTTP header")
+}
+
+/// Build a JSON response with the given status code.
+pub(crate) fn json_response(status: u16, body: &serde_json::Value) -> HttpResponse {
+    Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+}
+
+/// Build an image response, or a 404 if no frame is available yet.
+pub(crate) fn image_response(image_data: Option<Vec<u8>>) -> HttpResponse {
+    match image_data {
+        Some(data) => {
+            let mime = match image::guess_format(&data) {
+                Ok(image::ImageFormat::Png) => "image/png",
+                Ok(image::ImageFormat::Gif) => "image/gif",
+                _ => "image/jpeg",
+            };
+            Response::from_data(data)
+                .with_header(header("Content-Type", mime))
+                .with_header(header("Cache-Control", "no-store"))
+        }
+        None => json_response(
+            404,
+            &serde_json::json!({ "error": "no frame available yet" }),
+        ),
+    }
+}
@@ -0,0 +1,328 @@
+#![allow(dead_code)]
+use serde_json::json;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::config::constants;
+use crate::detector::Detection;
+use crate::image_fetcher::CameraHealth;
+
+/// Monitoring state shared between the monitoring loop and the HTTP server.
+pub type SharedState = Arc<Mutex<MonitorState>>;
+
+/// Lock a shared value, recovering the data if another thread panicked
+/// while holding the lock.
+pub fn lock<T>(shared: &Arc<Mutex<T>>) -> MutexGuard<'_, T> {
+    shared
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Summary of a single detection, kept for status reporting.
+#[derive(Debug, Clone)]
+pub struct DetectionSummary {
+    /// The type of print failure detected.
+    pub label: String,
+
+    /// Confidence score from 0.0 to 1.0.
+    pub confidence: f32,
+
+    /// Bounding box center and size, relative to the image dimensions.
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+
+    /// Local time at which the detection was made.
+    pub timestamp: chrono::DateTime<chrono::Local>,
+}
+
+impl DetectionSummary {
+    /// Create a summary of the given detection, timestamped now.
+    pub fn from_detection(detection: &Detection) -> Self {
+        Self {
+            label: detection.label.clone(),
+            confidence: detection.confidence,
+            x: detection.center_x(),
+            y: detection.center_y(),
+            w: detection.width(),
+            h: detection.height(),
+            timestamp: chrono::Local::now(),
+        }
+    }
+
+    /// Convert the summary to JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "label": self.label,
+            "confidence": self.confidence,
+            "x": self.x,
+            "y": self.y,
+            "w": self.w,
+            "h": self.h,
+            "timestamp": self.timestamp.to_rfc3339(),
+        })
+    }
+}
+
+/// Severity of an entry in the event log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLevel {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl EventLevel {
+    /// Get the lowercase name of the level.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventLevel::Info => "info",
+            EventLevel::Warning => "warning",
+            EventLevel::Critical => "critical",
+        }
+    }
+}
+
+/// A single entry in the recent event log.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// Local time at which the event happened.
+    pub timestamp: chrono::DateTime<chrono::Local>,
+
+    /// Severity of the event.
+    pub level: EventLevel,
+
+    /// Human-readable description of the event.
+    pub message: String,
+}
+
+/// Snapshot of everything the monitoring loop knows about the printer,
+/// the cameras and the detector.
+#[derive(Debug, Default)]
+pub struct MonitorState {
+    /// Whether the detection model has been loaded.
+    pub model_loaded: bool,
+
+    /// Whether the last Moonraker request succeeded.
+    pub moonraker_reachable: bool,
+
+    /// Error message of the last failed Moonraker request.
+    pub moonraker_error: Option<String>,
+
+    /// Current print state reported by Moonraker (e.g. "printing").
+    pub printer_state: String,
+
+    /// File currently loaded on the printer.
+    pub print_filename: Option<String>,
+
+    /// Print progress from 0.0 to 1.0, as reported by the virtual SD card.
+    pub print_progress: Option<f64>,
+
+    /// Number of failures counted towards the pause threshold.
+    pub failure_count: u32,
+
+    /// Highest detection confidence in the most recently analysed frame.
+    pub failure_score: f32,
+
+    /// Most recent significant detection.
+    pub last_detection: Option<DetectionSummary>,
+
+    /// Health of each configured camera.
+    pub cameras: Vec<CameraHealth>,
+
+    /// Most recent processed frame.
+    pub latest_frame: Option<Vec<u8>>,
+
+    /// Most recent processed frame with detections drawn on it.
+    pub latest_annotated_frame: Option<Vec<u8>>,
+
+    /// Most recent annotated frame per camera, indexed like `cameras`.
+    pub camera_frames: Vec<Option<Vec<u8>>>,
+
+    /// Incremented every time a new frame is recorded.
+    pub frame_version: u64,
+
+    /// Rolling history of failure scores, oldest first.
+    pub score_history: VecDeque<(chrono::DateTime<chrono::Local>, f32)>,
+
+    /// Recent events, oldest first.
+    pub event_log: VecDeque<LogEntry>,
+
+    /// Time the monitoring loop last started an iteration.
+    pub last_heartbeat: Option<chrono::DateTime<chrono::Local>>,
+
+    /// Whether failure detection has been paused through the control API.
+    pub monitoring_paused: bool,
+
+    /// Non-critical alerts are not sent until this time.
+    pub alerts_snoozed_until: Option<chrono::DateTime<chrono::Local>>,
+
+    /// Objectness threshold currently used by the detector.
+    pub objectness_threshold: f32,
+
+    /// Class probability threshold currently used by the detector.
+    pub class_prob_threshold: f32,
+
+    /// Objectness threshold requested through the control API, applied by
+    /// the monitoring loop on its next iteration.
+    pub pending_objectness_threshold: Option<f32>,
+
+    /// Class probability threshold requested through the control API,
+    /// applied by the monitoring loop on its next iteration.
+    pub pending_class_prob_threshold: Option<f32>,
+
+    /// Whether a one-off detection was requested through the control API.
+    pub detection_requested: bool,
+
+    /// Number of alerts waiting in the delivery queue.
+    pub alert_queue_depth: usize,
+
+    /// Number of alerts held back by quiet hours.
+    pub held_alerts: usize,
+}
+
+impl MonitorState {
+    /// Append an entry to the recent event log.
+    pub fn push_event(&mut self, level: EventLevel, message: impl Into<String>) {
+        self.event_log.push_back(LogEntry {
+            timestamp: chrono::Local::now(),
+            level,
+            message: message.into(),
+        });
+        while self.event_log.len() > constants::EVENT_LOG_LENGTH {
+            self.event_log.pop_front();
+        }
+    }
+
+    /// Record the failure score of the most recently analysed frame.
+    pub fn record_score(&mut self, score: f32) {
+        self.failure_score = score;
+        self.score_history.push_back((chrono::Local::now(), score));
+        while self.score_history.len() > constants::SCORE_HISTORY_LENGTH {
+            self.score_history.pop_front();
+        }
+    }
+
+    /// Record the latest processed and annotated frames from a camera.
+    pub fn record_frame(&mut self, camera_index: usize, frame: Vec<u8>, annotated: Vec<u8>) {
+        if self.camera_frames.len() <= camera_index {
+            self.camera_frames.resize(camera_index + 1, None);
+        }
+        self.camera_frames[camera_index] = Some(annotated.clone());
+        self.latest_frame = Some(frame);
+        self.latest_annotated_frame = Some(annotated);
+        self.frame_version += 1;
+    }
+
+    /// Check whether the monitoring loop has run within the given timeout.
+    pub fn is_alive(&self, timeout_seconds: i64) -> bool {
+        self.last_heartbeat.is_some_and(|heartbeat| {
+            (chrono::Local::now() - heartbeat).num_seconds() <= timeout_seconds
+        })
+    }
+
+    /// Check whether non-critical alerts are currently snoozed.
+    pub fn alerts_snoozed(&self) -> bool {
+        self.alerts_snoozed_until
+            .is_some_and(|until| chrono::Local::now() < until)
+    }
+
+    /// Check whether at least one camera returned an image on its last fetch.
+    pub fn any_camera_healthy(&self) -> bool {
+        self.cameras.iter().any(|camera| camera.healthy)
+    }
+
+    /// Check whether the application is ready to monitor prints.
+    ///
+    /// Requires the model to be loaded, Moonraker to be reachable and at
+    /// least one camera to be healthy.
+    pub fn is_ready(&self) -> bool {
+        self.model_loaded && self.moonraker_reachable && self.any_camera_healthy()
+    }
+
+    /// Build the readiness report returned by the `/readyz` endpoint.
+    pub fn readiness_json(&self) -> serde_json::Value {
+        json!({
+            "ready": self.is_ready(),
+            "checks": {
+                "model_loaded": self.model_loaded,
+                "moonraker_reachable": self.moonraker_reachable,
+                "camera_reachable": self.any_camera_healthy(),
+            }
+        })
+    }
+
+    /// Build the status report returned by the `/status` endpoint.
+    pub fn status_json(&self) -> serde_json::Value {
+        let cameras: Vec<_> = self
+            .cameras
+            .iter()
+            .map(|camera| {
+                json!({
+                    "url": camera.url,
+                    "healthy": camera.healthy,
+                    "consecutive_failures": camera.consecutive_failures,
+                    "last_success": camera.last_success.map(|t| t.to_rfc3339()),
+                })
+            })
+            .collect();
+
+        json!({
+            "printer": {
+                "state": self.printer_state,
+                "filename": self.print_filename,
+                "progress": self.print_progress,
+                "moonraker_reachable": self.moonraker_reachable,
+                "moonraker_error": self.moonraker_error,
+            },
+            "failure_count": self.failure_count,
+            "failure_score": self.failure_score,
+            "last_detection": self.last_detection.as_ref().map(DetectionSummary::to_json),
+            "cameras": cameras,
+            "model_loaded": self.model_loaded,
+            "last_heartbeat": self.last_heartbeat.map(|t| t.to_rfc3339()),
+            "monitoring_paused": self.monitoring_paused,
+            "alerts_snoozed_until": self
+                .alerts_snoozed_until
+                .filter(|_| self.alerts_snoozed())
+                .map(|t| t.to_rfc3339()),
+            "alert_queue_depth": self.alert_queue_depth,
+            "held_alerts": self.held_alerts,
+            "thresholds": {
+                "objectness": self.objectness_threshold,
+                "class_prob": self.class_prob_threshold,
+            },
+        })
+    }
+
+    /// Build the payload pushed to the dashboard over Server-Sent Events.
+    pub fn dashboard_json(&self) -> serde_json::Value {
+        let score_history: Vec<_> = self
+            .score_history
+            .iter()
+            .map(|(timestamp, score)| json!([timestamp.timestamp_millis(), score]))
+            .collect();
+        let events: Vec<_> = self
+            .event_log
+            .iter()
+            .rev()
+            .map(|entry| {
+                json!({
+                    "timestamp": entry.timestamp.to_rfc3339(),
+                    "level": entry.level.as_str(),
+                    "message": entry.message,
+                })
+            })
+            .collect();
+
+        json!({
+            "status": self.status_json(),
+            "score_history": score_history,
+            "events": events,
+            "frame_version": self.frame_version,
+        })
+    }
+}
@@ -0,0 +1,212 @@
+#![allow(dead_code)]
+use anyhow::{Context, Result};
+use handlebars::Handlebars;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::notifiers::{Alert, AlertKind};
+
+/// Locales with bundled translations.
+pub const BUNDLED_LOCALES: &[&str] = &["de", "en", "es", "fr"];
+
+/// Get the bundled template file of a locale.
+fn bundled(locale: &str) -> Option<&'static str> {
+    match locale {
+        "de" => Some(include_str!("../assets/locales/de.json")),
+        "en" => Some(include_str!("../assets/locales/en.json")),
+        "es" => Some(include_str!("../assets/locales/es.json")),
+        "fr" => Some(include_str!("../assets/locales/fr.json")),
+        _ => None,
+    }
+}
+
+/// Color and emoji overrides of one alert type.
+#[derive(Debug, Clone, Default)]
+struct Style {
+    color: Option<u32>,
+    emoji: Option<String>,
+}
+
+/// Localized alert message templates.
+///
+/// A template file is a JSON object with an `alerts` section holding a
+/// `title` and `body` Handlebars template per alert type (`failure`, `pause`,
+/// `status`, `offline`, `recovery`, `progress`, `report`, `digest`,
+/// `summary` and `email_digest`), each with an optional `color` (`"#RRGGBB"`) and `emoji`, and a
+/// `labels` section with the names of alert fields.
+///
+/// Templates are layered: the bundled English file provides every key, the
+/// bundled file of the selected locale overrides it, and a `<locale>.json`
+/// file in the custom template directory overrides both. Custom files only
+/// need to contain the keys they change.
+pub struct MessageTemplates {
+    locale: String,
+    registry: Handlebars<'static>,
+    styles: HashMap<String, Style>,
+    labels: HashMap<String, String>,
+}
+
+impl MessageTemplates {
+    /// Load the templates of a locale.
+    ///
+    /// # Arguments
+    ///
+    /// * `locale` - Locale code such as `en` or `de`
+    /// * `custom_dir` - Optional directory with `<locale>.json` overrides
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the locale is neither bundled nor provided by a
+    /// custom file, or if a template file or template is invalid.
+    pub fn load(locale: &str, custom_dir: Option<&Path>) -> Result<Self> {
+        let mut merged = parse(bundled("en").unwrap_or_default(), "bundled en")?;
+
+        let custom_path = custom_dir.map(|dir| dir.join(format!("{}.json", locale)));
+        let custom = custom_path.as_ref().filter(|path| path.exists());
+
+        match bundled(locale) {
+            Some(file) => merge(&mut merged, parse(file, locale)?),
+            None if custom.is_some() => {}
+            None => anyhow::bail!(
+                "Unsupported locale '{}' (bundled: {})",
+                locale,
+                BUNDLED_LOCALES.join(", ")
+            ),
+        }
+
+        if let Some(path) = custom {
+            let file = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read templates from {}", path.display()))?;
+            merge(&mut merged, parse(&file, &path.display().to_string())?);
+        }
+
+        Self::from_json(locale, &merged)
+    }
+
+    /// Build templates from a complete template file.
+    fn from_json(locale: &str, file: &serde_json::Value) -> Result<Self> {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(handlebars::no_escape);
+
+        let mut styles = HashMap::new();
+        if let Some(alerts) = file["alerts"].as_object() {
+            for (name, template) in alerts {
+                for part in ["title", "body"] {
+                    registry
+                        .register_template_string(
+                            &format!("{}.{}", name, part),
+                            template[part].as_str().unwrap_or_default(),
+                        )
+                        .with_context(|| {
+                            format!("Invalid {} template for {} alerts", part, name)
+                        })?;
+                }
+
+                let color = template["color"]
+                    .as_str()
+                    .map(|color| {
+                        u32::from_str_radix(color.trim_start_matches('#'), 16).with_context(|| {
+                            format!("Invalid color '{}' for {} alerts", color, name)
+                        })
+                    })
+                    .transpose()?;
+                let emoji = template["emoji"].as_str().map(str::to_string);
+                styles.insert(name.clone(), Style { color, emoji });
+            }
+        }
+
+        let labels = file["labels"]
+            .as_object()
+            .map(|labels| {
+                labels
+                    .iter()
+                    .filter_map(|(key, label)| Some((key.clone(), label.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            locale: locale.to_string(),
+            registry,
+            styles,
+            labels,
+        })
+    }
+
+    /// Get the locale of the templates.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Render an alert of the given kind from the template of the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering a template fails.
+    pub fn alert(&self, kind: AlertKind, context: &serde_json::Value) -> Result<Alert> {
+        self.render(kind.as_str(), kind, context)
+    }
+
+    /// Render an alert of the given kind from a named template.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if rendering a template fails.
+    pub fn render(
+        &self,
+        template: &str,
+        kind: AlertKind,
+        context: &serde_json::Value,
+    ) -> Result<Alert> {
+        let title = self
+            .registry
+            .render(&format!("{}.title", template), context)
+            .with_context(|| format!("Failed to render {} alert title", template))?;
+        let body = self
+            .registry
+            .render(&format!("{}.body", template), context)
+            .with_context(|| format!("Failed to render {} alert body", template))?;
+
+        let mut alert = Alert::new(kind, title.trim(), body.trim());
+        if let Some(style) = self.styles.get(template) {
+            if let Some(color) = style.color {
+                alert.color = color;
+            }
+            if let Some(emoji) = &style.emoji {
+                alert.emoji = emoji.clone();
+            }
+        }
+        Ok(alert)
+    }
+
+    /// Get the translated label for a key, falling back to the key itself.
+    pub fn label(&self, key: &str) -> String {
+        self.labels
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+impl Default for MessageTemplates {
+    /// The bundled English templates.
+    fn default() -> Self {
+        Self::load("en", None).expect("Bundled English templates are valid")
+    }
+}
+
+fn parse(file: &str, source: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(file).with_context(|| format!("Invalid template file {}", source))
+}
+
+/// Recursively merge `overlay` into `base`, overlay values taking precedence.
+fn merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
@@ -39,3 +39,2382 @@ fn test_printer_service_url_and_mock() {
     let cancel_result = printer.cancel_print();
     assert!(cancel_result.is_err());
 }
+
+#[test]
+fn test_feedback_false_positive_suppression() {
+    use print_guardian::FeedbackService;
+    use print_guardian::detector::Detection;
+
+    let dir = std::env::temp_dir().join(format!("print-guardian-negatives-{}", std::process::id()));
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+    let detection = Detection {
+        label: "failure".to_string(),
+        confidence: 0.9,
+        bbox: darknet::BBox {
+            x: 0.5,
+            y: 0.5,
+            w: 0.2,
+            h: 0.2,
+        },
+    };
+
+    let mut feedback = FeedbackService::new(dir.clone(), 5);
+    let event_id = feedback.record_event(&image_bytes, std::slice::from_ref(&detection));
+    assert!(!feedback.is_suppressed(&detection));
+
+    // The event did not pause the print, so no request is made to the printer
+    let printer = print_guardian::PrinterService::new("http://localhost:7125".to_string());
+    feedback
+        .acknowledge_false_positive(event_id, &printer)
+        .expect("Acknowledgement failed");
+    assert!(feedback.is_suppressed(&detection));
+    assert!(
+        feedback
+            .filter_suppressed(vec![detection.clone()])
+            .is_empty()
+    );
+    assert!(
+        feedback
+            .acknowledge_false_positive(event_id + 1, &printer)
+            .is_err()
+    );
+
+    let samples = std::fs::read_dir(&dir)
+        .expect("Negatives dir missing")
+        .count();
+    assert_eq!(samples, 3);
+
+    feedback.clear_suppressions();
+    assert!(!feedback.is_suppressed(&detection));
+
+    // A paused event stays unacknowledged while the print cannot be resumed,
+    // so the acknowledgement can be retried
+    let paused_id = feedback.record_event(&image_bytes, std::slice::from_ref(&detection));
+    feedback.mark_paused(paused_id);
+    let unreachable = print_guardian::PrinterService::new("http://127.0.0.1:1".to_string());
+    for _ in 0..2 {
+        assert!(
+            feedback
+                .acknowledge_false_positive(paused_id, &unreachable)
+                .is_err()
+        );
+    }
+    assert!(!feedback.get_event(paused_id).unwrap().false_positive);
+    assert!(!feedback.is_suppressed(&detection));
+    assert_eq!(feedback.acknowledged_count(), 1);
+
+    // A second acknowledgement is rejected while the first one is in flight
+    assert!(feedback.begin_acknowledgement(paused_id).unwrap().is_some());
+    assert!(feedback.is_acknowledging(paused_id));
+    assert!(feedback.begin_acknowledgement(paused_id).is_err());
+    feedback.abort_acknowledgement(paused_id);
+    assert!(feedback.begin_acknowledgement(paused_id).unwrap().is_some());
+    feedback.complete_acknowledgement(paused_id);
+    assert!(!feedback.is_acknowledging(paused_id));
+    assert!(feedback.get_event(paused_id).unwrap().false_positive);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_monitor_state_readiness() {
+    use print_guardian::image_fetcher::CameraHealth;
+    use print_guardian::state::MonitorState;
+
+    let mut state = MonitorState::default();
+    assert!(!state.is_ready());
+    assert!(!state.is_alive(60));
+
+    state.model_loaded = true;
+    state.moonraker_reachable = true;
+    state.cameras = vec![CameraHealth {
+        url: "http://camera.local/image.jpg".to_string(),
+        healthy: true,
+        consecutive_failures: 0,
+        last_success: Some(chrono::Local::now()),
+    }];
+    state.last_heartbeat = Some(chrono::Local::now());
+    assert!(state.is_ready());
+    assert!(state.is_alive(60));
+
+    let status = state.status_json();
+    assert_eq!(status["cameras"][0]["healthy"], true);
+    assert!(status["last_detection"].is_null());
+}
+
+#[test]
+fn test_control_api_authentication_and_snooze() {
+    use print_guardian::state::{self, MonitorState};
+    use print_guardian::{ControlApi, FeedbackService, HttpServer, PrinterService};
+    use std::sync::{Arc, Mutex};
+
+    let state = Arc::new(Mutex::new(MonitorState::default()));
+    let feedback = Arc::new(Mutex::new(FeedbackService::new(
+        std::env::temp_dir().join("print-guardian-api-negatives"),
+        5,
+    )));
+    let api = ControlApi::new(
+        state.clone(),
+        feedback,
+        PrinterService::new("http://localhost:7125".to_string()),
+        Some("secret".to_string()),
+    );
+    HttpServer::new(state.clone(), api)
+        .start("127.0.0.1:18787")
+        .expect("Failed to start HTTP server");
+
+    let client = reqwest::blocking::Client::new();
+    let unauthorized = client
+        .post("http://127.0.0.1:18787/api/alerts/snooze")
+        .body(r#"{"minutes": 10}"#)
+        .send()
+        .expect("Request failed");
+    assert_eq!(unauthorized.status().as_u16(), 401);
+
+    let snoozed = client
+        .post("http://127.0.0.1:18787/api/alerts/snooze")
+        .bearer_auth("secret")
+        .body(r#"{"minutes": 10}"#)
+        .send()
+        .expect("Request failed");
+    assert_eq!(snoozed.status().as_u16(), 200);
+    assert!(state::lock(&state).alerts_snoozed());
+
+    let thresholds = client
+        .put("http://127.0.0.1:18787/api/thresholds")
+        .bearer_auth("secret")
+        .body(r#"{"objectness_threshold": 0.3}"#)
+        .send()
+        .expect("Request failed");
+    assert_eq!(thresholds.status().as_u16(), 202);
+    assert_eq!(state::lock(&state).pending_objectness_threshold, Some(0.3));
+
+    let status = client
+        .get("http://127.0.0.1:18787/status")
+        .send()
+        .expect("Request failed");
+    assert_eq!(status.status().as_u16(), 401);
+
+    let status = client
+        .get("http://127.0.0.1:18787/status")
+        .bearer_auth("secret")
+        .send()
+        .expect("Request failed");
+    assert_eq!(status.status().as_u16(), 200);
+
+    let frame = client
+        .get("http://127.0.0.1:18787/cameras/0/latest-annotated.jpg")
+        .send()
+        .expect("Request failed");
+    assert_eq!(frame.status().as_u16(), 401);
+
+    let frame = client
+        .get("http://127.0.0.1:18787/cameras/0/latest-annotated.jpg?v=1&token=secret")
+        .send()
+        .expect("Request failed");
+    assert_eq!(frame.status().as_u16(), 404);
+
+    let health = client
+        .get("http://127.0.0.1:18787/healthz")
+        .send()
+        .expect("Request failed");
+    assert_eq!(health.status().as_u16(), 200);
+    let health: serde_json::Value = health.json().expect("Invalid JSON");
+    assert_eq!(health["starting"], true);
+}
+
+#[test]
+fn test_control_api_serves_requests_while_printer_is_slow() {
+    use print_guardian::detector::Detection;
+    use print_guardian::state::MonitorState;
+    use print_guardian::{ControlApi, FeedbackService, HttpServer, PrinterService};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    // Moonraker stand-in that accepts connections but never answers
+    let moonraker = std::net::TcpListener::bind("127.0.0.1:18802").expect("Failed to bind");
+    std::thread::spawn(move || {
+        let _connections: Vec<_> = moonraker.incoming().collect();
+    });
+
+    let state = Arc::new(Mutex::new(MonitorState::default()));
+    let feedback = Arc::new(Mutex::new(FeedbackService::new(
+        std::env::temp_dir().join(format!("print-guardian-slow-{}", std::process::id())),
+        5,
+    )));
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+    let event_id = {
+        let mut feedback = feedback.lock().unwrap();
+        let detection = Detection {
+            label: "failure".to_string(),
+            confidence: 0.9,
+            bbox: darknet::BBox {
+                x: 0.5,
+                y: 0.5,
+                w: 0.2,
+                h: 0.2,
+            },
+        };
+        let event_id = feedback.record_event(&image_bytes, &[detection]);
+        feedback.mark_paused(event_id);
+        event_id
+    };
+    let api = ControlApi::new(
+        state.clone(),
+        feedback.clone(),
+        PrinterService::new("http://127.0.0.1:18802".to_string()),
+        Some("secret".to_string()),
+    );
+    HttpServer::new(state, api)
+        .start("127.0.0.1:18801")
+        .expect("Failed to start HTTP server");
+
+    // Acknowledging the paused event waits on the printer to resume
+    std::thread::spawn(move || {
+        let _ = reqwest::blocking::Client::new()
+            .post(format!(
+                "http://127.0.0.1:18801/api/events/{}/false-positive",
+                event_id
+            ))
+            .bearer_auth("secret")
+            .send();
+    });
+    std::thread::sleep(Duration::from_millis(500));
+
+    // Other requests and the monitoring loop's feedback lock are not blocked meanwhile
+    let started = Instant::now();
+    let client = reqwest::blocking::Client::new();
+    let events = client
+        .get("http://127.0.0.1:18801/api/events")
+        .bearer_auth("secret")
+        .send()
+        .expect("Request failed");
+    assert_eq!(events.status().as_u16(), 200);
+    client
+        .get("http://127.0.0.1:18801/metrics")
+        .send()
+        .expect("Request failed");
+    assert!(feedback.try_lock().is_ok());
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!feedback.lock().unwrap().get_event(event_id).unwrap().false_positive);
+}
+
+#[test]
+fn test_metrics_encoding() {
+    let metrics = print_guardian::metrics::get();
+    metrics
+        .record_alert("test", Ok::<(), anyhow::Error>(()))
+        .expect("Alert result changed");
+    assert!(
+        metrics
+            .record_alert("test", Err(anyhow::anyhow!("webhook down")))
+            .is_err()
+    );
+
+    let body = metrics.encode().expect("Failed to encode metrics");
+    assert!(body.contains(r#"print_guardian_alerts_sent_total{type="test"} 1"#));
+    assert!(body.contains(r#"print_guardian_alerts_failed_total{type="test"} 1"#));
+    assert!(body.contains("print_guardian_inference_duration_seconds"));
+}
+
+#[test]
+fn test_dashboard_and_event_stream() {
+    use print_guardian::state::{EventLevel, MonitorState};
+    use print_guardian::{ControlApi, FeedbackService, HttpServer, PrinterService};
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    let state = Arc::new(Mutex::new(MonitorState::default()));
+    {
+        let mut state = state.lock().unwrap();
+        state.record_score(0.42);
+        state.push_event(EventLevel::Warning, "test event");
+    }
+    let api = ControlApi::new(
+        state.clone(),
+        Arc::new(Mutex::new(FeedbackService::new(
+            std::env::temp_dir().join("print-guardian-stream-negatives"),
+            5,
+        ))),
+        PrinterService::new("http://localhost:7125".to_string()),
+        None,
+    );
+    HttpServer::new(state, api)
+        .start("127.0.0.1:18788")
+        .expect("Failed to start HTTP server");
+
+    let dashboard = reqwest::blocking::get("http://127.0.0.1:18788/")
+        .expect("Request failed")
+        .text()
+        .expect("Invalid body");
+    assert!(dashboard.contains("EventSource"));
+
+    let mut stream =
+        reqwest::blocking::get("http://127.0.0.1:18788/stream").expect("Request failed");
+    let mut buffer = [0u8; 4096];
+    let read = stream.read(&mut buffer).expect("Failed to read stream");
+    let event = String::from_utf8_lossy(&buffer[..read]);
+    assert!(event.starts_with("data: "));
+    assert!(event.contains("test event"));
+
+    let mut clients = vec![stream];
+    for _ in 1..print_guardian::config::constants::MAX_STREAM_CLIENTS {
+        let mut client =
+            reqwest::blocking::get("http://127.0.0.1:18788/stream").expect("Request failed");
+        assert_eq!(client.status().as_u16(), 200);
+        assert!(client.read(&mut buffer).expect("Failed to read stream") > 0);
+        clients.push(client);
+    }
+    let rejected =
+        reqwest::blocking::get("http://127.0.0.1:18788/stream").expect("Request failed");
+    assert_eq!(rejected.status().as_u16(), 503);
+}
+
+#[test]
+fn test_alert_service_fans_out_to_all_notifiers() {
+    use print_guardian::{Alert, AlertKind, AlertService, Notifier, Severity};
+    use std::sync::{Arc, Mutex};
+
+    struct RecordingNotifier {
+        sent: Arc<Mutex<Vec<Alert>>>,
+        fail: bool,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push(alert.clone());
+            if self.fail {
+                anyhow::bail!("backend down");
+            }
+            Ok(())
+        }
+    }
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let service = AlertService::new(vec![
+        Box::new(RecordingNotifier {
+            sent: sent.clone(),
+            fail: true,
+        }),
+        Box::new(RecordingNotifier {
+            sent: sent.clone(),
+            fail: false,
+        }),
+    ]);
+
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+    let result = service.send_print_failure_alert(
+        7,
+        "spaghetti",
+        87.5,
+        10.0,
+        20.0,
+        30.0,
+        40.0,
+        Some(&image_bytes),
+    );
+
+    // The failing backend is reported but does not prevent delivery to the other one
+    assert!(result.is_err());
+    let sent = sent.lock().unwrap();
+    assert_eq!(sent.len(), 2);
+
+    let alert = &sent[1];
+    assert_eq!(alert.kind, AlertKind::PrintFailure);
+    assert_eq!(alert.severity, Severity::Warning);
+    assert!(
+        alert
+            .fields
+            .iter()
+            .any(|f| f.name == "Confidence" && f.value == "87.50%")
+    );
+    assert!(
+        alert
+            .fields
+            .iter()
+            .any(|f| f.name == "Event" && f.value == "#7")
+    );
+    let image = alert.image.as_ref().expect("Missing image");
+    assert_eq!(image.mime_type, "image/jpeg");
+    assert!(image.filename.starts_with("failure_detection_"));
+}
+
+#[test]
+fn test_telegram_notifier() {
+    use print_guardian::notifiers::{Alert, AlertKind, Notifier, TelegramNotifier};
+
+    let alert = Alert::new(
+        AlertKind::PrintFailure,
+        "Print Failure Detected",
+        "Detected layer_shift print failure",
+    )
+    .with_field("Confidence", "87.50%");
+
+    let text = TelegramNotifier::format_message(&alert);
+    assert!(text.starts_with("*⚠️ Print Failure Detected*"));
+    assert!(text.contains("layer\\_shift"));
+    assert!(text.contains("*Confidence:* 87.50%"));
+
+    // Local stand-in for the Bot API
+    let server = tiny_http::Server::http("127.0.0.1:18789").expect("Failed to bind test server");
+    let handle = std::thread::spawn(move || {
+        let mut request = server.recv().expect("Failed to receive request");
+        let url = request.url().to_string();
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).unwrap();
+        request
+            .respond(tiny_http::Response::from_string(r#"{"ok":true}"#))
+            .unwrap();
+        (url, body)
+    });
+
+    let notifier = TelegramNotifier::new("123:abc".to_string(), vec!["-1001".to_string()])
+        .with_api_url("http://127.0.0.1:18789/".to_string());
+    notifier
+        .send(&alert)
+        .expect("Failed to send Telegram alert");
+
+    let (url, body) = handle.join().unwrap();
+    assert_eq!(url, "/bot123:abc/sendMessage");
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["chat_id"], "-1001");
+    assert_eq!(body["parse_mode"], "Markdown");
+    assert_eq!(body["text"], text);
+}
+
+#[test]
+fn test_slack_notifier_blocks() {
+    use print_guardian::notifiers::{Alert, AlertKind, Notifier, SlackNotifier};
+
+    let alert = Alert::new(
+        AlertKind::PrintPaused,
+        "Print Paused Due to Multiple Failures",
+        "Print has been paused after detecting 3 print failures.",
+    )
+    .with_field("Label", "spaghetti")
+    .with_field("Position", "X: 1.0, Y: 2.0");
+
+    let blocks = SlackNotifier::build_blocks(&alert);
+    assert_eq!(blocks[0]["type"], "header");
+    assert_eq!(
+        blocks[0]["text"]["text"],
+        "🚨 Print Paused Due to Multiple Failures"
+    );
+    assert_eq!(blocks[2]["fields"][0]["text"], "*Label:*\nspaghetti");
+    assert_eq!(blocks[2]["fields"].as_array().unwrap().len(), 2);
+
+    // Local stand-in for the Web API, reporting an error like Slack does
+    let server = tiny_http::Server::http("127.0.0.1:18790").expect("Failed to bind test server");
+    let handle = std::thread::spawn(move || {
+        let request = server.recv().expect("Failed to receive request");
+        let url = request.url().to_string();
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .map(|h| h.value.to_string());
+        request
+            .respond(tiny_http::Response::from_string(
+                r#"{"ok":false,"error":"channel_not_found"}"#,
+            ))
+            .unwrap();
+        (url, authorization)
+    });
+
+    let notifier = SlackNotifier::new("xoxb-test".to_string(), "C123".to_string())
+        .with_api_url("http://127.0.0.1:18790".to_string());
+    let error = notifier.send(&alert).unwrap_err();
+    assert!(error.to_string().contains("channel_not_found"));
+
+    let (url, authorization) = handle.join().unwrap();
+    assert_eq!(url, "/chat.postMessage");
+    assert_eq!(authorization.as_deref(), Some("Bearer xoxb-test"));
+
+    // Incoming webhook without a bot token: the message is posted, the image is skipped
+    let server = tiny_http::Server::http("127.0.0.1:18804").expect("Failed to bind test server");
+    let handle = std::thread::spawn(move || {
+        let mut request = server.recv().expect("Failed to receive request");
+        let url = request.url().to_string();
+        let mut body = String::new();
+        request.as_reader().read_to_string(&mut body).unwrap();
+        request
+            .respond(tiny_http::Response::from_string("ok"))
+            .unwrap();
+        // No further requests may follow
+        let extra = server
+            .recv_timeout(std::time::Duration::from_millis(300))
+            .unwrap();
+        (url, body, extra.is_some())
+    });
+
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+    let notifier =
+        SlackNotifier::from_webhook("http://127.0.0.1:18804/services/T0/B0/x".to_string());
+    assert_eq!(notifier.max_upload_bytes(), Some(1_000_000_000));
+    notifier
+        .send(&alert.clone().with_image(Some(&image_bytes), "print_pause"))
+        .expect("Failed to post to webhook");
+
+    let (url, body, extra_request) = handle.join().unwrap();
+    assert_eq!(url, "/services/T0/B0/x");
+    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["text"], "🚨 Print Paused Due to Multiple Failures");
+    assert_eq!(body["blocks"][0]["type"], "header");
+    assert!(body.get("channel").is_none());
+    assert!(!extra_request);
+}
+
+#[test]
+fn test_push_notifiers() {
+    use print_guardian::notifiers::{Alert, AlertKind, GotifyNotifier, NtfyNotifier};
+
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+    let pause = Alert::new(
+        AlertKind::PrintPaused,
+        "Print Paused",
+        "Paused after 3 failures",
+    )
+    .with_field("Event", "#4")
+    .with_image(Some(&image_bytes), "print_pause");
+    let status = Alert::new(
+        AlertKind::PrinterStatus,
+        "Printing",
+        "Current printer state: printing",
+    );
+
+    let ntfy = NtfyNotifier::new(
+        "https://ntfy.sh/printer".to_string(),
+        None,
+        Some("http://printer.local/".to_string()),
+    );
+    let params = ntfy.query_params(&pause);
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.clone())
+    };
+    assert_eq!(param("priority").as_deref(), Some("5"));
+    assert_eq!(param("tags").as_deref(), Some("rotating_light,pause"));
+    assert_eq!(param("click").as_deref(), Some("http://printer.local/"));
+    assert!(param("filename").unwrap().ends_with(".jpg"));
+    assert!(param("message").unwrap().contains("Event: #4"));
+    assert_eq!(
+        ntfy.query_params(&status)
+            .iter()
+            .find(|(key, _)| *key == "priority")
+            .map(|(_, value)| value.as_str()),
+        Some("2")
+    );
+
+    // Every alert kind's emoji is sent as an ntfy shortcode, not as raw text
+    for kind in AlertKind::ALL {
+        let params = ntfy.query_params(&Alert::new(kind, "Title", "Description"));
+        let tags = params
+            .iter()
+            .find(|(key, _)| *key == "tags")
+            .map(|(_, value)| value.clone())
+            .unwrap();
+        let emoji_tag = tags.split(',').next().unwrap();
+        assert!(
+            emoji_tag
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c == '_'),
+            "{} alerts use the unmapped emoji tag '{}'",
+            kind.as_str(),
+            emoji_tag
+        );
+    }
+
+    let gotify = GotifyNotifier::new(
+        "https://gotify.local/".to_string(),
+        "token".to_string(),
+        Some("http://printer.local/".to_string()),
+    );
+    let message = gotify.build_message(&pause);
+    assert_eq!(message["priority"], 10);
+    assert_eq!(message["title"], "🚨 Print Paused");
+    assert_eq!(
+        message["extras"]["client::notification"]["click"]["url"],
+        "http://printer.local/"
+    );
+    assert!(
+        message["message"]
+            .as_str()
+            .unwrap()
+            .contains("](data:image/jpeg;base64,")
+    );
+    assert_eq!(gotify.build_message(&status)["priority"], 2);
+}
+
+/// Minimal SMTP server accepting every message and passing its data to the test.
+fn start_smtp_stand_in(address: &str) -> std::sync::mpsc::Receiver<String> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind(address).expect("Failed to bind SMTP stand-in");
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            stream.write_all(b"220 localhost ESMTP\r\n").unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                let command = line.trim_end().to_uppercase();
+                if command.starts_with("DATA") {
+                    stream
+                        .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                        .unwrap();
+                    let mut data = String::new();
+                    loop {
+                        let mut data_line = String::new();
+                        reader.read_line(&mut data_line).unwrap();
+                        if data_line == ".\r\n" {
+                            break;
+                        }
+                        data.push_str(&data_line);
+                    }
+                    sender.send(data).unwrap();
+                    stream.write_all(b"250 OK queued\r\n").unwrap();
+                } else if command.starts_with("QUIT") {
+                    stream.write_all(b"221 Bye\r\n").unwrap();
+                    break;
+                } else {
+                    stream.write_all(b"250 OK\r\n").unwrap();
+                }
+                line.clear();
+            }
+        }
+    });
+    receiver
+}
+
+#[test]
+fn test_email_notifier_with_digest() {
+    use print_guardian::notifiers::{
+        Alert, AlertKind, EmailNotifier, Notifier, SmtpSecurity, SmtpSettings,
+    };
+    use std::time::Duration;
+
+    let messages = start_smtp_stand_in("127.0.0.1:18791");
+    let dir = std::env::temp_dir().join(format!("print-guardian-digest-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let create_notifier = || {
+        EmailNotifier::new(
+            SmtpSettings {
+                host: "127.0.0.1".to_string(),
+                port: 18791,
+                security: SmtpSecurity::None,
+                username: None,
+                password: None,
+                from: "Print Guardian <guardian@example.com>".to_string(),
+                to: vec!["ops@example.com".to_string()],
+            },
+            Some(Duration::from_secs(3600)),
+        )
+        .expect("Failed to create email notifier")
+        .with_digest_dir(&dir)
+        .expect("Failed to open digest directory")
+    };
+    let notifier = create_notifier();
+
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+    let failure = Alert::new(
+        AlertKind::PrintFailure,
+        "Print Failure Detected",
+        "spaghetti",
+    )
+    .with_field("Confidence", "87.50%")
+    .with_image(Some(&image_bytes), "failure_detection");
+    let offline = Alert::new(
+        AlertKind::SystemOffline,
+        "CRITICAL: Print Monitoring Offline",
+        "Camera <unreachable>",
+    );
+
+    // Non-critical alerts wait for the digest, critical alerts are sent right away
+    notifier.send(&failure).unwrap();
+    assert_eq!(notifier.pending_digest_alerts(), 1);
+    notifier.send(&offline).unwrap();
+    let email = messages
+        .recv_timeout(Duration::from_secs(10))
+        .expect("Critical alert not sent");
+    assert!(email.contains("multipart/alternative"));
+    assert!(email.contains("text/plain"));
+    assert!(email.contains("Camera &lt;unreachable&gt;"));
+
+    // The digest is not due yet
+    notifier.flush().unwrap();
+    assert!(messages.recv_timeout(Duration::from_millis(200)).is_err());
+
+    // Pending digest alerts survive a restart
+    drop(notifier);
+    let notifier = create_notifier();
+    assert_eq!(notifier.pending_digest_alerts(), 1);
+
+    notifier.send_digest().unwrap();
+    assert_eq!(notifier.pending_digest_alerts(), 0);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    let digest = messages
+        .recv_timeout(Duration::from_secs(10))
+        .expect("Digest not sent");
+    assert!(digest.contains("Print Guardian digest: 1 alert(s)"));
+    assert!(digest.contains("multipart/related"));
+    assert!(digest.contains("Content-ID: <image0>"));
+    assert!(digest.contains("cid:image0"));
+
+    // The subject is rendered from the locale's templates
+    let notifier = create_notifier().with_templates(
+        print_guardian::templates::MessageTemplates::load("de", None).unwrap(),
+    );
+    notifier.send(&failure).unwrap();
+    notifier.send_digest().unwrap();
+    let digest = messages
+        .recv_timeout(Duration::from_secs(10))
+        .expect("Digest not sent");
+    assert!(digest.contains("Print Guardian Sammelmail: 1 Benachrichtigung(en)"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_mqtt_discovery_and_commands() {
+    use print_guardian::PrinterService;
+    use print_guardian::mqtt::{MqttBridge, MqttSettings, state_payload};
+    use print_guardian::state::{self, MonitorState};
+    use std::sync::{Arc, Mutex};
+
+    let state = Arc::new(Mutex::new(MonitorState::default()));
+    let bridge = MqttBridge::new(
+        state.clone(),
+        PrinterService::new("http://localhost:7125".to_string()),
+        MqttSettings {
+            host: "localhost".to_string(),
+            port: 1883,
+            username: None,
+            password: None,
+            discovery_prefix: "homeassistant".to_string(),
+            node_id: "voron".to_string(),
+        },
+    );
+
+    let configs = bridge.discovery_configs();
+    let find = |object_id: &str| {
+        configs
+            .iter()
+            .find(|(_, id, _)| *id == object_id)
+            .map(|(component, _, config)| (*component, config.clone()))
+            .unwrap()
+    };
+    let (component, image) = find("latest_frame");
+    assert_eq!(component, "image");
+    assert_eq!(image["image_topic"], "print_guardian/voron/image");
+    let (component, pause) = find("pause");
+    assert_eq!(component, "button");
+    assert_eq!(pause["command_topic"], "print_guardian/voron/command/pause");
+    assert_eq!(pause["device"]["identifiers"][0], "print_guardian_voron");
+    assert_eq!(find("camera").0, "binary_sensor");
+
+    // Snooze button presses use the default duration, numbers override it
+    bridge.handle_command("print_guardian/voron/command/snooze", "PRESS");
+    assert!(state::lock(&state).alerts_snoozed());
+    bridge.handle_command("print_guardian/other/command/snooze", "5");
+    let payload = state_payload(&state::lock(&state));
+    assert_eq!(payload["alerts_snoozed"], true);
+    assert_eq!(payload["camera_available"], false);
+}
+
+#[test]
+fn test_webhook_templates_and_signing() {
+    use print_guardian::notifiers::{
+        Alert, AlertDetection, AlertKind, Notifier, WebhookNotifier, WebhookSettings,
+    };
+    use std::collections::BTreeMap;
+    use print_guardian::state::MonitorState;
+    use print_guardian::{ControlApi, FeedbackService, HttpServer, PrinterService};
+    use std::sync::{Arc, Mutex};
+
+    let settings = WebhookSettings {
+        url: "http://127.0.0.1:18792/incidents".to_string(),
+        method_template: "{{#if bbox}}post{{else}}put{{/if}}".to_string(),
+        body_template: Some(
+            r#"{"printer": "{{printer}}", "summary": "{{title}}", "severity": "{{severity}}", "label": "{{label}}", "bbox": {{{json bbox}}}, "image": "{{image_url}}"}"#
+                .to_string(),
+        ),
+        header_templates: BTreeMap::from([(
+            "X-Incident-Source".to_string(),
+            "print-guardian/{{printer}}".to_string(),
+        )]),
+        secret: Some("s3cret".to_string()),
+        printer_name: "voron \"2.4\"".to_string(),
+        public_url: Some("https://guardian.example.com/".to_string()),
+    };
+    let image_dir =
+        std::env::temp_dir().join(format!("print-guardian-alert-images-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&image_dir);
+    let notifier = WebhookNotifier::new(settings)
+        .expect("Invalid templates")
+        .with_image_dir(&image_dir)
+        .expect("Failed to create image directory")
+        .with_image_signing_key(Some("secret".to_string()));
+
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+    let alert = Alert::new(
+        AlertKind::PrintFailure,
+        "Print Failure Detected",
+        "spaghetti",
+    )
+    .with_detection(AlertDetection {
+        label: "spaghetti".to_string(),
+        confidence: 87.5,
+        x: 10.0,
+        y: 20.0,
+        w: 30.0,
+        h: 40.0,
+    })
+    .with_image(Some(&image_bytes), "failure_detection");
+
+    let request = notifier.render(&alert).unwrap();
+    let body = request.body;
+    assert_eq!(request.method, "POST");
+    assert_eq!(
+        request.headers,
+        vec![(
+            "X-Incident-Source".to_string(),
+            "print-guardian/voron \"2.4\"".to_string()
+        )]
+    );
+    // Values are escaped for JSON, so the body stays valid
+    let parsed: serde_json::Value = serde_json::from_str(&body).expect("Body is not valid JSON");
+    assert_eq!(parsed["printer"], "voron \"2.4\"");
+    assert_eq!(parsed["severity"], "warning");
+    assert_eq!(parsed["bbox"]["w"], 30.0);
+    // The image link points at this alert's frame and is signed
+    let file_name = WebhookNotifier::image_file_name(&alert).unwrap();
+    assert!(file_name.ends_with(".jpg"));
+    assert_eq!(
+        parsed["image"],
+        format!(
+            "https://guardian.example.com/alerts/{}?sig={}",
+            file_name,
+            WebhookNotifier::image_signature("secret", &file_name)
+        )
+    );
+
+    // Local receiver verifying the signature
+    let server = tiny_http::Server::http("127.0.0.1:18792").expect("Failed to bind test server");
+    let handle = std::thread::spawn(move || {
+        let mut request = server.recv().expect("Failed to receive request");
+        let signature = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("X-Print-Guardian-Signature"))
+            .map(|h| h.value.to_string());
+        let mut received = String::new();
+        request.as_reader().read_to_string(&mut received).unwrap();
+        request.respond(tiny_http::Response::empty(204)).unwrap();
+        (signature, received)
+    });
+
+    notifier.send(&alert).expect("Failed to send webhook");
+    let (signature, received) = handle.join().unwrap();
+    assert_eq!(received, body);
+    assert_eq!(
+        signature.unwrap(),
+        WebhookNotifier::sign("s3cret", received.as_bytes())
+    );
+    assert!(WebhookNotifier::sign("s3cret", b"").starts_with("sha256="));
+
+    // The stored image is served with the signature, but not without it
+    assert_eq!(
+        std::fs::read(image_dir.join(&file_name)).unwrap(),
+        image_bytes
+    );
+    let state = Arc::new(Mutex::new(MonitorState::default()));
+    let api = ControlApi::new(
+        state.clone(),
+        Arc::new(Mutex::new(FeedbackService::new(
+            std::env::temp_dir().join("print-guardian-webhook-negatives"),
+            5,
+        ))),
+        PrinterService::new("http://localhost:7125".to_string()),
+        Some("secret".to_string()),
+    );
+    HttpServer::new(state, api)
+        .with_alert_image_dir(&image_dir)
+        .start("127.0.0.1:18803")
+        .expect("Failed to start HTTP server");
+    let url = format!("http://127.0.0.1:18803/alerts/{}", file_name);
+    let signed = reqwest::blocking::get(format!(
+        "{}?sig={}",
+        url,
+        WebhookNotifier::image_signature("secret", &file_name)
+    ))
+    .expect("Request failed");
+    assert_eq!(signed.status().as_u16(), 200);
+    assert_eq!(signed.bytes().unwrap().as_ref(), image_bytes.as_slice());
+    let unsigned = reqwest::blocking::get(&url).expect("Request failed");
+    assert_eq!(unsigned.status().as_u16(), 401);
+    let forged = reqwest::blocking::get(format!("{}?sig=00", url)).expect("Request failed");
+    assert_eq!(forged.status().as_u16(), 401);
+    let _ = std::fs::remove_dir_all(&image_dir);
+}
+
+#[test]
+fn test_alert_queue_survives_restart_and_honors_rate_limits() {
+    use print_guardian::notifiers::RateLimited;
+    use print_guardian::{Alert, AlertKind, AlertQueue, AlertService, Notifier};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    struct FlakyNotifier {
+        sent: Arc<Mutex<Vec<(Instant, Alert)>>>,
+        rate_limited_once: Mutex<bool>,
+    }
+
+    impl Notifier for FlakyNotifier {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            let mut rate_limited = self.rate_limited_once.lock().unwrap();
+            if !*rate_limited {
+                *rate_limited = true;
+                return Err(RateLimited {
+                    retry_after: Duration::from_millis(500),
+                }
+                .into());
+            }
+            self.sent
+                .lock()
+                .unwrap()
+                .push((Instant::now(), alert.clone()));
+            Ok(())
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("print-guardian-queue-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+
+    // Queue an alert without a worker, as if the process stopped before delivery
+    {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let service = AlertService::new(vec![Box::new(FlakyNotifier {
+            sent: sent.clone(),
+            rate_limited_once: Mutex::new(false),
+        })])
+        .with_queue(AlertQueue::open(&dir).expect("Failed to open queue"));
+        service
+            .send_print_pause_alert(
+                4,
+                12,
+                &print_guardian::printer::PauseOutcome::Paused { attempts: 1 },
+                Some(&image_bytes),
+            )
+            .expect("Failed to queue alert");
+        assert_eq!(service.queue_depth(), 1);
+        assert!(sent.lock().unwrap().is_empty());
+    }
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    // After a restart the alert is delivered once the rate limit has passed
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let queue = AlertQueue::open(&dir)
+        .expect("Failed to reopen queue")
+        .with_backoff(Duration::from_millis(100), Duration::from_secs(1));
+    assert_eq!(queue.len(), 1);
+    let service = AlertService::new(vec![Box::new(FlakyNotifier {
+        sent: sent.clone(),
+        rate_limited_once: Mutex::new(false),
+    })])
+    .with_queue(queue);
+    let started = Instant::now();
+    service.start_worker().expect("Failed to start worker");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while service.queue_depth() > 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let sent = sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    let (delivered_at, alert) = &sent[0];
+    assert!(delivered_at.duration_since(started) >= Duration::from_millis(500));
+    assert_eq!(alert.kind, AlertKind::PrintPaused);
+    assert_eq!(alert.image.as_ref().unwrap().data, image_bytes);
+    assert!(
+        alert
+            .fields
+            .iter()
+            .any(|f| f.name == "Event" && f.value == "#12")
+    );
+    assert_eq!(service.queue_depth(), 0);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_alert_queue_retries_only_failed_delivery_steps() {
+    use print_guardian::notifiers::TelegramNotifier;
+    use print_guardian::{Alert, AlertKind, AlertQueue, AlertService};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    // Bot API stand-in that rejects the first message to the second chat
+    let server = tiny_http::Server::http("127.0.0.1:18800").expect("Failed to bind test server");
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let recorded = received.clone();
+    std::thread::spawn(move || {
+        let mut rejected = false;
+        for mut request in server.incoming_requests() {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            let chat_id = body["chat_id"].as_str().unwrap().to_string();
+            if chat_id == "-1002" && !rejected {
+                rejected = true;
+                let _ = request.respond(
+                    tiny_http::Response::from_string(r#"{"ok":false}"#).with_status_code(502),
+                );
+                continue;
+            }
+            recorded.lock().unwrap().push(chat_id);
+            let _ = request.respond(tiny_http::Response::from_string(r#"{"ok":true}"#));
+        }
+    });
+
+    let dir = std::env::temp_dir().join(format!("print-guardian-steps-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let queue = AlertQueue::open(&dir)
+        .expect("Failed to open queue")
+        .with_backoff(Duration::from_millis(100), Duration::from_secs(1));
+    let notifier = TelegramNotifier::new(
+        "123:abc".to_string(),
+        vec!["-1001".to_string(), "-1002".to_string()],
+    )
+    .with_api_url("http://127.0.0.1:18800".to_string());
+    let service = AlertService::new(vec![Box::new(notifier)]).with_queue(queue);
+    service
+        .dispatch(&Alert::new(AlertKind::SystemOffline, "Offline", "Camera offline"))
+        .expect("Failed to queue alert");
+    service.start_worker().expect("Failed to start worker");
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while service.queue_depth() > 0 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    // The chat that received the alert on the first attempt is not sent it again
+    assert_eq!(service.queue_depth(), 0);
+    assert_eq!(*received.lock().unwrap(), vec!["-1001", "-1002"]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_alert_routing_and_quiet_hours() {
+    use chrono::NaiveTime;
+    use print_guardian::routing::{self, QuietHours, RoutingRules};
+    use print_guardian::{Alert, AlertKind, AlertService, Notifier};
+    use std::sync::{Arc, Mutex};
+
+    struct NamedNotifier {
+        name: &'static str,
+        sent: Arc<Mutex<Vec<(&'static str, AlertKind)>>>,
+    }
+
+    impl Notifier for NamedNotifier {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push((self.name, alert.kind));
+            Ok(())
+        }
+    }
+
+    let time = |s: &str| NaiveTime::parse_from_str(s, "%H:%M").unwrap();
+    let night: QuietHours = "22:00-07:00".parse().expect("Invalid quiet hours");
+    assert!(night.contains(time("23:30")));
+    assert!(night.contains(time("06:59")));
+    assert!(!night.contains(time("07:00")));
+    assert!(!night.contains(time("12:00")));
+    assert!("22:00".parse::<QuietHours>().is_err());
+    assert!(RoutingRules::default().parse_routes("log=bogus").is_err());
+
+    // Quiet hours for the owner channel around the current time
+    let now = chrono::Local::now().time();
+    let quiet_now = QuietHours {
+        start: now - chrono::Duration::hours(1),
+        end: now + chrono::Duration::hours(1),
+    };
+    let rules = RoutingRules::default()
+        .parse_routes("owner=failure,critical;log=status")
+        .expect("Invalid routes")
+        .with_quiet_hours("owner", quiet_now);
+    assert_eq!(rules.destinations(), vec!["log", "owner"]);
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let notifier = |name| -> Box<dyn Notifier> {
+        Box::new(NamedNotifier {
+            name,
+            sent: sent.clone(),
+        })
+    };
+    let service = AlertService::new(vec![
+        notifier("owner"),
+        notifier("log"),
+        notifier("everyone"),
+    ])
+    .with_routing(rules);
+
+    service
+        .send_system_offline_alert("http://camera.local", 3)
+        .unwrap();
+    service
+        .send_print_failure_alert(1, "spaghetti", 80.0, 1.0, 2.0, 3.0, 4.0, None)
+        .unwrap();
+    service
+        .send_printer_status_alert(
+            &print_guardian::printer_status::PrinterStatus::default(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    let mut sent = sent.lock().unwrap().clone();
+    sent.sort_by_key(|(name, kind)| (*name, kind.as_str()));
+    assert_eq!(
+        sent,
+        vec![
+            ("everyone", AlertKind::PrintFailure),
+            ("everyone", AlertKind::SystemOffline),
+            ("everyone", AlertKind::PrinterStatus),
+            ("log", AlertKind::PrinterStatus),
+            // The failure warning is held during quiet hours, the critical alert is not
+            ("owner", AlertKind::SystemOffline),
+        ]
+    );
+    assert_eq!(service.held_alerts(), 1);
+
+    // Still quiet, so nothing is released yet
+    service.flush();
+    assert_eq!(service.held_alerts(), 1);
+
+    // With a queue, held alerts are stored until the end of the quiet hours
+    let dir = std::env::temp_dir().join(format!("print-guardian-held-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let failure = Alert::new(AlertKind::PrintFailure, "Print Failure Detected", "Earlier");
+    let queue = print_guardian::AlertQueue::open(&dir).expect("Failed to open queue");
+    queue
+        .hold("owner", &failure, chrono::Utc::now() - chrono::Duration::minutes(1))
+        .expect("Failed to hold alert");
+    let queued_service = AlertService::new(vec![notifier("owner")])
+        .with_routing(RoutingRules::default().with_quiet_hours("owner", quiet_now))
+        .with_queue(queue);
+    queued_service
+        .send_print_failure_alert(2, "spaghetti", 80.0, 1.0, 2.0, 3.0, 4.0, None)
+        .unwrap();
+    assert_eq!(queued_service.held_alerts(), 2);
+    assert_eq!(queued_service.queue_depth(), 0);
+
+    // The alert whose quiet hours are over is sent as a summary, the other survives a restart
+    queued_service.flush();
+    assert_eq!(queued_service.held_alerts(), 1);
+    assert_eq!(queued_service.queue_depth(), 1);
+    let reopened = print_guardian::AlertQueue::open(&dir).expect("Failed to reopen queue");
+    assert_eq!(reopened.held_len(), 1);
+    assert_eq!(reopened.len(), 1);
+    assert!(reopened.due().iter().all(|entry| entry.not_before.is_none()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // Alerts held in memory are kept when their summary cannot be sent
+    struct FlakyNotifier {
+        failures: Mutex<usize>,
+        sent: Arc<Mutex<Vec<(&'static str, AlertKind)>>>,
+    }
+
+    impl Notifier for FlakyNotifier {
+        fn name(&self) -> &str {
+            "owner"
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                anyhow::bail!("owner unreachable");
+            }
+            self.sent.lock().unwrap().push(("owner", alert.kind));
+            Ok(())
+        }
+    }
+
+    let now = chrono::Local::now().time();
+    let ending_soon = QuietHours {
+        start: now - chrono::Duration::hours(1),
+        end: now + chrono::Duration::seconds(1),
+    };
+    let flaky_sent = Arc::new(Mutex::new(Vec::new()));
+    let flaky_service = AlertService::new(vec![Box::new(FlakyNotifier {
+        failures: Mutex::new(1),
+        sent: flaky_sent.clone(),
+    })])
+    .with_routing(RoutingRules::default().with_quiet_hours("owner", ending_soon));
+    flaky_service
+        .send_print_failure_alert(3, "spaghetti", 80.0, 1.0, 2.0, 3.0, 4.0, None)
+        .unwrap();
+    assert_eq!(flaky_service.held_alerts(), 1);
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    flaky_service.flush();
+    assert_eq!(flaky_service.held_alerts(), 1);
+    assert!(flaky_sent.lock().unwrap().is_empty());
+    flaky_service.flush();
+    assert_eq!(flaky_service.held_alerts(), 0);
+    assert_eq!(
+        *flaky_sent.lock().unwrap(),
+        vec![("owner", AlertKind::PrinterStatus)]
+    );
+
+    let failure = Alert::new(
+        AlertKind::PrintFailure,
+        "Print Failure Detected",
+        "Detected spaghetti",
+    );
+    let summary = routing::summarize(
+        Alert::new(AlertKind::PrinterStatus, "Quiet Hours Summary", ""),
+        &[failure.clone(), failure],
+    );
+    assert_eq!(summary.title, "Quiet Hours Summary");
+    assert_eq!(summary.fields.len(), 2);
+    assert!(summary.fields[0].name.ends_with("Print Failure Detected"));
+    assert_eq!(summary.fields[0].value, "Detected spaghetti");
+}
+
+#[test]
+fn test_discord_edits_print_job_message_in_place() {
+    use print_guardian::notifiers::{DiscordNotifier, PrintJob};
+    use print_guardian::{AlertService, Notifier};
+
+    // Local stand-in for the Discord webhook API
+    let server = tiny_http::Server::http("127.0.0.1:18793").expect("Failed to bind test server");
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for _ in 0..4 {
+            let mut request = server.recv().expect("Failed to receive request");
+            let method = request.method().to_string();
+            let url = request.url().to_string();
+            let mut body = Vec::new();
+            request.as_reader().read_to_end(&mut body).unwrap();
+            let body = String::from_utf8_lossy(&body).to_string();
+            let response = if url.ends_with("?wait=true") {
+                tiny_http::Response::from_string(r#"{"id":"42"}"#)
+            } else {
+                tiny_http::Response::from_string("")
+            };
+            request.respond(response).unwrap();
+            requests.push((method, url, body));
+        }
+        requests
+    });
+
+    let notifier = DiscordNotifier::new("http://127.0.0.1:18793/api/webhooks/1/token".to_string());
+    assert!(notifier.updates_in_place());
+    let service = AlertService::new(vec![Box::new(notifier)]);
+
+    let status = print_guardian::printer_status::PrinterStatus::from_query_response(
+        &serde_json::json!({
+            "result": {"status": {
+                "print_stats": {"state": "printing", "filename": "benchy.gcode", "print_duration": 600.0},
+                "virtual_sdcard": {"progress": 0.25},
+            }}
+        }),
+    )
+    .unwrap();
+    let job = PrintJob {
+        id: "benchy.gcode-1".to_string(),
+        finished: false,
+    };
+    let image_bytes = std::fs::read("tests/test_failure.jpg").expect("Test image missing");
+
+    service
+        .send_printer_status_alert(&status, None, Some(&job))
+        .expect("Failed to create job message");
+    service
+        .send_print_progress_alert(&job, &status, 0.12, Some(&image_bytes))
+        .expect("Failed to update job message");
+    service
+        .send_printer_status_alert(
+            &status,
+            None,
+            Some(&PrintJob {
+                finished: true,
+                ..job.clone()
+            }),
+        )
+        .expect("Failed to finish job message");
+    // Alerts without a job are posted as new messages
+    service
+        .send_system_recovery_alert()
+        .expect("Failed to send alert");
+
+    let requests = handle.join().unwrap();
+    assert_eq!(requests[0].0, "POST");
+    assert_eq!(requests[0].1, "/api/webhooks/1/token?wait=true");
+
+    assert_eq!(requests[1].0, "PATCH");
+    assert_eq!(requests[1].1, "/api/webhooks/1/token/messages/42");
+    assert!(requests[1].2.contains("Printing benchy.gcode"));
+    assert!(requests[1].2.contains("25.0%"));
+    assert!(requests[1].2.contains("\"attachments\""));
+    assert!(requests[1].2.contains("12.0%"));
+
+    assert_eq!(requests[2].0, "PATCH");
+    assert_eq!(requests[2].1, "/api/webhooks/1/token/messages/42");
+
+    assert_eq!(requests[3].0, "POST");
+    assert_eq!(requests[3].1, "/api/webhooks/1/token");
+}
+
+#[test]
+fn test_localized_alert_templates() {
+    use print_guardian::templates::MessageTemplates;
+    use print_guardian::{Alert, AlertService, Notifier};
+    use std::sync::{Arc, Mutex};
+
+    struct RecordingNotifier {
+        sent: Arc<Mutex<Vec<Alert>>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    assert!(MessageTemplates::load("xx", None).is_err());
+
+    // Custom file overriding part of the bundled German templates
+    let dir = std::env::temp_dir().join(format!("print-guardian-templates-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("de.json"),
+        r##"{"alerts": {"failure": {"title": "Druck kaputt: {{label}}", "color": "#00FF00", "emoji": "🔥"}}}"##,
+    )
+    .unwrap();
+
+    let templates = MessageTemplates::load("de", Some(&dir)).expect("Failed to load templates");
+    assert_eq!(templates.locale(), "de");
+    assert_eq!(templates.label("confidence"), "Konfidenz");
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let service = AlertService::new(vec![Box::new(RecordingNotifier { sent: sent.clone() })])
+        .with_templates(templates);
+    service
+        .send_print_failure_alert(1, "spaghetti", 80.0, 1.0, 2.0, 3.0, 4.0, None)
+        .unwrap();
+    service.send_system_recovery_alert().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let sent = sent.lock().unwrap();
+    assert_eq!(sent[0].title, "Druck kaputt: spaghetti");
+    assert!(sent[0].description.contains("80.00"));
+    assert_eq!(sent[0].color, 0x00FF00);
+    assert_eq!(sent[0].emoji, "🔥");
+    assert_eq!(sent[0].fields[1].name, "Konfidenz");
+    // Keys missing from the custom file come from the bundled translation
+    assert!(sent[1].title.starts_with("WIEDERHERGESTELLT"));
+}
+
+#[test]
+fn test_alert_clip_from_recent_frames() {
+    use print_guardian::clip::{ClipRecorder, encode_clip};
+    use std::time::Duration;
+
+    // Small frames keep encoding fast in unoptimized test builds
+    let frame = {
+        let image = image::open("tests/test_failure.jpg").expect("Test image missing");
+        let mut data = Vec::new();
+        image
+            .thumbnail(320, 320)
+            .write_to(
+                &mut std::io::Cursor::new(&mut data),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+        data
+    };
+
+    let mut clips = ClipRecorder::new(Duration::from_secs(30), 8_000_000);
+    clips.record(1, frame.clone());
+    // A single frame is no clip
+    assert!(clips.clip(1).is_none());
+    assert!(clips.clip(0).is_none());
+
+    clips.record(1, frame.clone());
+    clips.record(1, frame.clone());
+    let clip = clips.clip(1).expect("Failed to render clip");
+    assert_eq!(image::guess_format(&clip).unwrap(), image::ImageFormat::Gif);
+    assert!(clip.len() <= 8_000_000);
+
+    // Clips over the limit are shrunk until they fit
+    let frames = vec![frame.as_slice(); 8];
+    let full = encode_clip(&frames, 200, usize::MAX).unwrap().unwrap();
+    let capped = encode_clip(&frames, 200, full.len() / 2)
+        .unwrap()
+        .expect("Clip should fit after shrinking");
+    assert!(capped.len() <= full.len() / 2);
+    // and dropped when they cannot fit at all
+    assert!(encode_clip(&frames, 200, 100).unwrap().is_none());
+}
+
+#[test]
+fn test_print_report_summarizes_monitoring_history() {
+    use print_guardian::notifiers::PrintJob;
+    use print_guardian::report::PrintReport;
+    use print_guardian::{Alert, AlertKind, AlertService, Notifier};
+    use std::sync::{Arc, Mutex};
+
+    struct RecordingNotifier {
+        sent: Arc<Mutex<Vec<Alert>>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    let frame = {
+        let image = image::open("tests/test_failure.jpg").expect("Test image missing");
+        let mut data = Vec::new();
+        image
+            .thumbnail(320, 320)
+            .write_to(
+                &mut std::io::Cursor::new(&mut data),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+        data
+    };
+
+    let job = PrintJob {
+        id: "benchy.gcode-1700000000".to_string(),
+        finished: false,
+    };
+    let mut report = PrintReport::new(job, "benchy.gcode");
+    for score in [0.0, 0.1, 0.3, 0.2, 0.7, 0.9, 0.05, 0.4, 0.6] {
+        let detections = usize::from(score > 0.5);
+        report.record_frame(score, detections, &frame);
+    }
+    report.record_alert();
+    report.record_alert();
+    report.record_pause();
+    report.record_camera(false);
+    report.record_camera(true);
+    report.finish("complete");
+
+    assert_eq!(report.frames_analysed(), 9);
+    assert_eq!(report.detections(), 3);
+    assert!((report.max_score() - 0.9).abs() < f32::EPSILON);
+    // Only the most suspicious frames are kept, highest score first
+    let scores: Vec<f32> = report.suspicious_frames().iter().map(|f| f.score).collect();
+    assert_eq!(scores, vec![0.9, 0.7, 0.6, 0.4, 0.3, 0.2]);
+
+    let dir = std::env::temp_dir().join(format!("print-guardian-reports-{}", std::process::id()));
+    let saved = report.save(&dir).expect("Failed to save report");
+    let json: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(saved.join("report.json")).unwrap()).unwrap();
+    assert_eq!(json["final_state"], "complete");
+    assert_eq!(json["alerts_sent"], 2);
+    assert_eq!(json["pauses"], 1);
+    assert_eq!(
+        image::guess_format(&std::fs::read(saved.join("chart.png")).unwrap()).unwrap(),
+        image::ImageFormat::Png
+    );
+    assert!(saved.join("mosaic.jpg").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let service = AlertService::new(vec![Box::new(RecordingNotifier { sent: sent.clone() })]);
+    let image = report
+        .render_summary_image()
+        .expect("Failed to render summary");
+    service
+        .send_print_report_alert(&report, Some(&image))
+        .unwrap();
+
+    let sent = sent.lock().unwrap();
+    assert_eq!(sent[0].kind, AlertKind::PrintReport);
+    assert_eq!(sent[0].title, "Print Report: benchy.gcode");
+    assert!(
+        sent[0]
+            .fields
+            .iter()
+            .any(|f| f.name == "Pauses" && f.value == "1")
+    );
+    assert!(sent[0].image.is_some());
+}
+
+#[test]
+fn test_fleet_digest_schedule_and_delivery() {
+    use chrono::{Datelike, TimeZone, Weekday};
+    use print_guardian::digest::{DigestSchedule, DigestScheduler};
+    use print_guardian::routing::RoutingRules;
+    use print_guardian::{AlertKind, AlertService, Notifier};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct NamedNotifier {
+        name: &'static str,
+        sent: Arc<Mutex<Vec<(&'static str, print_guardian::Alert)>>>,
+    }
+
+    impl Notifier for NamedNotifier {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn send(&self, alert: &print_guardian::Alert) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push((self.name, alert.clone()));
+            Ok(())
+        }
+    }
+
+    assert!("hourly 08:00".parse::<DigestSchedule>().is_err());
+    assert!("weekly someday 08:00".parse::<DigestSchedule>().is_err());
+
+    // Wednesday noon
+    let now = chrono::Local
+        .with_ymd_and_hms(2026, 3, 4, 12, 0, 0)
+        .unwrap();
+    let daily: DigestSchedule = "daily 08:00".parse().unwrap();
+    let next = daily.next_after(now);
+    assert_eq!(
+        (next.day(), next.format("%H:%M").to_string()),
+        (5, "08:00".to_string())
+    );
+    let weekly: DigestSchedule = "weekly mon 08:00".parse().unwrap();
+    let next = weekly.next_after(now);
+    assert_eq!((next.weekday(), next.day()), (Weekday::Mon, 9));
+
+    let mut scheduler = DigestScheduler::new("voron", daily);
+    scheduler.record_print_finished("complete");
+    scheduler.record_print_finished("cancelled");
+    scheduler.record_failure();
+    scheduler.record_failure();
+    scheduler.record_pause();
+    scheduler.record_inference(Duration::from_millis(100));
+    scheduler.record_inference(Duration::from_millis(300));
+    scheduler.record_moonraker(true);
+    scheduler.record_camera(true);
+    std::thread::sleep(Duration::from_millis(5));
+
+    assert!(scheduler.poll(chrono::Local::now(), 1).is_none());
+    let due = scheduler.next_run();
+    let digest = scheduler.poll(due, 1).expect("Digest should be due");
+    assert_eq!(digest.prints_completed, 1);
+    assert_eq!(digest.failures, 2);
+    assert_eq!(digest.pauses, 1);
+    assert_eq!(digest.false_positives, 1);
+    assert_eq!(
+        digest.average_inference_time(),
+        Some(Duration::from_millis(200))
+    );
+    assert_eq!(digest.camera.percent(), Some(100.0));
+    // The next period starts from scratch
+    assert!(scheduler.next_run() > due);
+    let next = scheduler.poll(scheduler.next_run(), 1).unwrap();
+    assert_eq!((next.failures, next.false_positives), (0, 0));
+
+    // Digests only reach the configured notifiers
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let notifier = |name| -> Box<dyn Notifier> {
+        Box::new(NamedNotifier {
+            name,
+            sent: sent.clone(),
+        })
+    };
+    let service = AlertService::new(vec![notifier("discord-farm"), notifier("telegram")])
+        .with_routing(
+            RoutingRules::default().with_digest_destinations(vec!["discord-farm".to_string()]),
+        );
+    service.send_fleet_digest_alert(&digest).unwrap();
+
+    let sent = sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    let (name, alert) = &sent[0];
+    assert_eq!(*name, "discord-farm");
+    assert_eq!(alert.kind, AlertKind::FleetDigest);
+    assert_eq!(alert.title, "Daily Digest: voron");
+    assert!(
+        alert
+            .fields
+            .iter()
+            .any(|f| f.name == "Average Inference Time" && f.value == "200 ms")
+    );
+}
+
+#[test]
+fn test_alert_images_fit_upload_limits() {
+    use print_guardian::{Alert, AlertService, Notifier};
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    struct LimitedNotifier {
+        name: &'static str,
+        limit: Option<usize>,
+        sent: Arc<Mutex<Vec<(&'static str, Alert)>>>,
+    }
+
+    impl Notifier for LimitedNotifier {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push((self.name, alert.clone()));
+            Ok(())
+        }
+
+        fn max_upload_bytes(&self) -> Option<usize> {
+            self.limit
+        }
+    }
+
+    // Noise compresses badly, so the PNG is far over the limits
+    let mut seed: u32 = 42;
+    let noise = image::RgbImage::from_fn(800, 600, |_, _| {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let [r, g, b, _] = seed.to_le_bytes();
+        image::Rgb([r, g, b])
+    });
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgb8(noise)
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            image::ImageOutputFormat::Png,
+        )
+        .unwrap();
+    assert!(png.len() > 1_000_000);
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let notifier = |name, limit| -> Box<dyn Notifier> {
+        Box::new(LimitedNotifier {
+            name,
+            limit,
+            sent: sent.clone(),
+        })
+    };
+    let service = AlertService::new(vec![
+        notifier("unlimited", None),
+        notifier("small", Some(200_000)),
+        notifier("tiny", Some(10_000_000)),
+    ])
+    .with_upload_limits(BTreeMap::from([("tiny".to_string(), 1_000)]));
+    service
+        .send_print_failure_alert(1, "spaghetti", 80.0, 0.5, 0.5, 0.2, 0.2, Some(&png))
+        .unwrap();
+
+    let sent = sent.lock().unwrap();
+    let alert = |name| &sent.iter().find(|(n, _)| *n == name).unwrap().1;
+
+    let original = alert("unlimited").image.as_ref().unwrap();
+    assert_eq!(original.data, png);
+    assert_eq!(original.mime_type, "image/png");
+
+    let compressed = alert("small").image.as_ref().unwrap();
+    assert!(compressed.data.len() <= 200_000);
+    assert_eq!(compressed.mime_type, "image/jpeg");
+    assert!(compressed.filename.ends_with(".jpg"));
+    assert!(image::load_from_memory(&compressed.data).is_ok());
+
+    // The configured limit overrides the backend's, and is too small for any image
+    let dropped = alert("tiny");
+    assert!(dropped.image.is_none());
+    assert!(
+        dropped
+            .fields
+            .iter()
+            .any(|f| f.name == "Image" && f.value == "Omitted, too large to upload")
+    );
+}
+
+#[test]
+fn test_moonraker_websocket_subscriptions_and_reconnect() {
+    use print_guardian::PrinterService;
+    use print_guardian::moonraker::MoonrakerSocket;
+    use serde_json::{Value, json};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::{Duration, Instant};
+    use tungstenite::{Message, WebSocket};
+
+    fn request(ws: &mut WebSocket<TcpStream>, method: &str) -> Value {
+        loop {
+            if let Message::Text(text) = ws.read().unwrap() {
+                let request: Value = serde_json::from_str(&text).unwrap();
+                assert_eq!(request["method"], method);
+                return request;
+            }
+        }
+    }
+
+    fn respond(ws: &mut WebSocket<TcpStream>, request: &Value, result: Value) {
+        let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": result});
+        ws.send(Message::Text(response.to_string())).unwrap();
+    }
+
+    fn notify(ws: &mut WebSocket<TcpStream>, method: &str, params: Value) {
+        let notification = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        ws.send(Message::Text(notification.to_string())).unwrap();
+    }
+
+    fn subscribe(ws: &mut WebSocket<TcpStream>, state: &str) {
+        let query = request(ws, "printer.objects.query");
+        respond(
+            ws,
+            &query,
+            json!({"status": {"heaters": {"available_heaters": ["extruder", "heater_bed"]}}}),
+        );
+        let subscription = request(ws, "printer.objects.subscribe");
+        let objects = subscription["params"]["objects"].as_object().unwrap();
+        for object in [
+            "print_stats",
+            "virtual_sdcard",
+            "webhooks",
+            "toolhead",
+            "extruder",
+            "heater_bed",
+        ] {
+            assert!(objects.contains_key(object), "{} not subscribed", object);
+        }
+        respond(
+            ws,
+            &subscription,
+            json!({"eventtime": 1.0, "status": {
+                "print_stats": {"state": state, "filename": "benchy.gcode"},
+                "extruder": {"temperature": 25.0, "target": 0.0},
+            }}),
+        );
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:18794").expect("Failed to bind test server");
+    let server = std::thread::spawn(move || {
+        // First connection: Klippy ready, then an update and a Klippy disconnect
+        let mut ws = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+        let info = request(&mut ws, "server.info");
+        respond(&mut ws, &info, json!({"klippy_state": "ready"}));
+        subscribe(&mut ws, "standby");
+        notify(
+            &mut ws,
+            "notify_status_update",
+            json!([{"print_stats": {"state": "printing"}, "extruder": {"temperature": 215.5}}, 2.0]),
+        );
+        std::thread::sleep(Duration::from_millis(300));
+        notify(&mut ws, "notify_klippy_disconnected", Value::Null);
+        std::thread::sleep(Duration::from_millis(300));
+        ws.close(None).unwrap();
+        let _ = ws.flush();
+
+        // Second connection: Klippy starting up, subscribed once it is ready
+        let mut ws = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+        let info = request(&mut ws, "server.info");
+        respond(&mut ws, &info, json!({"klippy_state": "startup"}));
+        std::thread::sleep(Duration::from_millis(300));
+        notify(&mut ws, "notify_klippy_ready", Value::Null);
+        subscribe(&mut ws, "paused");
+        std::thread::sleep(Duration::from_secs(2));
+    });
+
+    let socket = MoonrakerSocket::new("http://127.0.0.1:18794").unwrap();
+    assert_eq!(socket.url(), "ws://127.0.0.1:18794/websocket");
+    assert_eq!(
+        MoonrakerSocket::new("https://printer.local/moonraker/")
+            .unwrap()
+            .url(),
+        "wss://printer.local/moonraker/websocket"
+    );
+    assert!(MoonrakerSocket::new("ftp://printer.local").is_err());
+    let state = socket.state();
+    // No HTTP server listens here, so statuses can only come from the WebSocket
+    let printer =
+        PrinterService::new("http://127.0.0.1:1".to_string()).with_live_state(state.clone());
+    socket.start().unwrap();
+
+    let wait_for =
+        |description: &str,
+         condition: &dyn Fn(&print_guardian::moonraker::PrinterState) -> bool| {
+            let deadline = Instant::now() + Duration::from_secs(15);
+            while !condition(&state.lock().unwrap()) {
+                assert!(
+                    Instant::now() < deadline,
+                    "Timed out waiting for {}",
+                    description
+                );
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        };
+
+    wait_for("status update", &|s| {
+        s.object("print_stats")
+            .is_some_and(|p| p["state"] == "printing")
+    });
+    {
+        let state = state.lock().unwrap();
+        assert!(state.is_ready());
+        // Updates only carry changed fields
+        assert_eq!(
+            state.object("print_stats").unwrap()["filename"],
+            "benchy.gcode"
+        );
+        assert_eq!(state.object("extruder").unwrap()["temperature"], 215.5);
+        assert_eq!(state.object("extruder").unwrap()["target"], 0.0);
+    }
+    let status = printer.get_printer_status().unwrap();
+    assert_eq!(
+        status.state(),
+        print_guardian::printer_status::PrintState::Printing
+    );
+    assert_eq!(status.heater("extruder").unwrap().temperature, 215.5);
+
+    wait_for("Klippy disconnect", &|s| s.klippy_state == "disconnected");
+    assert!(printer.get_printer_status().is_err());
+
+    wait_for("resubscription after reconnect", &|s| {
+        s.is_ready()
+            && s.object("print_stats")
+                .is_some_and(|p| p["state"] == "paused")
+    });
+    server.join().unwrap();
+}
+
+#[test]
+fn test_printer_status_from_recorded_moonraker_payloads() {
+    use print_guardian::moonraker::PrinterState;
+    use print_guardian::printer_status::{PrintState, PrinterStatus};
+
+    let fixture = |name: &str| -> serde_json::Value {
+        let path = format!("tests/fixtures/moonraker/{}.json", name);
+        serde_json::from_str(&std::fs::read_to_string(&path).expect("Fixture missing")).unwrap()
+    };
+
+    let printing = fixture("objects_query_printing");
+    let status = PrinterStatus::from_query_response(&printing).unwrap();
+    assert_eq!(status.state(), PrintState::Printing);
+    assert!(status.state().is_active());
+    assert_eq!(
+        status.print_stats.filename(),
+        Some("Voron_Design_Cube_v7_0.2mm_ABS_1h12m.gcode")
+    );
+    assert!((status.print_stats.print_duration - 1710.57).abs() < 0.01);
+    assert!((status.print_stats.total_duration - 1843.21).abs() < 0.01);
+    assert!((status.print_stats.filament_used - 2316.42).abs() < 0.01);
+    assert!((status.progress() - 0.4213).abs() < 0.0001);
+    assert_eq!(status.layer(), Some((64, 150)));
+    assert_eq!(status.klippy_message(), Some("Printer is ready"));
+    assert_eq!(status.toolhead.as_ref().unwrap().homed_axes, "xyz");
+    // Every available heater, including generic ones
+    assert_eq!(
+        status.heaters.keys().collect::<Vec<_>>(),
+        vec!["extruder", "heater_bed", "heater_generic chamber"]
+    );
+    assert_eq!(status.heater("extruder").unwrap().target, 245.0);
+    assert_eq!(
+        status.heater("heater_generic chamber").unwrap().temperature,
+        48.31
+    );
+
+    let shutdown = PrinterStatus::from_query_response(&fixture("objects_query_shutdown")).unwrap();
+    assert_eq!(shutdown.state(), PrintState::Error);
+    assert_eq!(shutdown.print_stats.filename(), None);
+    assert_eq!(shutdown.layer(), None);
+    assert_eq!(shutdown.webhooks.state, "shutdown");
+    assert!(
+        shutdown
+            .klippy_message()
+            .unwrap()
+            .starts_with("MCU 'mcu' shutdown: Timer too close")
+    );
+    assert!(shutdown.toolhead.is_none());
+    assert!(shutdown.heaters.is_empty());
+
+    // Status updates only carry changed fields and are merged into the live model
+    let mut state = PrinterState::default();
+    state.apply_update(&printing["result"]["status"]);
+    state.apply_update(&fixture("notify_status_update")["params"][0]);
+    let updated = state.status().unwrap();
+    assert_eq!(updated.state(), PrintState::Printing);
+    assert_eq!(
+        updated.print_stats.filename(),
+        status.print_stats.filename()
+    );
+    assert_eq!(updated.layer(), Some((65, 150)));
+    assert!((updated.progress() - 0.4221).abs() < 0.0001);
+    assert_eq!(updated.heater("extruder").unwrap().temperature, 245.12);
+    assert_eq!(updated.heater("extruder").unwrap().target, 245.0);
+    assert_eq!(updated.heater("heater_bed"), status.heater("heater_bed"));
+
+    // States added by future Klipper versions don't break parsing
+    let future = serde_json::json!({"print_stats": {"state": "resuming"}});
+    assert_eq!(
+        PrinterStatus::from_status(&future).unwrap().state(),
+        PrintState::Unknown
+    );
+    let malformed = serde_json::json!({"print_stats": {"state": 5}});
+    assert!(PrinterStatus::from_status(&malformed).is_err());
+    assert!(PrinterStatus::from_query_response(&serde_json::json!({"error": {}})).is_err());
+}
+
+#[test]
+fn test_moonraker_authentication() {
+    use print_guardian::PrinterService;
+    use print_guardian::error::PrinterError;
+    use print_guardian::moonraker::MoonrakerSocket;
+    use print_guardian::moonraker_auth::{MoonrakerAuth, MoonrakerCredentials};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    // Local stand-in for Moonraker with [authorization] enabled, expiring the first access token
+    let server = tiny_http::Server::http("127.0.0.1:18795").expect("Failed to bind test server");
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let log = requests.clone();
+    std::thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let header = |name: &'static str| {
+                request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv(name))
+                    .map(|h| h.value.to_string())
+            };
+            let credentials = header("X-Api-Key").or_else(|| header("Authorization"));
+            let url = request.url().to_string();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            log.lock()
+                .unwrap()
+                .push(format!("{} {:?}", url, credentials));
+
+            let (status, response) = match url.split('?').next().unwrap() {
+                "/access/login" if body.contains("\"password\":\"hunter2\"") => (
+                    200,
+                    r#"{"result": {"username": "guardian", "token": "access-1", "refresh_token": "refresh-1", "action": "user_logged_in", "source": "moonraker"}}"#,
+                ),
+                "/access/login" => (
+                    401,
+                    r#"{"error": {"code": 401, "message": "Invalid Password"}}"#,
+                ),
+                "/access/refresh_jwt" if body.contains("refresh-1") => (
+                    200,
+                    r#"{"result": {"username": "guardian", "token": "access-2", "source": "moonraker", "action": "user_jwt_refresh"}}"#,
+                ),
+                _ => match credentials.as_deref() {
+                    Some("secret") | Some("Bearer access-2") => (
+                        200,
+                        r#"{"result": {"status": {"print_stats": {"state": "printing"}}}}"#,
+                    ),
+                    Some("Bearer access-1") => (
+                        401,
+                        r#"{"error": {"code": 401, "message": "JWT has expired"}}"#,
+                    ),
+                    _ => (403, r#"{"error": {"code": 403, "message": "Forbidden"}}"#),
+                },
+            };
+            request
+                .respond(tiny_http::Response::from_string(response).with_status_code(status))
+                .unwrap();
+        }
+    });
+
+    let api_url = "http://127.0.0.1:18795";
+    let is_auth_failure = |e: &PrinterError| matches!(e, PrinterError::AuthenticationFailed { .. });
+
+    let printer = |credentials| {
+        PrinterService::new(api_url.to_string()).with_auth(MoonrakerAuth::new(api_url, credentials))
+    };
+
+    // Trusted clients are disabled, so unauthenticated requests are rejected
+    let error = printer(MoonrakerCredentials::None)
+        .get_printer_status()
+        .unwrap_err();
+    assert!(is_auth_failure(&error));
+
+    let with_key = printer(MoonrakerCredentials::ApiKey("secret".to_string()));
+    assert!(with_key.get_printer_status().is_ok());
+    assert!(with_key.pause_print().is_ok());
+    let error = printer(MoonrakerCredentials::ApiKey("wrong".to_string()))
+        .get_printer_status()
+        .unwrap_err();
+    assert!(is_auth_failure(&error));
+
+    // The expired access token is refreshed and the request retried
+    let logged_in = printer(MoonrakerCredentials::Login {
+        username: "guardian".to_string(),
+        password: "hunter2".to_string(),
+    });
+    requests.lock().unwrap().clear();
+    assert!(logged_in.get_printer_status().is_ok());
+    assert!(logged_in.pause_print().is_ok());
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![
+            "/access/login None",
+            "/printer/objects/query?webhooks&print_stats&virtual_sdcard&toolhead&heaters&extruder&heater_bed Some(\"Bearer access-1\")",
+            "/access/refresh_jwt None",
+            "/printer/objects/query?webhooks&print_stats&virtual_sdcard&toolhead&heaters&extruder&heater_bed Some(\"Bearer access-2\")",
+            "/printer/print/pause Some(\"Bearer access-2\")",
+        ]
+    );
+
+    let error = printer(MoonrakerCredentials::Login {
+        username: "guardian".to_string(),
+        password: "wrong".to_string(),
+    })
+    .get_printer_status()
+    .unwrap_err();
+    assert!(is_auth_failure(&error));
+
+    // WebSocket connections are authorized with a oneshot token requested with the API key
+    struct UriRecorder(Arc<Mutex<String>>);
+
+    impl tungstenite::handshake::server::Callback for UriRecorder {
+        fn on_request(
+            self,
+            request: &tungstenite::handshake::server::Request,
+            response: tungstenite::handshake::server::Response,
+        ) -> Result<
+            tungstenite::handshake::server::Response,
+            tungstenite::handshake::server::ErrorResponse,
+        > {
+            *self.0.lock().unwrap() = request.uri().to_string();
+            Ok(response)
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:18796").expect("Failed to bind test server");
+    let websocket_uri = Arc::new(Mutex::new(String::new()));
+    let uri = websocket_uri.clone();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut head = String::new();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        while reader.read_line(&mut head).unwrap() > 2 && !head.ends_with("\r\n\r\n") {}
+        assert!(head.starts_with("GET /access/oneshot_token"));
+        assert!(head.to_lowercase().contains("x-api-key: secret"));
+        let body = r#"{"result": "oneshot-1"}"#;
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        drop(stream);
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut ws = tungstenite::accept_hdr(stream, UriRecorder(uri)).unwrap();
+        loop {
+            let Ok(message) = ws.read() else { break };
+            if let tungstenite::Message::Text(text) = message {
+                let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {"klippy_state": "startup"}});
+                ws.send(tungstenite::Message::Text(response.to_string()))
+                    .unwrap();
+            }
+        }
+    });
+
+    let socket = MoonrakerSocket::new("http://127.0.0.1:18796")
+        .unwrap()
+        .with_auth(MoonrakerAuth::new(
+            "http://127.0.0.1:18796",
+            MoonrakerCredentials::ApiKey("secret".to_string()),
+        ));
+    let state = socket.state();
+    socket.start().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while state.lock().unwrap().klippy_state != "startup" {
+        assert!(Instant::now() < deadline, "WebSocket did not connect");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(*websocket_uri.lock().unwrap(), "/websocket?token=oneshot-1");
+}
+
+#[test]
+fn test_typed_errors_and_source_chains() {
+    use print_guardian::error::{
+        AlertError, ErrorChain, ImageError, NetworkError, PrintGuardianError, PrinterError,
+    };
+    use print_guardian::{Alert, AlertService, ImageFetcher, Notifier, PrinterService};
+    use std::error::Error;
+
+    // Moonraker answering with an error body, first while Klippy restarts and then for good
+    let server = tiny_http::Server::http("127.0.0.1:18797").expect("Failed to bind test server");
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let (status, message) = match request.url() {
+                "/printer/print/pause" => (503, "Klippy Disconnected"),
+                _ => (400, "No active print"),
+            };
+            let body = serde_json::json!({"error": {"code": status, "message": message}});
+            request
+                .respond(
+                    tiny_http::Response::from_string(body.to_string()).with_status_code(status),
+                )
+                .unwrap();
+        }
+    });
+
+    let printer = PrinterService::new("http://127.0.0.1:18797".to_string());
+    match printer.pause_print().unwrap_err() {
+        PrinterError::ApiError {
+            endpoint,
+            status,
+            message,
+        } => {
+            assert_eq!(endpoint, "printer/print/pause");
+            assert_eq!(status, 503);
+            assert_eq!(message, "Klippy Disconnected");
+        }
+        e => panic!("Unexpected error: {:?}", e),
+    }
+    assert!(printer.pause_print().unwrap_err().is_transient());
+    let error = printer.cancel_print().unwrap_err();
+    assert!(!error.is_transient() && !error.is_fatal());
+
+    // Nothing listens here, the connection error is kept as the source
+    let unreachable = PrinterService::new("http://127.0.0.1:18798".to_string());
+    let error = unreachable.get_printer_status().unwrap_err();
+    let PrinterError::ConnectionFailed { source, .. } = &error else {
+        panic!("Unexpected error: {:?}", error);
+    };
+    assert!(matches!(source, NetworkError::RequestFailed { .. }));
+    assert!(
+        error
+            .source()
+            .is_some_and(|source| source.is::<NetworkError>())
+    );
+    assert!(error.is_transient());
+    let chain = ErrorChain(&error).to_string();
+    assert!(chain.starts_with("Failed to connect to printer at 'http://127.0.0.1:18798': Network request to 'http://127.0.0.1:18798/printer/objects/query"));
+    let error = PrintGuardianError::from(error);
+    assert!(error.is_transient() && !error.is_fatal());
+
+    let error = ImageFetcher::bytes_to_darknet_image(b"not an image").unwrap_err();
+    assert!(matches!(error, ImageError::InvalidFormat { .. }));
+    assert!(
+        error
+            .source()
+            .is_some_and(|source| source.is::<image::ImageError>())
+    );
+
+    // Every failed notifier is reported with its own error
+    struct FailingNotifier(&'static str);
+
+    impl Notifier for FailingNotifier {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn send(&self, _alert: &Alert) -> anyhow::Result<()> {
+            anyhow::bail!("{} is down", self.0)
+        }
+    }
+
+    let service = AlertService::new(vec![
+        Box::new(FailingNotifier("first")),
+        Box::new(FailingNotifier("second")),
+    ]);
+    match service.send_system_recovery_alert().unwrap_err() {
+        AlertError::DeliveryFailed {
+            alert_kind,
+            failures,
+        } => {
+            assert_eq!(alert_kind, "recovery");
+            let failures: Vec<_> = failures
+                .iter()
+                .map(|(name, e)| format!("{}: {}", name, e))
+                .collect();
+            assert_eq!(failures, ["first: first is down", "second: second is down"]);
+        }
+        e => panic!("Unexpected error: {:?}", e),
+    }
+}
+
+#[test]
+fn test_pause_verification_and_escalation() {
+    use print_guardian::printer::{PauseEscalation, PauseOutcome, PauseVerification};
+    use print_guardian::{Alert, AlertService, Notifier, PrinterService};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    // Klipper stand-in that ignores or rejects the given number of pause requests
+    struct Printer {
+        state: &'static str,
+        klippy: &'static str,
+        ignored_pauses: u32,
+        failed_pauses: u32,
+        ignore_emergency_stop: bool,
+        requests: Vec<String>,
+    }
+
+    let printer_state = Arc::new(Mutex::new(Printer {
+        state: "printing",
+        klippy: "ready",
+        ignored_pauses: 0,
+        failed_pauses: 0,
+        ignore_emergency_stop: false,
+        requests: Vec::new(),
+    }));
+    let server = tiny_http::Server::http("127.0.0.1:18799").expect("Failed to bind test server");
+    let shared = printer_state.clone();
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let mut printer = shared.lock().unwrap();
+            let path = request.url().split('?').next().unwrap().to_string();
+            let mut status = 200;
+            match path.as_str() {
+                "/printer/print/pause" if printer.failed_pauses > 0 => {
+                    printer.failed_pauses -= 1;
+                    status = 500;
+                }
+                "/printer/print/pause" if printer.ignored_pauses > 0 => printer.ignored_pauses -= 1,
+                "/printer/print/pause" => printer.state = "paused",
+                "/printer/print/cancel" => printer.state = "cancelled",
+                "/printer/emergency_stop" if printer.ignore_emergency_stop => {}
+                "/printer/emergency_stop" => {
+                    printer.state = "error";
+                    printer.klippy = "shutdown";
+                }
+                _ => {}
+            }
+            if path != "/printer/objects/query" {
+                printer.requests.push(path);
+            }
+            let body = serde_json::json!({"result": {"status": {
+                "print_stats": {"state": printer.state},
+                "webhooks": {"state": printer.klippy},
+            }}});
+            request
+                .respond(tiny_http::Response::from_string(body.to_string()).with_status_code(status))
+                .unwrap();
+        }
+    });
+
+    let printer = PrinterService::new("http://127.0.0.1:18799".to_string());
+    let verification = |escalation| PauseVerification {
+        timeout: Duration::from_millis(300),
+        attempts: 2,
+        escalation,
+    };
+    let run = |ignored_pauses, escalation| {
+        {
+            let mut state = printer_state.lock().unwrap();
+            state.state = "printing";
+            state.klippy = "ready";
+            state.ignored_pauses = ignored_pauses;
+            state.requests.clear();
+        }
+        let outcome = printer
+            .pause_print_verified(&verification(escalation))
+            .expect("Pause failed");
+        (outcome, printer_state.lock().unwrap().requests.clone())
+    };
+
+    // The first pause is ignored, the retry takes effect
+    let (outcome, requests) = run(1, PauseEscalation::EmergencyStop);
+    assert_eq!(outcome, PauseOutcome::Paused { attempts: 2 });
+    assert_eq!(requests, ["/printer/print/pause", "/printer/print/pause"]);
+    // Retried pause requests are not counted as separate pauses
+    let metrics = print_guardian::metrics::get().encode().unwrap();
+    assert!(!metrics.contains("print_guardian_pauses_total{"));
+
+    let (outcome, requests) = run(2, PauseEscalation::None);
+    assert_eq!(outcome, PauseOutcome::StillPrinting);
+    assert!(!outcome.is_halted());
+    assert_eq!(requests.len(), 2);
+
+    let (outcome, requests) = run(2, PauseEscalation::Cancel);
+    assert_eq!(outcome, PauseOutcome::Cancelled);
+    assert_eq!(requests.last().unwrap(), "/printer/print/cancel");
+
+    let (outcome, requests) = run(2, PauseEscalation::EmergencyStop);
+    assert_eq!(outcome, PauseOutcome::EmergencyStopped);
+    assert_eq!(requests.last().unwrap(), "/printer/emergency_stop");
+
+    // An accepted emergency stop only counts once Klippy reports a shutdown
+    printer_state.lock().unwrap().ignore_emergency_stop = true;
+    let (outcome, requests) = run(2, PauseEscalation::EmergencyStop);
+    assert_eq!(outcome, PauseOutcome::StillPrinting);
+    assert_eq!(requests.last().unwrap(), "/printer/emergency_stop");
+    printer_state.lock().unwrap().ignore_emergency_stop = false;
+
+    // A rejected pause request is retried without waiting for the printer
+    printer_state.lock().unwrap().failed_pauses = 1;
+    let started = std::time::Instant::now();
+    let (outcome, requests) = run(0, PauseEscalation::None);
+    assert_eq!(outcome, PauseOutcome::Paused { attempts: 2 });
+    assert_eq!(requests.len(), 2);
+    assert!(started.elapsed() < Duration::from_millis(300));
+
+    assert_eq!(
+        "m112".parse::<PauseEscalation>(),
+        Ok(PauseEscalation::EmergencyStop)
+    );
+    assert!("stop".parse::<PauseEscalation>().is_err());
+
+    // The pause alert reports the outcome
+    struct RecordingNotifier(Arc<Mutex<Vec<Alert>>>);
+
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn send(&self, alert: &Alert) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let service = AlertService::new(vec![Box::new(RecordingNotifier(sent.clone()))]);
+    service
+        .send_print_pause_alert(4, 3, &PauseOutcome::StillPrinting, None)
+        .unwrap();
+    service
+        .send_print_pause_alert(4, 3, &PauseOutcome::Paused { attempts: 2 }, None)
+        .unwrap();
+    let sent = sent.lock().unwrap();
+    assert_eq!(sent[0].title, "CRITICAL: Printer Did Not Pause");
+    assert!(sent[0].description.contains("is still printing"));
+    assert!(
+        sent[0]
+            .fields
+            .iter()
+            .any(|f| f.name == "Outcome" && f.value == "Still printing")
+    );
+    assert_eq!(sent[1].title, "Print Paused Due to Multiple Failures");
+    assert!(
+        sent[1]
+            .fields
+            .iter()
+            .any(|f| f.name == "Pause Attempts" && f.value == "2")
+    );
+}